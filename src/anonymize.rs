@@ -0,0 +1,416 @@
+// Recording anonymizer: scrubs an rrweb recording so it can be attached to bug reports or
+// checked in as a fixture. Node ids and tree structure are left untouched, so compiling the
+// scrubbed recording produces the same actions and selectors as the original.
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::diagnostics::{Code, CompileError, Diagnostic};
+use crate::redact;
+
+#[derive(Debug, Default)]
+pub struct AnonymizeStats {
+    pub inputs_masked: usize,
+    pub text_nodes_scrubbed: usize,
+    pub attributes_scrubbed: usize,
+    pub urls_stripped: usize,
+}
+
+// Input types whose value isn't typed by the user
+const NON_TEXT_INPUT_TYPES: &[&str] = &[
+    "button", "submit", "reset", "image", "hidden", "checkbox", "radio", "file", "color", "range",
+];
+
+// Attributes holding URLs whose query strings may carry tokens
+const URL_ATTRIBUTES: &[&str] = &["href", "src", "action", "formaction", "poster", "data-src"];
+
+// Query parameter names that usually carry credentials or session state
+const SENSITIVE_QUERY_PARAMS: &[&str] = &[
+    "token",
+    "access_token",
+    "id_token",
+    "refresh_token",
+    "auth",
+    "authorization",
+    "code",
+    "key",
+    "api_key",
+    "apikey",
+    "secret",
+    "password",
+    "session",
+    "sessionid",
+    "sid",
+    "sig",
+    "signature",
+    "state",
+    "email",
+];
+
+pub fn anonymize_file(input_path: &str, output_path: &str) -> Result<AnonymizeStats, CompileError> {
+    let content = fs::read_to_string(input_path).map_err(|source| CompileError::Io {
+        path: PathBuf::from(input_path),
+        source,
+    })?;
+    if !crate::is_event_list(&content) {
+        return Err(CompileError::Recording(Diagnostic::error(
+            Code::InvalidRecording,
+            format!(
+                "{} is not an rrweb recording; only rrweb recordings can be anonymized",
+                input_path
+            ),
+        )));
+    }
+    // Work on raw JSON so fields the compiler doesn't model survive the round trip
+    let mut events: Vec<Value> =
+        serde_json::from_str(&content).map_err(|source| CompileError::InvalidRecording {
+            name: input_path.to_string(),
+            source,
+        })?;
+    let stats = anonymize_events(&mut events);
+
+    let output = Value::Array(events).to_string();
+    fs::write(output_path, output).map_err(|source| CompileError::Io {
+        path: PathBuf::from(output_path),
+        source,
+    })?;
+    Ok(stats)
+}
+
+// Scrub rrweb events in place
+pub fn anonymize_events(events: &mut [Value]) -> AnonymizeStats {
+    let mut stats = AnonymizeStats::default();
+    // Element id -> whether its value is text the user typed, for every element seen so far
+    let mut typed_values: HashMap<i64, bool> = HashMap::new();
+
+    for event in events.iter_mut() {
+        let event_type = event.get("type").and_then(|v| v.as_i64());
+        let Some(data) = event.get_mut("data") else {
+            continue;
+        };
+        match event_type {
+            Some(2) => {
+                // Full Snapshot
+                if let Some(node) = data.get_mut("node") {
+                    scrub_node(node, &mut typed_values, &mut stats);
+                }
+            }
+            Some(3) => scrub_incremental(data, &mut typed_values, &mut stats),
+            Some(4) => {
+                // Meta
+                if let Some(Value::String(href)) = data.get_mut("href") {
+                    if let Some(stripped) = strip_url_tokens(href) {
+                        *href = stripped;
+                        stats.urls_stripped += 1;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    stats
+}
+
+fn scrub_incremental(
+    data: &mut Value,
+    typed_values: &mut HashMap<i64, bool>,
+    stats: &mut AnonymizeStats,
+) {
+    match data.get("source").and_then(|v| v.as_i64()) {
+        Some(0) => {
+            // Mutation
+            if let Some(adds) = data.get_mut("adds").and_then(|v| v.as_array_mut()) {
+                for addition in adds {
+                    if let Some(node) = addition.get_mut("node") {
+                        scrub_node(node, typed_values, stats);
+                    }
+                }
+            }
+            if let Some(texts) = data.get_mut("texts").and_then(|v| v.as_array_mut()) {
+                for text_change in texts {
+                    if let Some(Value::String(text)) = text_change.get_mut("value") {
+                        if let Some(scrubbed) = scrub_text(text) {
+                            *text = scrubbed;
+                            stats.text_nodes_scrubbed += 1;
+                        }
+                    }
+                }
+            }
+            if let Some(changes) = data.get_mut("attributes").and_then(|v| v.as_array_mut()) {
+                for change in changes {
+                    // Elements the recording never showed are treated as text fields
+                    let typed_value = change
+                        .get("id")
+                        .and_then(Value::as_i64)
+                        .and_then(|id| typed_values.get(&id).copied())
+                        .unwrap_or(true);
+                    if let Some(attrs) =
+                        change.get_mut("attributes").and_then(|v| v.as_object_mut())
+                    {
+                        for (key, value) in attrs.iter_mut() {
+                            scrub_attribute(key, value, typed_value, stats);
+                        }
+                    }
+                }
+            }
+        }
+        Some(5) => {
+            // Input: every typed value is masked, sensitive or not. Options chosen in selects
+            // and the `on` of checkboxes and radio buttons aren't typed, so they are kept.
+            let typed_value = data
+                .get("id")
+                .and_then(Value::as_i64)
+                .and_then(|id| typed_values.get(&id).copied())
+                .unwrap_or(true);
+            if let Some(Value::String(text)) = data.get_mut("text") {
+                if typed_value && !text.is_empty() {
+                    *text = mask(text);
+                    stats.inputs_masked += 1;
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn scrub_node(node: &mut Value, typed_values: &mut HashMap<i64, bool>, stats: &mut AnonymizeStats) {
+    let typed_value = node.get("tagName").and_then(Value::as_str).map(|tag| {
+        let input_type = node
+            .get("attributes")
+            .and_then(|attrs| attrs.get("type"))
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        holds_typed_value(tag, input_type)
+    });
+    if let (Some(id), Some(typed_value)) = (node.get("id").and_then(Value::as_i64), typed_value) {
+        typed_values.insert(id, typed_value);
+    }
+    if let Some(Value::String(text)) = node.get_mut("textContent") {
+        if let Some(scrubbed) = scrub_text(text) {
+            *text = scrubbed;
+            stats.text_nodes_scrubbed += 1;
+        }
+    }
+    if let Some(attrs) = node.get_mut("attributes").and_then(|v| v.as_object_mut()) {
+        for (key, value) in attrs.iter_mut() {
+            scrub_attribute(key, value, typed_value.unwrap_or(false), stats);
+        }
+    }
+    if let Some(children) = node.get_mut("childNodes").and_then(|v| v.as_array_mut()) {
+        for child in children {
+            scrub_node(child, typed_values, stats);
+        }
+    }
+}
+
+// Whether an element's value is text the user typed: textareas and text-like inputs. Buttons,
+// options, hidden inputs and choices hold values the page set, which replay needs as they are.
+fn holds_typed_value(tag: &str, input_type: &str) -> bool {
+    match tag.to_ascii_lowercase().as_str() {
+        "textarea" => true,
+        "input" => !NON_TEXT_INPUT_TYPES.contains(&input_type.to_ascii_lowercase().as_str()),
+        _ => false,
+    }
+}
+
+fn scrub_attribute(key: &str, value: &mut Value, typed_value: bool, stats: &mut AnonymizeStats) {
+    let Value::String(text) = value else {
+        return;
+    };
+    if key == "value" && typed_value {
+        // Pre-filled form values are input values too
+        if !text.is_empty() {
+            *text = mask(text);
+            stats.inputs_masked += 1;
+        }
+    } else if key == "value" {
+        // Values the page set are kept for replay, but PII in them is still scrubbed
+        if let Some(scrubbed) = scrub_text(text) {
+            *text = scrubbed;
+            stats.attributes_scrubbed += 1;
+        }
+    } else if URL_ATTRIBUTES.contains(&key) {
+        if let Some(stripped) = strip_url_tokens(text) {
+            *text = stripped;
+            stats.urls_stripped += 1;
+        }
+    } else if key != "class" && key != "id" && !key.starts_with("data-") {
+        // Selector-relevant attributes are kept so selector generation is unaffected
+        if let Some(scrubbed) = scrub_text(text) {
+            *text = scrubbed;
+            stats.attributes_scrubbed += 1;
+        }
+    }
+}
+
+// Mask like rrweb's maskInputOptions does: one `*` per character, so lengths are preserved
+fn mask(value: &str) -> String {
+    "*".repeat(value.chars().count())
+}
+
+// Replace PII-looking words in free text. Returns None when nothing matched.
+fn scrub_text(text: &str) -> Option<String> {
+    let mut words: Vec<String> = text.split(' ').map(String::from).collect();
+    let mut changed = false;
+
+    // Phone and card numbers are often written across several words ("+1 (555) 123-4567"),
+    // so runs of number-like words are checked as a whole first
+    let is_number_like = |word: &str| {
+        word.chars().any(|c| c.is_ascii_digit())
+            && word
+                .trim_end_matches(['.', ','])
+                .chars()
+                .all(|c| c.is_ascii_digit() || matches!(c, '+' | '(' | ')' | '-' | '.'))
+    };
+    let mut run_start = 0;
+    while run_start < words.len() {
+        if !is_number_like(&words[run_start]) {
+            run_start += 1;
+            continue;
+        }
+        let mut run_end = run_start;
+        while run_end < words.len() && is_number_like(&words[run_end]) {
+            run_end += 1;
+        }
+        let joined = words[run_start..run_end].join(" ");
+        let joined = joined.trim_end_matches(['.', ',']);
+        if redact::is_phone(joined) || redact::is_card_number(joined) {
+            for word in &mut words[run_start..run_end] {
+                *word = word
+                    .chars()
+                    .map(|c| if c.is_ascii_digit() { '*' } else { c })
+                    .collect();
+            }
+            changed = true;
+        }
+        run_start = run_end;
+    }
+
+    for word in words.iter_mut() {
+        let trimmed = word
+            .trim_matches(|c: char| {
+                matches!(c, ',' | ';' | ':' | '(' | ')' | '"' | '\'' | '<' | '>')
+            })
+            .trim_end_matches('.');
+        if trimmed.is_empty() || redact::classify_value(trimmed).is_none() {
+            continue;
+        }
+        *word = word.replacen(trimmed, &mask(trimmed), 1);
+        changed = true;
+    }
+
+    changed.then(|| words.join(" "))
+}
+
+// Drop query and fragment parameters that look like credentials. Returns None when unchanged.
+fn strip_url_tokens(url: &str) -> Option<String> {
+    let (without_fragment, fragment) = match url.split_once('#') {
+        Some((base, fragment)) => (base, Some(fragment)),
+        None => (url, None),
+    };
+    let (base, query) = match without_fragment.split_once('?') {
+        Some((base, query)) => (base, Some(query)),
+        None => (without_fragment, None),
+    };
+
+    let mut changed = false;
+    let mut filter_params = |params: &str| -> String {
+        params
+            .split('&')
+            .filter(|pair| {
+                let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+                let sensitive = SENSITIVE_QUERY_PARAMS
+                    .contains(&name.to_ascii_lowercase().as_str())
+                    || redact::classify_value(value).is_some();
+                changed |= sensitive;
+                !sensitive
+            })
+            .collect::<Vec<_>>()
+            .join("&")
+    };
+
+    let mut result = base.to_string();
+    if let Some(query) = query {
+        let kept = filter_params(query);
+        if !kept.is_empty() {
+            result.push('?');
+            result.push_str(&kept);
+        }
+    }
+    if let Some(fragment) = fragment {
+        // Fragments are only parameter lists in OAuth-style redirects; plain anchors are kept
+        let kept = if fragment.contains('=') {
+            filter_params(fragment)
+        } else {
+            fragment.to_string()
+        };
+        if !kept.is_empty() {
+            result.push('#');
+            result.push_str(&kept);
+        }
+    }
+    changed.then_some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{element, input, snapshot};
+    use serde_json::json;
+
+    fn value_of(events: &[Value], id: i64) -> &str {
+        fn find(node: &Value, id: i64) -> Option<&Value> {
+            if node.get("id").and_then(Value::as_i64) == Some(id) {
+                return Some(node);
+            }
+            node.get("childNodes")?
+                .as_array()?
+                .iter()
+                .find_map(|child| find(child, id))
+        }
+        find(&events[0]["data"]["node"], id).unwrap()["attributes"]["value"]
+            .as_str()
+            .unwrap()
+    }
+
+    #[test]
+    fn masks_typed_values_and_keeps_values_the_page_set() {
+        let mut events = vec![
+            snapshot(
+                1000,
+                json!([
+                    element(10, "input", json!({"type": "text", "value": "Jane"})),
+                    element(11, "textarea", json!({"value": "Call me"})),
+                    element(12, "input", json!({"type": "submit", "value": "Pay now"})),
+                    element(13, "input", json!({"type": "hidden", "value": "cart-42"})),
+                    element(14, "option", json!({"value": "de"})),
+                    element(15, "select", json!({})),
+                ]),
+            ),
+            input(1100, 10, "Jane Doe"),
+            input(1200, 15, "de"),
+        ];
+        let stats = anonymize_events(&mut events);
+
+        assert_eq!(value_of(&events, 10), "****");
+        assert_eq!(value_of(&events, 11), "*******");
+        assert_eq!(value_of(&events, 12), "Pay now");
+        assert_eq!(value_of(&events, 13), "cart-42");
+        assert_eq!(value_of(&events, 14), "de");
+        assert_eq!(events[1]["data"]["text"], "********");
+        assert_eq!(events[2]["data"]["text"], "de");
+        assert_eq!(stats.inputs_masked, 3);
+    }
+
+    #[test]
+    fn scrubs_phone_numbers_but_not_dates_in_text() {
+        assert_eq!(scrub_text("Delivered on 2024-03-15"), None);
+        assert_eq!(scrub_text("Due 15/03/2024."), None);
+        assert_eq!(
+            scrub_text("Call +1 (555) 123-4567 today").as_deref(),
+            Some("Call +* (***) ***-**** today")
+        );
+    }
+}
@@ -0,0 +1,521 @@
+// Project configuration: `imitator.toml`.
+//
+// The file is found by walking up from the current directory, or given with `--config`.
+// Command-line flags take precedence over it. Every key is optional:
+//
+//   [output]
+//   dir = "./output"                  # where project folders are created, relative to
+//                                     # this file
+//   target = "stagehand"              # stagehand | playwright-test | cypress | selenium-python |
+//                                     # puppeteer | chrome-recorder | selenium-ide |
+//                                     # gherkin
+//   template = "stagehand"            # template pack: built-in name, directory or archive
+//                                     # (default: the target's built-in pack)
+//
+//   [selectors]
+//   priority = ["id", "data-testid", "data-cy", "name", "class"]
+//   test_attributes = ["data-qa", "data-test"]  # tried right after data-testid and data-cy
+//   deny_ids = ["ember*", "react-select-*"]     # generated ids never used in selectors
+//   deny_classes = ["css-*", "sc-*"]            # generated class names, likewise
+//
+//   [redaction]
+//   sensitive_fields = ["member number"]  # name/id/label fragments that are always redacted
+//   ignore_fields = ["search"]            # fragments that are never redacted
+//   mask_characters = "*•●"               # characters recorders use to mask input values
+//
+//   [emit]
+//   mode = "locator"                  # locator | act | hybrid
+//   params = "sensitive"              # sensitive | all
+//   params_source = "env"             # env | json | csv
+//   cookie_banner = "Accept|Agree|Allow|Got it"  # button name regex; "" skips dismissal
+//
+//   [llm]
+//   model = "google/gemini-2.0-flash" # model the generated project's Stagehand config uses
+//   api_key_env = "GOOGLE_API_KEY"    # environment variable holding its API key
+//
+//   [[domain]]                        # overrides for recordings that start on matching hosts
+//   match = "*.example.com"
+//   [domain.selectors]
+//   priority = ["data-qa", "id"]
+//
+// `[[domain]]` sections may override `selectors`, `redaction`, `emit` and `llm`. Matching
+// sections apply in file order, so later ones win. `match`, `deny_ids` and `deny_classes`
+// take `*` wildcards.
+
+use serde::Deserialize;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::params::{ParamMode, ParamSource};
+use crate::redact::RedactionRules;
+use crate::templates::wildcard_match;
+use crate::{EmitMode, SelectorRules, SelectorStrategy};
+
+pub const CONFIG_FILE: &str = "imitator.toml";
+
+const DEFAULT_COOKIE_BANNER: &str = "Accept|Agree|Allow|Got it";
+const DEFAULT_LLM_MODEL: &str = "google/gemini-2.0-flash";
+const DEFAULT_LLM_API_KEY_ENV: &str = "GOOGLE_API_KEY";
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub output: OutputConfig,
+    pub selectors: SelectorConfig,
+    pub redaction: RedactionConfig,
+    pub emit: EmitConfig,
+    pub llm: LlmConfig,
+    #[serde(rename = "domain")]
+    pub domains: Vec<DomainConfig>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutputConfig {
+    pub dir: Option<PathBuf>,
+    pub target: Option<String>,
+    pub template: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SelectorConfig {
+    pub priority: Option<Vec<String>>,
+    pub test_attributes: Option<Vec<String>>,
+    pub deny_ids: Option<Vec<String>>,
+    pub deny_classes: Option<Vec<String>>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RedactionConfig {
+    pub sensitive_fields: Option<Vec<String>>,
+    pub ignore_fields: Option<Vec<String>>,
+    pub mask_characters: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EmitConfig {
+    pub mode: Option<EmitMode>,
+    pub params: Option<ParamMode>,
+    pub params_source: Option<ParamSource>,
+    pub cookie_banner: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LlmConfig {
+    pub model: Option<String>,
+    pub api_key_env: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DomainConfig {
+    #[serde(rename = "match")]
+    pub pattern: String,
+    #[serde(default)]
+    pub selectors: SelectorConfig,
+    #[serde(default)]
+    pub redaction: RedactionConfig,
+    #[serde(default)]
+    pub emit: EmitConfig,
+    #[serde(default)]
+    pub llm: LlmConfig,
+}
+
+// Settings for one recording: the config's top level, matching domain sections and
+// command-line flags merged in that order. Unset keys fall back to built-in defaults.
+#[derive(Debug, Default, Clone)]
+pub struct Settings {
+    pub selectors: SelectorConfig,
+    pub redaction: RedactionConfig,
+    pub emit: EmitConfig,
+    pub llm: LlmConfig,
+}
+
+// Later layers replace individual keys, not whole sections
+macro_rules! merge_fields {
+    ($target:expr, $layer:expr, $($field:ident),+) => {
+        $(
+            if $layer.$field.is_some() {
+                $target.$field = $layer.$field.clone();
+            }
+        )+
+    };
+}
+
+impl Settings {
+    pub fn merge(&mut self, layer: &Settings) {
+        merge_fields!(
+            self.selectors,
+            layer.selectors,
+            priority,
+            test_attributes,
+            deny_ids,
+            deny_classes
+        );
+        merge_fields!(
+            self.redaction,
+            layer.redaction,
+            sensitive_fields,
+            ignore_fields,
+            mask_characters
+        );
+        merge_fields!(
+            self.emit,
+            layer.emit,
+            mode,
+            params,
+            params_source,
+            cookie_banner
+        );
+        merge_fields!(self.llm, layer.llm, model, api_key_env);
+    }
+
+    // `default_order` applies unless the config sets a priority; targets may prefer their own
+    pub fn selector_rules(&self, default_order: &[SelectorStrategy]) -> SelectorRules {
+        let mut strategies: Vec<SelectorStrategy> = match &self.selectors.priority {
+            // Names were validated when the config was loaded
+            Some(names) => names
+                .iter()
+                .filter_map(|name| SelectorStrategy::parse(name))
+                .collect(),
+            None => default_order.to_vec(),
+        };
+        // Custom test attributes go right after the built-in test ids, unless listed already
+        let insert_at = strategies
+            .iter()
+            .rposition(|s| matches!(s, SelectorStrategy::TestId | SelectorStrategy::DataCy))
+            .map_or(0, |index| index + 1);
+        let custom: Vec<SelectorStrategy> = self
+            .selectors
+            .test_attributes
+            .iter()
+            .flatten()
+            .map(|attribute| SelectorStrategy::Attribute(attribute.clone()))
+            .filter(|strategy| !strategies.contains(strategy))
+            .collect();
+        strategies.splice(insert_at..insert_at, custom);
+
+        SelectorRules {
+            strategies,
+            deny_ids: self.selectors.deny_ids.clone().unwrap_or_default(),
+            deny_classes: self.selectors.deny_classes.clone().unwrap_or_default(),
+        }
+    }
+
+    pub fn redaction_rules(&self) -> RedactionRules {
+        let defaults = RedactionRules::default();
+        RedactionRules {
+            sensitive_fields: self.redaction.sensitive_fields.clone().unwrap_or_default(),
+            ignore_fields: self.redaction.ignore_fields.clone().unwrap_or_default(),
+            mask_characters: self
+                .redaction
+                .mask_characters
+                .as_ref()
+                .map_or(defaults.mask_characters, |chars| chars.chars().collect()),
+        }
+    }
+
+    pub fn emit_mode(&self) -> EmitMode {
+        self.emit.mode.unwrap_or(EmitMode::Locator)
+    }
+
+    pub fn param_mode(&self) -> Option<ParamMode> {
+        self.emit.params
+    }
+
+    pub fn param_source(&self) -> ParamSource {
+        self.emit.params_source.unwrap_or(ParamSource::Env)
+    }
+
+    // Button name pattern for the cookie banner dismissal, None when disabled
+    pub fn cookie_banner(&self) -> Option<&str> {
+        match self.emit.cookie_banner.as_deref() {
+            Some("") => None,
+            Some(pattern) => Some(pattern),
+            None => Some(DEFAULT_COOKIE_BANNER),
+        }
+    }
+
+    pub fn llm_model(&self) -> &str {
+        self.llm.model.as_deref().unwrap_or(DEFAULT_LLM_MODEL)
+    }
+
+    pub fn llm_api_key_env(&self) -> &str {
+        self.llm
+            .api_key_env
+            .as_deref()
+            .unwrap_or(DEFAULT_LLM_API_KEY_ENV)
+    }
+}
+
+// Why a config file couldn't be used
+#[derive(Debug)]
+pub enum ConfigError {
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    UnknownStrategy {
+        path: PathBuf,
+        name: String,
+    },
+    TestAttribute {
+        path: PathBuf,
+        attribute: String,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io { path, source } => {
+                write!(f, "Failed to read config file {:?}: {}", path, source)
+            }
+            ConfigError::Parse { path, source } => {
+                write!(f, "Invalid config file {:?}: {}", path, source)
+            }
+            ConfigError::UnknownStrategy { path, name } => write!(
+                f,
+                "Invalid config file {:?}: unknown selector strategy '{}' (expected {} or a data-* attribute)",
+                path,
+                name,
+                SelectorStrategy::NAMES.join(", ")
+            ),
+            ConfigError::TestAttribute { path, attribute } => write!(
+                f,
+                "Invalid config file {:?}: test attribute '{}' must start with data-",
+                path, attribute
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io { source, .. } => Some(source),
+            ConfigError::Parse { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl Config {
+    // Load the given file, or the nearest imitator.toml above the current directory.
+    // Returns the default config when there is none.
+    pub fn discover(
+        explicit_path: Option<&Path>,
+    ) -> Result<(Config, Option<PathBuf>), ConfigError> {
+        let path = match explicit_path {
+            Some(path) => Some(path.to_path_buf()),
+            None => std::env::current_dir()
+                .map_err(|source| ConfigError::Io {
+                    path: PathBuf::from("."),
+                    source,
+                })?
+                .ancestors()
+                .map(|dir| dir.join(CONFIG_FILE))
+                .find(|candidate| candidate.is_file()),
+        };
+        match path {
+            Some(path) => Ok((Config::load(&path)?, Some(path))),
+            None => Ok((Config::default(), None)),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Config, ConfigError> {
+        let content = fs::read_to_string(path).map_err(|source| ConfigError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let mut config: Config = toml::from_str(&content).map_err(|source| ConfigError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        let selector_sections = std::iter::once(&config.selectors)
+            .chain(config.domains.iter().map(|domain| &domain.selectors));
+        for selectors in selector_sections {
+            for name in selectors.priority.iter().flatten() {
+                if SelectorStrategy::parse(name).is_none() {
+                    return Err(ConfigError::UnknownStrategy {
+                        path: path.to_path_buf(),
+                        name: name.clone(),
+                    });
+                }
+            }
+            for attribute in selectors.test_attributes.iter().flatten() {
+                if !attribute.starts_with("data-") {
+                    return Err(ConfigError::TestAttribute {
+                        path: path.to_path_buf(),
+                        attribute: attribute.clone(),
+                    });
+                }
+            }
+        }
+
+        // The file may be discovered in a parent directory, so its paths can't depend on where
+        // the compiler runs
+        if let Some(dir) = config.output.dir.as_mut().filter(|dir| dir.is_relative()) {
+            let base = path.parent().unwrap_or(Path::new(""));
+            *dir = base.join(dir.strip_prefix(".").unwrap_or(dir));
+        }
+        Ok(config)
+    }
+
+    // Settings for a recording that starts at `url`, with command-line flags applied last
+    pub fn resolve(&self, url: &str, overrides: &Settings) -> Settings {
+        let mut settings = Settings {
+            selectors: self.selectors.clone(),
+            redaction: self.redaction.clone(),
+            emit: self.emit.clone(),
+            llm: self.llm.clone(),
+        };
+        for domain in self.domains_for(url) {
+            settings.merge(&Settings {
+                selectors: domain.selectors.clone(),
+                redaction: domain.redaction.clone(),
+                emit: domain.emit.clone(),
+                llm: domain.llm.clone(),
+            });
+        }
+        settings.merge(overrides);
+        settings
+    }
+
+    // Domain sections whose pattern matches the host of `url`, in file order
+    pub fn domains_for<'a>(&'a self, url: &str) -> impl Iterator<Item = &'a DomainConfig> {
+        let host = url_host(url);
+        self.domains.iter().filter(move |domain| {
+            host.as_ref()
+                .is_some_and(|host| wildcard_match(&domain.pattern.to_ascii_lowercase(), host))
+        })
+    }
+}
+
+// Host part of an absolute URL, lowercased and without userinfo or port
+fn url_host(url: &str) -> Option<String> {
+    let (_, rest) = url.split_once("://")?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host_port = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let host = match host_port.rsplit_once(':') {
+        Some((host, port)) if port.chars().all(|c| c.is_ascii_digit()) => host,
+        _ => host_port,
+    };
+    (!host.is_empty()).then(|| host.to_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load_text(name: &str, content: &str) -> Result<Config, ConfigError> {
+        let path =
+            std::env::temp_dir().join(format!("imitator-{}-{}.toml", name, std::process::id()));
+        fs::write(&path, content).unwrap();
+        let config = Config::load(&path);
+        fs::remove_file(&path).unwrap();
+        config
+    }
+
+    #[test]
+    fn reports_each_kind_of_config_problem() {
+        let missing = Config::load(Path::new("/nonexistent/imitator.toml"));
+        assert!(matches!(missing, Err(ConfigError::Io { .. })));
+        assert!(matches!(
+            load_text("syntax", "[selectors"),
+            Err(ConfigError::Parse { .. })
+        ));
+        assert!(matches!(
+            load_text("strategy", "[selectors]\npriority = [\"xpath\"]\n"),
+            Err(ConfigError::UnknownStrategy { name, .. }) if name == "xpath"
+        ));
+        assert!(matches!(
+            load_text("attribute", "[selectors]\ntest_attributes = [\"qa\"]\n"),
+            Err(ConfigError::TestAttribute { attribute, .. }) if attribute == "qa"
+        ));
+    }
+
+    #[test]
+    fn resolves_the_output_dir_against_the_config_file() {
+        let relative = load_text("relative", "[output]\ndir = \"./generated\"\n").unwrap();
+        assert_eq!(
+            relative.output.dir,
+            Some(std::env::temp_dir().join("generated"))
+        );
+        let absolute = load_text("absolute", "[output]\ndir = \"/srv/generated\"\n").unwrap();
+        assert_eq!(absolute.output.dir, Some(PathBuf::from("/srv/generated")));
+    }
+
+    #[test]
+    fn later_settings_replace_keys_they_set_and_keep_the_rest() {
+        let mut settings = Settings::default();
+        settings.selectors.priority = Some(vec!["id".to_string()]);
+        settings.selectors.deny_ids = Some(vec!["ember*".to_string()]);
+        settings.emit.cookie_banner = Some("Accept".to_string());
+
+        let mut layer = Settings::default();
+        layer.selectors.priority = Some(vec!["test-id".to_string(), "id".to_string()]);
+        layer.llm.model = Some("local/model".to_string());
+        settings.merge(&layer);
+
+        assert_eq!(
+            settings.selectors.priority.as_deref(),
+            Some(&["test-id".to_string(), "id".to_string()][..])
+        );
+        assert_eq!(
+            settings.selectors.deny_ids.as_deref(),
+            Some(&["ember*".to_string()][..])
+        );
+        assert_eq!(settings.emit.cookie_banner.as_deref(), Some("Accept"));
+        assert_eq!(settings.llm.model.as_deref(), Some("local/model"));
+    }
+
+    #[test]
+    fn applies_matching_domain_sections_then_command_line_flags() {
+        let config = load_text(
+            "domains",
+            r#"
+[emit]
+cookie_banner = "Accept"
+params = "sensitive"
+
+[llm]
+model = "top/model"
+
+[[domain]]
+match = "*.shop.test"
+emit = { cookie_banner = "Agree" }
+
+[[domain]]
+match = "checkout.shop.test"
+llm = { model = "checkout/model" }
+"#,
+        )
+        .unwrap();
+        let mut flags = Settings::default();
+        flags.emit.params = Some(ParamMode::All);
+
+        let checkout = config.resolve("https://user@Checkout.Shop.Test:8443/pay", &flags);
+        assert_eq!(checkout.emit.cookie_banner.as_deref(), Some("Agree"));
+        assert_eq!(checkout.llm.model.as_deref(), Some("checkout/model"));
+        assert_eq!(checkout.emit.params, Some(ParamMode::All));
+
+        let other = config.resolve("https://example.test/", &Settings::default());
+        assert_eq!(other.emit.cookie_banner.as_deref(), Some("Accept"));
+        assert_eq!(other.llm.model.as_deref(), Some("top/model"));
+        assert_eq!(other.emit.params, Some(ParamMode::Sensitive));
+    }
+}
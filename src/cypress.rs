@@ -0,0 +1,313 @@
+// Cypress emitter: the action sequence as commands in the `it()` block of a Cypress spec.
+//
+// Cypress queues commands instead of awaiting them, so every action is a single `cy.get()`
+// chain and there is no try/catch: a command that can't find its element fails the test.
+// Selectors prefer `data-cy`, the attribute Cypress recommends. Cypress only lets a spec touch
+// the origin it visited first, so actions recorded on another origin run inside `cy.origin()`.
+
+use crate::diagnostics::Diagnostics;
+use crate::emitter::Emitter;
+use crate::params::{ParamSource, Parameter, CSV_LINE_PARSER};
+use crate::playwright::{action_comment, unmapped_upload_comment, value_expression};
+use crate::templates::TemplateSource;
+use crate::{
+    action_summary, pressed_key, upload_fixture, ActionType, ActionWithSelector, Control, Emission,
+    EmitOptions, SelectorStrategy, PLACEHOLDER_FIXTURE,
+};
+
+pub struct CypressEmitter;
+
+impl Emitter for CypressEmitter {
+    fn name(&self) -> &str {
+        "cypress"
+    }
+
+    fn label(&self) -> &str {
+        "Cypress"
+    }
+
+    fn template(&self) -> TemplateSource {
+        TemplateSource::Builtin("cypress")
+    }
+
+    fn run_command(&self) -> &str {
+        "npm install && npm test"
+    }
+
+    fn selector_order(&self) -> Vec<SelectorStrategy> {
+        vec![
+            SelectorStrategy::DataCy,
+            SelectorStrategy::TestId,
+            SelectorStrategy::Id,
+            SelectorStrategy::Name,
+            SelectorStrategy::Class,
+        ]
+    }
+
+    fn prologue(&self, options: &EmitOptions, _diagnostics: &mut Diagnostics) -> String {
+        let Some(button_pattern) = options.cookie_banner else {
+            return String::new();
+        };
+        // cy.get() would fail the test when no banner is shown, so look for it with jQuery
+        let mut code = String::from("  // Dismiss common cookie banners first, if one is shown\n");
+        code.push_str("  cy.get(\"body\").then(($body) => {\n");
+        code.push_str(&format!(
+            "    const button = $body\n      .find(\"button, [role=button]\")\n      .filter((_, element) => /{}/i.test(element.textContent ?? \"\"));\n",
+            button_pattern.replace('/', "\\/")
+        ));
+        code.push_str("    if (button.length > 0) {\n");
+        code.push_str("      cy.wrap(button.first()).click();\n");
+        code.push_str("    }\n");
+        code.push_str("  });\n\n");
+        code
+    }
+
+    fn action(
+        &self,
+        action: &ActionWithSelector,
+        _previous: Option<&ActionWithSelector>,
+        emission: Emission,
+        options: &EmitOptions,
+        diagnostics: &mut Diagnostics,
+    ) -> String {
+        let mut code = action_comment(action);
+
+        if emission == Emission::Skipped {
+            let failed_tag = action
+                .selector
+                .split("::")
+                .nth(1)
+                .unwrap_or("unknown_element");
+            code.push_str(&format!(
+                "  // Action skipped: Could not generate stable selector for <{}> element.\n",
+                failed_tag
+            ));
+            code.push_str(&format!(
+                "  cy.log({});\n",
+                self.string_literal(&format!("Skipped: {}", action_summary(action)))
+            ));
+            return code;
+        }
+
+        let element = element_chain(self, action);
+        match action.action_type {
+            ActionType::Click => {
+                code.push_str(&format!("  {}.click();\n", element));
+            }
+            ActionType::Input => {
+                let Some(value) = value_expression(self, action) else {
+                    return code;
+                };
+                if let (None, Some(finding)) = (&action.parameter, &action.redaction) {
+                    code.push_str(&format!(
+                        "  // Input value redacted ({}, detected by {}), using placeholder:\n",
+                        finding.kind, finding.source
+                    ));
+                }
+                match action.control {
+                    Control::Select => {
+                        code.push_str(&format!("  {}.select({});\n", element, value));
+                    }
+                    Control::Checkbox if action.checked == Some(false) => {
+                        code.push_str(&format!("  {}.uncheck();\n", element));
+                    }
+                    Control::Checkbox | Control::Radio => {
+                        code.push_str(&format!("  {}.check();\n", element));
+                    }
+                    // type() rejects an empty string, so a field left empty is only cleared
+                    Control::Field if action.parameter.is_none() && value == "\"\"" => {
+                        code.push_str(&format!("  {}.clear();\n", element));
+                    }
+                    Control::Field => {
+                        code.push_str(&format!(
+                            "  {}.clear().type({}, {});\n",
+                            element,
+                            value,
+                            type_options(action)
+                        ));
+                    }
+                }
+            }
+            ActionType::Upload => {
+                let fixture_path = upload_fixture(action, options.fixture_files, diagnostics)
+                    .unwrap_or_else(|| {
+                        // The shared comment is indented for a try block; Cypress has none
+                        code.push_str(&unmapped_upload_comment(action).replacen("  ", "", 1));
+                        PLACEHOLDER_FIXTURE.to_string()
+                    });
+                code.push_str(&format!(
+                    "  {}.selectFile({});\n",
+                    element,
+                    self.string_literal(&fixture_path.replace('\\', "/"))
+                ));
+            }
+            ActionType::TypeText => {
+                if let Some(value) = value_expression(self, action) {
+                    // Focus the editing host first, so the editor's own key handlers run
+                    code.push_str(&format!(
+                        "  {}.click().type({}, {});\n",
+                        element,
+                        value,
+                        type_options(action)
+                    ));
+                }
+            }
+            // type() takes special keys as sequences such as `{enter}`
+            ActionType::KeyPress => {
+                code.push_str(&format!(
+                    "  {}.type({});\n",
+                    element,
+                    self.string_literal(&format!("{{{}}}", pressed_key(action).to_lowercase()))
+                ));
+            }
+        }
+        code
+    }
+
+    fn cross_origin(&self, origin: &str, actions: &[ActionWithSelector], blocks: String) -> String {
+        // The callback runs on the other origin, so `params` has to be passed in explicitly
+        let uses_params = actions.iter().any(|action| action.parameter.is_some());
+        let mut code = if uses_params {
+            format!(
+                "  cy.origin({}, {{ args: {{ params }} }}, ({{ params }}) => {{\n",
+                self.string_literal(origin)
+            )
+        } else {
+            format!("  cy.origin({}, () => {{\n", self.string_literal(origin))
+        };
+        code.push_str(&indent(blocks.trim_end()));
+        code.push_str("\n  });\n\n");
+        code
+    }
+
+    fn wrap_parameters(
+        &self,
+        parameters: &[Parameter],
+        source: ParamSource,
+        initial_url: &str,
+        action_sequence: &str,
+    ) -> String {
+        let mut code = String::new();
+
+        code.push_str("  interface RecordingParams {\n");
+        for parameter in parameters {
+            code.push_str(&format!("    {}: string;\n", parameter.name));
+        }
+        code.push_str("  }\n\n");
+
+        match source {
+            ParamSource::Env => {
+                code.push_str(
+                    "  // Recording parameters, overridable through environment variables (see .env.example)\n",
+                );
+                code.push_str("  const params: RecordingParams = {\n");
+                for parameter in parameters {
+                    code.push_str(&format!(
+                        "    {}: Cypress.env({}) || {},\n",
+                        parameter.name,
+                        self.string_literal(&parameter.env_var),
+                        self.string_literal(&parameter.default_value)
+                    ));
+                }
+                code.push_str("  };\n\n");
+                code.push_str(action_sequence);
+                return code;
+            }
+            ParamSource::Json => {
+                code.push_str("  // One run of the recorded sequence per row in params.json\n");
+                code.push_str(
+                    "  cy.readFile(\"params.json\").then((paramRows: RecordingParams[]) => {\n",
+                );
+            }
+            ParamSource::Csv => {
+                code.push_str("  // One run of the recorded sequence per row in params.csv (header row = parameter names)\n");
+                code.push_str(CSV_LINE_PARSER);
+                code.push_str("  cy.readFile(\"params.csv\").then((csv: string) => {\n");
+                code.push_str("    const [headerLine, ...rowLines] = csv\n");
+                code.push_str("      .split(/\\r?\\n/)\n");
+                code.push_str("      .filter((line) => line.trim() !== \"\");\n");
+                code.push_str("    const header = parseCsvLine(headerLine);\n");
+                code.push_str("    const paramRows = rowLines.map((line) => {\n");
+                code.push_str("      const cells = parseCsvLine(line);\n");
+                code.push_str(
+                    "      return Object.fromEntries(header.map((name, i) => [name, cells[i] ?? \"\"]));\n",
+                );
+                code.push_str("    }) as unknown as RecordingParams[];\n");
+            }
+        }
+
+        code.push_str("\n    paramRows.forEach((params, rowIndex) => {\n");
+        code.push_str(&format!(
+            "      if (rowIndex > 0) {{\n        cy.visit({});\n      }}\n\n",
+            self.string_literal(initial_url)
+        ));
+        code.push_str(&indent(&indent(action_sequence)));
+        code.push_str("\n    });\n  });");
+        code
+    }
+}
+
+// `cy.get()` chain yielding the action's element. Commands don't cross into iframes, so each
+// frame's body is wrapped as the subject for the next lookup; shadow roots get `.shadow()`.
+fn element_chain(emitter: &dyn Emitter, action: &ActionWithSelector) -> String {
+    let mut chain = String::from("cy");
+    let mut lookup = "get";
+    for frame in &action.frames {
+        chain.push_str(&format!(
+            ".{}({}).its(\"0.contentDocument.body\").should(\"not.be.empty\").then(cy.wrap)",
+            lookup,
+            emitter.string_literal(frame)
+        ));
+        lookup = "find";
+    }
+    for host in &action.shadow_hosts {
+        chain.push_str(&format!(
+            ".{}({}).shadow()",
+            lookup,
+            emitter.string_literal(host)
+        ));
+        lookup = "find";
+    }
+    chain.push_str(&format!(
+        ".{}({})",
+        lookup,
+        emitter.string_literal(&action.selector)
+    ));
+    chain
+}
+
+// Options for type(): braces are typed as-is rather than read as key sequences like `{enter}`,
+// and sensitive values stay out of the command log
+fn type_options(action: &ActionWithSelector) -> &'static str {
+    if action.redaction.is_some() || action.masked {
+        "{ parseSpecialCharSequences: false, log: false }"
+    } else {
+        "{ parseSpecialCharSequences: false }"
+    }
+}
+
+// Indent every non-empty line by two more spaces
+fn indent(code: &str) -> String {
+    code.lines()
+        .map(|line| {
+            if line.is_empty() {
+                String::new()
+            } else {
+                format!("  {}", line)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{assert_golden, emit_recording, shop_recording};
+
+    #[test]
+    fn writes_the_shop_checkout_as_a_cypress_spec() {
+        let code = emit_recording(&CypressEmitter, &shop_recording());
+        assert_golden("cypress.ts", &code);
+    }
+}
@@ -0,0 +1,725 @@
+// Chrome DevTools Recorder flows: the JSON the Recorder panel exports and imports.
+//
+// Flows are a second front end next to rrweb. `import` turns a flow's steps into the same
+// actions that preprocessing and selector generation extract from rrweb events, so everything
+// after them (redaction, parameters, every emitter) works unchanged. A flow has no DOM, so each
+// step's target gets a stand-in node built from its selectors: the tag, id, name and type
+// found in its CSS selector and the accessible name from its `aria/` selector. Descriptions,
+// redaction hints and parameter names are derived from that. An action's event index and
+// timestamp are the index of the step it came from.
+//
+// `RecorderEmitter` goes the other way: the `chrome-recorder` target writes compiled actions
+// out as a flow that the Recorder panel imports and `@puppeteer/replay` runs.
+//
+// Format: https://developer.chrome.com/docs/devtools/recorder/reference
+
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::diagnostics::{Code, Diagnostic, Diagnostics};
+use crate::emitter::Emitter;
+use crate::params::{ParamSource, Parameter};
+use crate::templates::TemplateSource;
+use crate::{
+    control_kind, describe_node, pressed_key, replayed_value, ActionType, ActionWithSelector,
+    Control, Emission, EmitOptions, ImportedActions, NodeInfo, SelectorCandidate, SelectorStrategy,
+};
+
+// A Recorder flow. Steps are kept as JSON so `explain` can show them as recorded.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Flow {
+    #[serde(default)]
+    pub title: String,
+    pub steps: Vec<Value>,
+}
+
+pub(crate) fn import(flow: &Flow, diagnostics: &mut Diagnostics) -> ImportedActions {
+    let mut imported = ImportedActions {
+        initial_url: None,
+        viewport: None,
+        dom_map: HashMap::new(),
+        actions: Vec::new(),
+    };
+    let mut url = None;
+
+    for (index, step) in flow.steps.iter().enumerate() {
+        let step_type = step.get("type").and_then(Value::as_str).unwrap_or_default();
+        let action_type = match step_type {
+            "setViewport" => {
+                imported.viewport = step
+                    .get("width")
+                    .and_then(Value::as_i64)
+                    .zip(step.get("height").and_then(Value::as_i64));
+                continue;
+            }
+            "navigate" if imported.initial_url.is_none() => {
+                let href = step.get("url").and_then(Value::as_str).map(String::from);
+                imported.initial_url = href.clone();
+                url = href;
+                continue;
+            }
+            "navigate" => {
+                diagnostics.push(
+                    Diagnostic::warning(
+                        Code::UnsupportedStep,
+                        format!(
+                            "Navigation to {} is not replayed; only the first navigate step is the start URL.",
+                            step.get("url").and_then(Value::as_str).unwrap_or("unknown URL")
+                        ),
+                    )
+                    .at_event(index, index as i64)
+                    .with_hint("Record one flow per page the user opens by typing its URL"),
+                );
+                url = step.get("url").and_then(Value::as_str).map(String::from);
+                continue;
+            }
+            "click" => ActionType::Click,
+            "change" => ActionType::Input,
+            // Enter, usually pressed to submit a form, goes to the field the step before acted
+            // on. Its keyUp only ends the key press, unless it holds the page load it caused.
+            "keyDown" | "keyUp" if step.get("key").and_then(Value::as_str) == Some("Enter") => {
+                let Some(previous) = imported.actions.last() else {
+                    diagnostics.push(
+                        Diagnostic::warning(
+                            Code::UnsupportedStep,
+                            "Enter was pressed before any step focused an element; it was skipped.",
+                        )
+                        .at_event(index, index as i64),
+                    );
+                    continue;
+                };
+                let navigation = asserted_navigation(step);
+                if step_type == "keyUp" {
+                    if matches!(previous.action_type, ActionType::KeyPress) && navigation.is_some()
+                    {
+                        imported.actions.last_mut().unwrap().navigates = true;
+                    }
+                } else {
+                    imported.actions.push(ActionWithSelector {
+                        action_type: ActionType::KeyPress,
+                        value: Some("Enter".to_string()),
+                        checked: None,
+                        select_by_label: false,
+                        timestamp: index as i64,
+                        event_index: index,
+                        url: url.clone(),
+                        navigates: navigation.is_some(),
+                        ..previous.clone()
+                    });
+                }
+                if let Some(next_url) = navigation.and_then(|event| event.get("url")?.as_str()) {
+                    url = Some(next_url.to_string());
+                }
+                continue;
+            }
+            _ => {
+                diagnostics.push(
+                    Diagnostic::warning(
+                        Code::UnsupportedStep,
+                        format!(
+                            "Recorder step '{}' is not supported and was skipped.",
+                            step_type
+                        ),
+                    )
+                    .at_event(index, index as i64),
+                );
+                continue;
+            }
+        };
+
+        if step
+            .get("frame")
+            .and_then(Value::as_array)
+            .is_some_and(|frame| !frame.is_empty())
+        {
+            diagnostics.push(
+                Diagnostic::warning(
+                    Code::UnsupportedStep,
+                    "Step targets an iframe by index, which can't be turned into a selector; it runs in the main frame.",
+                )
+                .at_event(index, index as i64),
+            );
+        }
+
+        let rrweb_id = index as i64 + 1;
+        let target = Target::from_step(step);
+        let mut node = target.stand_in_node(rrweb_id);
+        if matches!(action_type, ActionType::Input) && node.tag_name.is_none() {
+            // Changes are made to form fields
+            node.tag_name = Some("input".to_string());
+        }
+        imported.dom_map.insert(rrweb_id, node);
+        let (selector, shadow_hosts, strategy) = match target.paths.first() {
+            Some(path) => {
+                let (element, hosts) = path.split_last().unwrap_or((&path[0], &[]));
+                (
+                    element.clone(),
+                    hosts.to_vec(),
+                    Some(SelectorStrategy::Recorder),
+                )
+            }
+            None => {
+                diagnostics.push(
+                    Diagnostic::warning(
+                        Code::SelectorFailed,
+                        "Step has no CSS or XPath selector; the action will be skipped.",
+                    )
+                    .at_event(index, index as i64)
+                    .with_hint(
+                        "Re-record the step with CSS or XPath selectors enabled in the Recorder",
+                    ),
+                );
+                (
+                    "SELECTOR_GENERATION_FAILED::element".to_string(),
+                    Vec::new(),
+                    None,
+                )
+            }
+        };
+
+        let navigation = asserted_navigation(step);
+        imported.actions.push(ActionWithSelector {
+            action_type,
+            rrweb_id,
+            value: step.get("value").and_then(Value::as_str).map(String::from),
+            checked: None,
+            control: control_kind(rrweb_id, &imported.dom_map),
+            select_by_label: false, // change steps hold the option's value
+            timestamp: index as i64,
+            event_index: index,
+            url: url.clone(),
+            navigates: navigation.is_some(),
+            page: 0, // Stand-in nodes all live in one map
+            selector,
+            strategy,
+            // Each path pierces its own chain of shadow roots, so its hosts stay with it
+            candidates: target
+                .paths
+                .iter()
+                .filter_map(|path| path.split_last())
+                .map(|(selector, hosts)| SelectorCandidate {
+                    strategy: SelectorStrategy::Recorder,
+                    selector: selector.clone(),
+                    shadow_hosts: hosts.to_vec(),
+                    matches: 1, // Unknown without a DOM
+                    score: SelectorStrategy::Recorder.stability(),
+                })
+                .collect(),
+            shadow_hosts,
+            frames: Vec::new(),
+            redaction: None,
+            parameter: None,
+            masked: false,
+            description: describe_node(rrweb_id, &imported.dom_map),
+        });
+        if let Some(next_url) = navigation.and_then(|event| event.get("url")?.as_str()) {
+            url = Some(next_url.to_string());
+        }
+    }
+
+    imported
+}
+
+// The navigation a step caused, from the events the Recorder asserts after it
+fn asserted_navigation(step: &Value) -> Option<&Value> {
+    step.get("assertedEvents")
+        .and_then(Value::as_array)?
+        .iter()
+        .find(|event| event.get("type").and_then(Value::as_str) == Some("navigation"))
+}
+
+// A step's selectors, sorted by how well our emitters can use them
+struct Target {
+    paths: Vec<Vec<String>>, // CSS, then `pierce/` CSS, then XPath; shadow hosts first
+    aria: Option<String>,    // From `aria/Name[role="button"]`
+}
+
+impl Target {
+    fn from_step(step: &Value) -> Self {
+        let mut css = Vec::new();
+        let mut pierce = Vec::new();
+        let mut xpath = Vec::new();
+        let mut aria = None;
+        for selector in step
+            .get("selectors")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            // A selector is a string, or an array of them: one per shadow root to descend into
+            let path: Vec<String> = match selector {
+                Value::String(selector) => vec![selector.clone()],
+                Value::Array(parts) => parts
+                    .iter()
+                    .filter_map(|part| part.as_str().map(String::from))
+                    .collect(),
+                _ => continue,
+            };
+            let Some(first) = path.first() else {
+                continue;
+            };
+            if let Some(name) = first.strip_prefix("aria/") {
+                aria.get_or_insert_with(|| name.to_string());
+            } else if let Some(first) = first.strip_prefix("pierce/") {
+                pierce.push([vec![first.to_string()], path[1..].to_vec()].concat());
+            } else if let Some(first) = first.strip_prefix("xpath/") {
+                xpath.push([vec![first.to_string()], path[1..].to_vec()].concat());
+            } else if !first.starts_with("text/") {
+                css.push(path);
+            }
+        }
+        Target {
+            paths: [css, pierce, xpath].concat(),
+            aria,
+        }
+    }
+
+    // A node with the attributes the selectors reveal
+    fn stand_in_node(&self, rrweb_id: i64) -> NodeInfo {
+        let css = self
+            .paths
+            .iter()
+            .filter_map(|path| path.last())
+            .find(|selector| !selector.starts_with('/') && !selector.starts_with("(/"));
+        let mut node = stand_in_node(rrweb_id, css.map(String::as_str));
+        if let Some(aria) = &self.aria {
+            let (name, role) = match aria
+                .strip_suffix("\"]")
+                .and_then(|a| a.split_once("[role=\""))
+            {
+                Some((name, role)) => (name, Some(role)),
+                None => (aria.as_str(), None),
+            };
+            if !name.is_empty() {
+                node.attributes
+                    .entry("aria-label".to_string())
+                    .or_insert_with(|| name.to_string());
+            }
+            if let Some(role) = role {
+                node.attributes.insert("role".to_string(), role.to_string());
+                // The role stands in for the tag when the CSS selector doesn't name one
+                let (tag, input_type) = match role {
+                    "button" => ("button", None),
+                    "link" => ("a", None),
+                    "checkbox" => ("input", Some("checkbox")),
+                    "radio" => ("input", Some("radio")),
+                    "textbox" | "searchbox" => ("input", None),
+                    _ => ("", None),
+                };
+                if node.tag_name.is_none() && !tag.is_empty() {
+                    node.tag_name = Some(tag.to_string());
+                    if let Some(input_type) = input_type {
+                        node.attributes
+                            .entry("type".to_string())
+                            .or_insert_with(|| input_type.to_string());
+                    }
+                }
+            }
+        }
+        node
+    }
+}
+
+// A node for an element known only by a selector, with the tag and attributes the last
+// compound of `css` tests for
+pub(crate) fn stand_in_node(rrweb_id: i64, css: Option<&str>) -> NodeInfo {
+    let mut node = NodeInfo {
+        rrweb_id,
+        tag_name: None,
+        attributes: HashMap::new(),
+        parent_id: None,
+        child_ids: Vec::new(),
+        text_content: None,
+        is_shadow_host: false,
+        is_shadow: false,
+    };
+    if let Some(css) = css {
+        parse_compound(last_compound(css), &mut node);
+    }
+    node
+}
+
+// The part of a CSS selector after its last combinator, e.g. `input#email` in `form > input#email`
+fn last_compound(selector: &str) -> &str {
+    let mut start = 0;
+    let mut brackets = 0;
+    let mut quote = None;
+    for (i, c) in selector.char_indices() {
+        match (c, quote) {
+            ('\\', _) => {}
+            ('"' | '\'', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            (_, Some(_)) => {}
+            ('[' | '(', None) => brackets += 1,
+            (']' | ')', None) => brackets -= 1,
+            (' ' | '>' | '+' | '~', None) if brackets == 0 => start = i + 1,
+            _ => {}
+        }
+    }
+    &selector[start..]
+}
+
+// Read the tag, id, classes and `[attribute = "value"]` tests of one compound selector
+fn parse_compound(compound: &str, node: &mut NodeInfo) {
+    let is_name = |c: char| c.is_alphanumeric() || c == '-' || c == '_' || c == '\\';
+    let tag: String = compound.chars().take_while(|&c| is_name(c)).collect();
+    if !tag.is_empty() {
+        node.tag_name = Some(tag.to_lowercase());
+    }
+    let mut rest = &compound[tag.len()..];
+    while let Some(c) = rest.chars().next() {
+        rest = &rest[c.len_utf8()..];
+        match c {
+            '#' | '.' => {
+                let end = rest.find(|c| !is_name(c)).unwrap_or(rest.len());
+                let name = rest[..end].replace('\\', "");
+                rest = &rest[end..];
+                if c == '#' {
+                    node.attributes.insert("id".to_string(), name);
+                } else {
+                    let class = node.attributes.entry("class".to_string()).or_default();
+                    if !class.is_empty() {
+                        class.push(' ');
+                    }
+                    class.push_str(&name);
+                }
+            }
+            '[' => {
+                let end = rest.find(']').unwrap_or(rest.len());
+                if let Some((attribute, value)) = rest[..end].split_once('=') {
+                    // Only exact matches say what the attribute holds
+                    if !attribute.ends_with(['~', '|', '^', '$', '*']) {
+                        let value = value.trim().trim_matches(['"', '\'']).replace("\\\"", "\"");
+                        node.attributes
+                            .insert(attribute.trim().to_lowercase(), value);
+                    }
+                }
+                rest = rest.get(end + 1..).unwrap_or_default();
+            }
+            _ => {}
+        }
+    }
+}
+
+pub struct RecorderEmitter;
+
+impl Emitter for RecorderEmitter {
+    fn name(&self) -> &str {
+        "chrome-recorder"
+    }
+
+    fn label(&self) -> &str {
+        "Chrome DevTools Recorder"
+    }
+
+    fn template(&self) -> TemplateSource {
+        TemplateSource::Builtin("chrome-recorder")
+    }
+
+    fn run_command(&self) -> &str {
+        "npm install && npm start"
+    }
+
+    // Steps are array elements, so each starts with the comma that follows the previous one
+    fn separator(&self) -> &str {
+        ""
+    }
+
+    fn action(
+        &self,
+        action: &ActionWithSelector,
+        previous: Option<&ActionWithSelector>,
+        emission: Emission,
+        options: &EmitOptions,
+        diagnostics: &mut Diagnostics,
+    ) -> String {
+        // Skipped actions were reported when their selector failed, and JSON has no comments
+        if emission == Emission::Skipped {
+            return String::new();
+        }
+        let unsupported = |diagnostics: &mut Diagnostics, message: &str| {
+            diagnostics.push(
+                Diagnostic::warning(Code::UnsupportedStep, message)
+                    .at_event(action.event_index, action.timestamp)
+                    .on_node(action.rrweb_id),
+            );
+        };
+        if !action.frames.is_empty() {
+            unsupported(
+                diagnostics,
+                "Recorder flows address iframes by index, not selector; the action in an iframe was left out.",
+            );
+            return String::new();
+        }
+
+        let step = |step_type: &str| {
+            vec![
+                ("type", self.string_literal(step_type)),
+                ("target", self.string_literal("main")),
+            ]
+        };
+        let navigation = || {
+            (
+                "assertedEvents",
+                "[{ \"type\": \"navigation\" }]".to_string(),
+            )
+        };
+        let click = || {
+            let mut fields = step("click");
+            fields.push(("selectors", self.selectors(action)));
+            fields.push(("offsetX", "1".to_string()));
+            fields.push(("offsetY", "1".to_string()));
+            if action.navigates {
+                fields.push(navigation());
+            }
+            fields
+        };
+        let steps = match action.action_type {
+            ActionType::Click => vec![click()],
+            // Flows have no check step, so the control is clicked, unless the recorded click
+            // just before already toggled it. A click can't clear a radio button.
+            ActionType::Input if matches!(action.control, Control::Checkbox | Control::Radio) => {
+                // The click step written just before, i.e. one that wasn't left out
+                let clicked = previous.is_some_and(|previous| {
+                    matches!(previous.action_type, ActionType::Click)
+                        && previous.frames.is_empty()
+                        && crate::emission(previous, options.mode) != Emission::Skipped
+                        && previous.selector == action.selector
+                });
+                if clicked || (action.control == Control::Radio && action.checked == Some(false)) {
+                    return String::new();
+                }
+                vec![click()]
+            }
+            ActionType::Input | ActionType::TypeText => {
+                let Some(value) = replayed_value(action) else {
+                    return String::new();
+                };
+                if action.select_by_label {
+                    unsupported(
+                        diagnostics,
+                        "Recorder change steps pick options by value; the option's label was written in its place.",
+                    );
+                }
+                let mut fields = step("change");
+                fields.push(("selectors", self.selectors(action)));
+                fields.push(("value", self.string_literal(&value)));
+                vec![fields]
+            }
+            // Keys go to the focused element, which the step before left in the field
+            ActionType::KeyPress => {
+                let key = ("key", self.string_literal(pressed_key(action)));
+                let mut key_down = step("keyDown");
+                key_down.push(key.clone());
+                let mut key_up = step("keyUp");
+                key_up.push(key);
+                if action.navigates {
+                    key_up.push(navigation());
+                }
+                vec![key_down, key_up]
+            }
+            ActionType::Upload => {
+                unsupported(
+                    diagnostics,
+                    "Recorder flows have no file upload step; the upload was left out.",
+                );
+                return String::new();
+            }
+        };
+
+        let mut code = String::new();
+        for fields in steps {
+            let fields: Vec<String> = fields
+                .iter()
+                .map(|(key, value)| format!("      \"{}\": {}", key, value))
+                .collect();
+            code.push_str(",\n    {\n");
+            code.push_str(&fields.join(",\n"));
+            code.push_str("\n    }");
+        }
+        code
+    }
+
+    // Flows have no parameters, so parameterized inputs replay their recorded values
+    fn wrap_parameters(
+        &self,
+        _parameters: &[Parameter],
+        _source: ParamSource,
+        _initial_url: &str,
+        action_sequence: &str,
+    ) -> String {
+        action_sequence.to_string()
+    }
+}
+
+impl RecorderEmitter {
+    // Every candidate selector, each prefixed by the shadow hosts it is scoped to. The Recorder
+    // tries them in order; XPath selectors carry the `xpath/` prefix.
+    fn selectors(&self, action: &ActionWithSelector) -> String {
+        let mut selectors: Vec<(&[String], &str)> = action
+            .candidates
+            .iter()
+            .map(|candidate| {
+                (
+                    candidate.shadow_hosts.as_slice(),
+                    candidate.selector.as_str(),
+                )
+            })
+            .collect();
+        if selectors.is_empty() {
+            selectors.push((&action.shadow_hosts, &action.selector));
+        }
+        let paths: Vec<String> = selectors
+            .iter()
+            .map(|(shadow_hosts, selector)| {
+                let path: Vec<String> = shadow_hosts
+                    .iter()
+                    .map(|host| self.string_literal(host))
+                    .chain(std::iter::once(
+                        if selector.starts_with('/') || selector.starts_with("(/") {
+                            self.string_literal(&format!("xpath/{}", selector))
+                        } else {
+                            self.string_literal(selector)
+                        },
+                    ))
+                    .collect();
+                format!("        [{}]", path.join(", "))
+            })
+            .collect();
+        format!("[\n{}\n      ]", paths.join(",\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{
+        action, assert_golden, emit_action, emit_actions, emit_recording, replayed_steps,
+        round_trip, shop_recording,
+    };
+    use crate::Recording;
+    use serde_json::json;
+
+    fn flow(steps: Value) -> Flow {
+        serde_json::from_value(json!({"title": "Search", "steps": steps})).unwrap()
+    }
+
+    #[test]
+    fn imports_enter_as_a_key_press_in_the_field_before_it() {
+        let flow = flow(json!([
+            {"type": "navigate", "url": "https://shop.test/"},
+            {"type": "change", "value": "shoes", "selectors": [["#q"]], "target": "main"},
+            {"type": "keyDown", "key": "Enter", "target": "main"},
+            {"type": "keyUp", "key": "Enter", "target": "main",
+             "assertedEvents": [{"type": "navigation", "url": "https://shop.test/search"}]},
+        ]));
+        let mut diagnostics = Diagnostics::default();
+        let imported = import(&flow, &mut diagnostics);
+        assert!(diagnostics.is_empty());
+        let press = &imported.actions[1];
+        assert!(matches!(press.action_type, ActionType::KeyPress));
+        assert_eq!(press.value.as_deref(), Some("Enter"));
+        assert_eq!(press.selector, "#q");
+        assert!(press.navigates);
+    }
+
+    #[test]
+    fn keeps_each_selector_path_with_its_own_shadow_hosts() {
+        let flow = flow(json!([
+            {"type": "navigate", "url": "https://shop.test/"},
+            {"type": "click", "target": "main", "offsetX": 1, "offsetY": 1,
+             "selectors": [["shop-app", "login-form", "#email"], ["#email-fallback"],
+                           ["pierce/sign-in", "input"]]},
+        ]));
+        let mut diagnostics = Diagnostics::default();
+        let imported = import(&flow, &mut diagnostics);
+        let click = &imported.actions[0];
+        assert_eq!(click.shadow_hosts, ["shop-app", "login-form"]);
+        let paths: Vec<(&[String], &str)> = click
+            .candidates
+            .iter()
+            .map(|candidate| {
+                (
+                    candidate.shadow_hosts.as_slice(),
+                    candidate.selector.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                (
+                    &["shop-app".to_string(), "login-form".to_string()][..],
+                    "#email"
+                ),
+                (&[][..], "#email-fallback"),
+                (&["sign-in".to_string()][..], "input"),
+            ]
+        );
+
+        // Exported again, every path keeps its own hosts
+        let (code, _) = emit_actions(&RecorderEmitter, &imported.actions);
+        assert!(code.contains("[\"shop-app\", \"login-form\", \"#email\"]"));
+        assert!(code.contains("[\"#email-fallback\"]"));
+        assert!(code.contains("[\"sign-in\", \"input\"]"));
+    }
+
+    #[test]
+    fn clicks_checkboxes_the_recording_did_not_click() {
+        let click = action(ActionType::Click, Control::Field, "#terms", None);
+        let mut terms = action(ActionType::Input, Control::Checkbox, "#terms", Some("on"));
+        terms.checked = Some(true);
+        let mut news = action(ActionType::Input, Control::Checkbox, "#news", Some("on"));
+        news.checked = Some(true);
+
+        let (code, diagnostics) = emit_actions(&RecorderEmitter, &[click, terms, news]);
+        assert!(diagnostics.is_empty());
+        assert_eq!(code.matches("\"type\": \"click\"").count(), 2);
+        assert_eq!(code.matches("\"#terms\"").count(), 1);
+        assert_eq!(code.matches("\"#news\"").count(), 1);
+    }
+
+    #[test]
+    fn keeps_no_click_between_separate_emits() {
+        // The registry shares one emitter between compiles, so a click written by one must not
+        // stop another from clicking the same checkbox
+        let emitter = RecorderEmitter;
+        let click = action(ActionType::Click, Control::Field, "#terms", None);
+        emit_actions(&emitter, &[click]);
+        let mut terms = action(ActionType::Input, Control::Checkbox, "#terms", Some("on"));
+        terms.checked = Some(true);
+        let (code, _) = emit_action(&emitter, &terms);
+        assert!(code.contains("\"type\": \"click\""));
+    }
+
+    #[test]
+    fn writes_the_shop_checkout_as_recorder_steps() {
+        let code = emit_recording(&RecorderEmitter, &shop_recording());
+        assert_golden("chrome-recorder.json", &code);
+    }
+
+    #[test]
+    fn exports_imported_flows_as_the_same_steps() {
+        let flow = json!({"title": "Checkout", "steps": [
+            {"type": "setViewport", "width": 1280, "height": 720, "deviceScaleFactor": 1,
+             "isMobile": false, "hasTouch": false, "isLandscape": false},
+            {"type": "navigate", "url": "https://shop.test/cart"},
+            {"type": "change", "value": "fr", "selectors": [["#country"]], "target": "main"},
+            {"type": "click", "selectors": [["#terms"]], "target": "main", "offsetX": 4, "offsetY": 4},
+            {"type": "change", "value": "jane \"j\" doe", "selectors": [["shop-form", "#name"]],
+             "target": "main"},
+            {"type": "keyDown", "key": "Enter", "target": "main"},
+            {"type": "keyUp", "key": "Enter", "target": "main",
+             "assertedEvents": [{"type": "navigation", "url": "https://shop.test/pay"}]},
+            {"type": "click", "selectors": [["aria/Pay"], ["#pay"]], "target": "main",
+             "offsetX": 4, "offsetY": 4},
+        ]});
+        let recording = Recording::from_json("checkout", &flow.to_string()).unwrap();
+        let exported = round_trip(&recording, "chrome-recorder");
+        let steps = replayed_steps(&recording);
+        assert_eq!(steps.len(), 5);
+        assert_eq!(replayed_steps(&exported), steps);
+        assert_eq!(exported.flow.unwrap().title, "checkout");
+    }
+}
@@ -0,0 +1,314 @@
+// Diagnostics: problems found while compiling a recording, and the library's error type.
+//
+// Every diagnostic carries a stable `code` so tooling can group the same problem across many
+// recordings, plus whatever location is known: the index of the rrweb event in the recording,
+// its timestamp and the rrweb node id involved. Messages leave the location out for the same
+// reason; it is rendered separately.
+//
+// Text:  Warning [selector-failed]: Could not generate a stable selector for <div>; ... (event 12, t=1900, node 17)
+//          hint: Add an id or data-testid attribute, or pass --selector-strategy with a test attribute
+// JSON:  {"severity":"warning","code":"selector-failed","event_index":12,"timestamp":1900,
+//         "node_id":17,"message":"...","hint":"..."}
+
+use serde::{Serialize, Serializer};
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,    // Worth knowing, e.g. a value was replaced by a placeholder
+    Warning, // The generated project may not replay the recording faithfully
+    Error,   // Compilation failed
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Code {
+    // Recording
+    InvalidRecording, // Not rrweb events, a Recorder flow or a Selenium IDE project
+    MissingSnapshot,  // No full snapshot event to build the DOM from
+    EmptySnapshot,    // Full snapshot without node data
+    MissingStartUrl,  // No Meta event with an href
+    NoActions,        // Nothing to replay
+    UnsupportedStep,  // Step or action the source or target format can't express
+    MultipleTests,    // Selenium IDE project with several tests, of which one is compiled
+    // Selectors
+    SelectorFailed, // No stable selector for the target element
+    NodeNotFound,   // Action targets a node missing from the DOM
+    // Values
+    MaskedInput,    // Value masked by the recorder, replaced by a parameter
+    RedactedValue,  // Sensitive value replaced by a placeholder
+    MissingFixture, // Recorded upload without a mapped fixture file
+    // Project
+    Io,
+    Config,
+    Template,
+    PostProcessFailed, // Optional template command failed
+}
+
+impl Code {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Code::InvalidRecording => "invalid-recording",
+            Code::MissingSnapshot => "missing-snapshot",
+            Code::EmptySnapshot => "empty-snapshot",
+            Code::MissingStartUrl => "missing-start-url",
+            Code::NoActions => "no-actions",
+            Code::UnsupportedStep => "unsupported-step",
+            Code::MultipleTests => "multiple-tests",
+            Code::SelectorFailed => "selector-failed",
+            Code::NodeNotFound => "node-not-found",
+            Code::MaskedInput => "masked-input",
+            Code::RedactedValue => "redacted-value",
+            Code::MissingFixture => "missing-fixture",
+            Code::Io => "io",
+            Code::Config => "config",
+            Code::Template => "template",
+            Code::PostProcessFailed => "post-process-failed",
+        }
+    }
+}
+
+impl Serialize for Code {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: Code,
+    pub event_index: Option<usize>, // Position of the rrweb event in the recording
+    pub timestamp: Option<i64>,
+    pub node_id: Option<i64>, // rrweb node id
+    pub message: String,
+    pub hint: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, code: Code, message: impl Into<String>) -> Self {
+        Diagnostic {
+            severity,
+            code,
+            event_index: None,
+            timestamp: None,
+            node_id: None,
+            message: message.into(),
+            hint: None,
+        }
+    }
+
+    pub fn info(code: Code, message: impl Into<String>) -> Self {
+        Diagnostic::new(Severity::Info, code, message)
+    }
+
+    pub fn warning(code: Code, message: impl Into<String>) -> Self {
+        Diagnostic::new(Severity::Warning, code, message)
+    }
+
+    pub fn error(code: Code, message: impl Into<String>) -> Self {
+        Diagnostic::new(Severity::Error, code, message)
+    }
+
+    pub fn at_event(mut self, event_index: usize, timestamp: i64) -> Self {
+        self.event_index = Some(event_index);
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    pub fn on_node(mut self, node_id: i64) -> Self {
+        self.node_id = Some(node_id);
+        self
+    }
+
+    pub fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self.severity {
+            Severity::Info => "Info",
+            Severity::Warning => "Warning",
+            Severity::Error => "Error",
+        };
+        write!(f, "{} [{}]: {}", label, self.code.as_str(), self.message)?;
+
+        let mut location = Vec::new();
+        if let Some(event_index) = self.event_index {
+            location.push(format!("event {}", event_index));
+        }
+        if let Some(timestamp) = self.timestamp {
+            location.push(format!("t={}", timestamp));
+        }
+        if let Some(node_id) = self.node_id {
+            location.push(format!("node {}", node_id));
+        }
+        if !location.is_empty() {
+            write!(f, " ({})", location.join(", "))?;
+        }
+        if let Some(hint) = &self.hint {
+            write!(f, "\n  hint: {}", hint)?;
+        }
+        Ok(())
+    }
+}
+
+// Diagnostics collected over one compilation. Nothing is printed here; callers decide how
+// to show them (the CLI renders them through `Logger::diagnostic`).
+#[derive(Debug, Clone, Default)]
+pub struct Diagnostics {
+    items: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        self.items.push(diagnostic);
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Diagnostic> {
+        self.items.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
+
+// Errors that stop a compilation
+#[derive(Debug)]
+pub enum CompileError {
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    InvalidRecording {
+        name: String,
+        source: serde_json::Error,
+    },
+    Recording(Diagnostic), // The recording can't be compiled at all
+    Config(String),        // Invalid configuration, compile options or template pack
+    Template(String),      // Template pack's post-processing commands failed
+}
+
+impl CompileError {
+    pub fn code(&self) -> Code {
+        match self {
+            CompileError::Io { .. } => Code::Io,
+            CompileError::InvalidRecording { .. } => Code::InvalidRecording,
+            CompileError::Recording(diagnostic) => diagnostic.code,
+            CompileError::Config(_) => Code::Config,
+            CompileError::Template(_) => Code::Template,
+        }
+    }
+
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            CompileError::Recording(diagnostic) => diagnostic.clone(),
+            _ => Diagnostic::error(self.code(), self.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompileError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            CompileError::InvalidRecording { name, source } => {
+                write!(
+                    f,
+                    "Recording '{}' is not a list of rrweb events, a DevTools Recorder flow or a Selenium IDE project: {}",
+                    name, source
+                )
+            }
+            CompileError::Recording(diagnostic) => write!(f, "{}", diagnostic.message),
+            CompileError::Config(message) | CompileError::Template(message) => {
+                write!(f, "{}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CompileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CompileError::Io { source, .. } => Some(source),
+            CompileError::InvalidRecording { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+
+    fn selector_failed() -> Diagnostic {
+        Diagnostic::warning(
+            Code::SelectorFailed,
+            "Could not generate a stable selector for <div>",
+        )
+        .at_event(12, 1900)
+        .on_node(17)
+        .with_hint("Add an id or data-testid attribute")
+    }
+
+    #[test]
+    fn renders_the_location_after_the_message_and_the_hint_below() {
+        assert_eq!(
+            selector_failed().to_string(),
+            "Warning [selector-failed]: Could not generate a stable selector for <div> \
+             (event 12, t=1900, node 17)\n  hint: Add an id or data-testid attribute"
+        );
+        assert_eq!(
+            Diagnostic::info(Code::RedactedValue, "Replaced an email").to_string(),
+            "Info [redacted-value]: Replaced an email"
+        );
+    }
+
+    #[test]
+    fn serializes_codes_and_severities_as_stable_strings() {
+        let json = serde_json::to_value(selector_failed()).unwrap();
+        assert_eq!(json["severity"], "warning");
+        assert_eq!(json["code"], "selector-failed");
+        assert_eq!(json["event_index"], 12);
+        assert_eq!(json["timestamp"], 1900);
+        assert_eq!(json["node_id"], 17);
+    }
+
+    #[test]
+    fn keeps_the_io_error_as_the_source() {
+        let error = CompileError::Io {
+            path: PathBuf::from("packs/custom"),
+            source: io::Error::new(io::ErrorKind::NotFound, "not found"),
+        };
+        assert_eq!(error.code(), Code::Io);
+        assert_eq!(error.to_string(), "packs/custom: not found");
+        assert_eq!(error.source().unwrap().to_string(), "not found");
+
+        let diagnostic = error.to_diagnostic();
+        assert_eq!(diagnostic.severity, Severity::Error);
+        assert_eq!(diagnostic.code, Code::Io);
+        assert!(CompileError::Config("bad".to_string()).source().is_none());
+    }
+
+    #[test]
+    fn passes_recording_diagnostics_through_unchanged() {
+        let error = CompileError::Recording(
+            Diagnostic::error(Code::NoActions, "Nothing to replay").at_event(3, 40),
+        );
+        assert_eq!(error.code(), Code::NoActions);
+        let diagnostic = error.to_diagnostic();
+        assert_eq!(diagnostic.message, "Nothing to replay");
+        assert_eq!(diagnostic.event_index, Some(3));
+    }
+}
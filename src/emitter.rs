@@ -0,0 +1,221 @@
+// Emitters: how the analyzed actions are printed for one target.
+//
+// The compiler decides which actions are replayed and how (`emission`: locator, `act()`
+// instruction or skipped); an `Emitter` only turns that into code. `emit_with` drives it:
+// the prologue, one block per action followed by a separator (a blank line by default), then
+// the epilogue; `finish` may then rework the whole sequence. Runs of actions recorded on
+// another origin than the start URL's are handed to `cross_origin` together. Each emitter
+// also names the template pack its projects are scaffolded from.
+//
+// Built-in emitters are registered in `EmitterRegistry::default()` and selected by name with
+// `--target`. Other crates can add their own:
+//
+//   let mut options = CompileOptions::default();
+//   options.emitters.register(MyEmitter);
+//   options.target = Some("my-target".to_string());
+//   let project = imitator::compile(&recording, &options)?;
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::cypress::CypressEmitter;
+use crate::devtools::RecorderEmitter;
+use crate::diagnostics::{CompileError, Diagnostics};
+use crate::gherkin::GherkinEmitter;
+use crate::params::{self, ParamSource, Parameter};
+use crate::playwright_test::PlaywrightTestEmitter;
+use crate::puppeteer::PuppeteerEmitter;
+use crate::selenium_python::SeleniumPythonEmitter;
+use crate::side::SideEmitter;
+use crate::stagehand::StagehandEmitter;
+use crate::templates::TemplateSource;
+use crate::{emission, split_origin, ActionWithSelector, Emission, EmitOptions, SelectorStrategy};
+
+pub trait Emitter {
+    // Name the emitter is selected by, e.g. `stagehand` for `--target stagehand`
+    fn name(&self) -> &str;
+
+    // Shown in progress output, e.g. "Stagehand"
+    fn label(&self) -> &str {
+        self.name()
+    }
+
+    // Template pack the project is scaffolded from, unless another one is given
+    fn template(&self) -> TemplateSource;
+
+    // How to install and run the generated project, shown once it is written
+    fn run_command(&self) -> &str;
+
+    // Whether the target can run Stagehand `act()` instructions; without it, act and hybrid
+    // modes fall back to locators
+    fn supports_act(&self) -> bool {
+        false
+    }
+
+    // Selector strategies in the order they are tried, unless the config sets a priority
+    fn selector_order(&self) -> Vec<SelectorStrategy> {
+        SelectorStrategy::DEFAULT_ORDER.to_vec()
+    }
+
+    // A string literal holding `value` in the target language
+    fn string_literal(&self, value: &str) -> String {
+        // JSON string literals are valid in JavaScript, TypeScript and Python
+        serde_json::to_string(value).unwrap_or_else(|_| "\"\"".to_string())
+    }
+
+    // Text after each action's code. Action code ends with a newline, so the default leaves a
+    // blank line between actions.
+    fn separator(&self) -> &str {
+        "\n"
+    }
+
+    // Code before the first action
+    fn prologue(&self, _options: &EmitOptions, _diagnostics: &mut Diagnostics) -> String {
+        String::new()
+    }
+
+    // Code for one action, ending with a newline. `emission` is how the compiler decided the
+    // action is replayed, and `previous` is the action recorded just before it, if any. Emitters
+    // are shared between compiles, so anything a step depends on comes in through here rather
+    // than being kept on `self`.
+    fn action(
+        &self,
+        action: &ActionWithSelector,
+        previous: Option<&ActionWithSelector>,
+        emission: Emission,
+        options: &EmitOptions,
+        diagnostics: &mut Diagnostics,
+    ) -> String;
+
+    // Code for a run of actions recorded on `origin`, another origin than the start URL's.
+    // `blocks` holds their code as generated by `action`, separators included.
+    fn cross_origin(
+        &self,
+        _origin: &str,
+        _actions: &[ActionWithSelector],
+        blocks: String,
+    ) -> String {
+        blocks
+    }
+
+    // Code after the last action
+    fn epilogue(&self, _options: &EmitOptions, _diagnostics: &mut Diagnostics) -> String {
+        String::new()
+    }
+
+    // Rework the whole action sequence once it is generated, before parameters are declared
+    // around it
+    fn finish(&self, action_sequence: String) -> String {
+        action_sequence
+    }
+
+    // Declare the recording's parameters around the action sequence. The default suits
+    // TypeScript targets that run inside an async function with a Playwright `page`.
+    fn wrap_parameters(
+        &self,
+        parameters: &[Parameter],
+        source: ParamSource,
+        initial_url: &str,
+        action_sequence: &str,
+    ) -> String {
+        params::wrap_action_sequence(parameters, source, initial_url, action_sequence)
+    }
+}
+
+// Generate the action sequence for `emitter`
+pub fn emit_with(
+    emitter: &dyn Emitter,
+    actions_with_selectors: &[ActionWithSelector],
+    options: &EmitOptions,
+    diagnostics: &mut Diagnostics,
+) -> Result<String, CompileError> {
+    let (start_origin, _) = split_origin(options.start_url);
+    // None while the page is on the start URL's origin
+    let other_origin = |action: &ActionWithSelector| {
+        let (origin, _) = split_origin(action.url.as_deref()?);
+        (origin != start_origin).then_some(origin)
+    };
+
+    let mut code = emitter.prologue(options, diagnostics);
+    let mut previous = None;
+    for run in actions_with_selectors.chunk_by(|a, b| other_origin(a) == other_origin(b)) {
+        let mut blocks = String::new();
+        for action in run {
+            let emission = emission(action, options.mode);
+            blocks.push_str(&emitter.action(action, previous, emission, options, diagnostics));
+            blocks.push_str(emitter.separator());
+            previous = Some(action);
+        }
+        match other_origin(&run[0]) {
+            Some(origin) => code.push_str(&emitter.cross_origin(&origin, run, blocks)),
+            None => code.push_str(&blocks),
+        }
+    }
+    code.push_str(&emitter.epilogue(options, diagnostics));
+    Ok(emitter.finish(code.trim_end().to_string()))
+}
+
+// Emitters selectable by name
+#[derive(Clone)]
+pub struct EmitterRegistry {
+    emitters: Vec<Arc<dyn Emitter>>,
+}
+
+impl Default for EmitterRegistry {
+    // The built-in emitters; the first one is the default target
+    fn default() -> Self {
+        EmitterRegistry {
+            emitters: vec![
+                Arc::new(StagehandEmitter),
+                Arc::new(PlaywrightTestEmitter),
+                Arc::new(CypressEmitter),
+                Arc::new(SeleniumPythonEmitter),
+                Arc::new(PuppeteerEmitter),
+                Arc::new(RecorderEmitter),
+                Arc::new(SideEmitter),
+                Arc::new(GherkinEmitter),
+            ],
+        }
+    }
+}
+
+impl EmitterRegistry {
+    // Add an emitter, replacing any registered under the same name
+    pub fn register(&mut self, emitter: impl Emitter + 'static) {
+        self.emitters
+            .retain(|existing| existing.name() != emitter.name());
+        self.emitters.push(Arc::new(emitter));
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Emitter> {
+        self.emitters
+            .iter()
+            .find(|emitter| emitter.name() == name)
+            .map(|emitter| emitter.as_ref())
+    }
+
+    // The emitter for `name`, or the default one when no target is given
+    pub fn resolve(&self, name: Option<&str>) -> Result<&dyn Emitter, CompileError> {
+        let emitter = match name {
+            Some(name) => self.get(name),
+            None => self.emitters.first().map(|emitter| emitter.as_ref()),
+        };
+        emitter.ok_or_else(|| {
+            CompileError::Config(format!(
+                "Unknown target '{}'; expected one of: {}",
+                name.unwrap_or_default(),
+                self.names().join(", ")
+            ))
+        })
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.emitters.iter().map(|emitter| emitter.name()).collect()
+    }
+}
+
+impl fmt::Debug for EmitterRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
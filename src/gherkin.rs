@@ -0,0 +1,318 @@
+// Gherkin emitter: the action sequence as a `.feature` scenario for BDD reviews.
+//
+// Steps read like the flow a user walked through ("When I click the 'Checkout' button",
+// "And I fill 'Email' with '<email>'"), built from the role, label and text that describe each
+// target. Targets the recording has no usable name for fall back to their selector ("When I
+// click the element at '#menu'"). The `gherkin` pack ships playwright-bdd step definitions for
+// every step written here, so the feature runs as it is.
+//
+// Parameterized values are Scenario Outline placeholders, and the recorded defaults are the one
+// row of its Examples table. A feature is read by people rather than debugged, so there are no
+// timestamp markers on the steps and `explain` can't map its lines back to actions.
+
+use crate::diagnostics::{Code, Diagnostic, Diagnostics};
+use crate::emitter::Emitter;
+use crate::params::{ParamSource, Parameter};
+use crate::templates::TemplateSource;
+use crate::{
+    action_summary, display_selector, pressed_key, replayed_value, upload_fixture, ActionType,
+    ActionWithSelector, Control, Emission, EmitOptions, PLACEHOLDER_FIXTURE,
+};
+
+pub struct GherkinEmitter;
+
+impl Emitter for GherkinEmitter {
+    fn name(&self) -> &str {
+        "gherkin"
+    }
+
+    fn label(&self) -> &str {
+        "Gherkin (playwright-bdd)"
+    }
+
+    fn template(&self) -> TemplateSource {
+        TemplateSource::Builtin("gherkin")
+    }
+
+    fn run_command(&self) -> &str {
+        "npm install && npm test"
+    }
+
+    // One step per line, without blank lines between them
+    fn separator(&self) -> &str {
+        ""
+    }
+
+    fn prologue(&self, options: &EmitOptions, _diagnostics: &mut Diagnostics) -> String {
+        let mut steps = format!("    Given I am on {}\n", quoted(options.start_url));
+        if let Some(button_pattern) = options.cookie_banner {
+            steps.push_str(&format!(
+                "    Given I dismiss any cookie banner matching {}\n",
+                quoted(button_pattern)
+            ));
+        }
+        steps
+    }
+
+    fn action(
+        &self,
+        action: &ActionWithSelector,
+        _previous: Option<&ActionWithSelector>,
+        emission: Emission,
+        options: &EmitOptions,
+        diagnostics: &mut Diagnostics,
+    ) -> String {
+        // Skipped actions were reported when their selector failed; reviewers still see them
+        if emission == Emission::Skipped {
+            return format!(
+                "    # Skipped, no stable selector: {}\n",
+                action_summary(action)
+            );
+        }
+        if !action.frames.is_empty() {
+            diagnostics.push(
+                Diagnostic::warning(
+                    Code::UnsupportedStep,
+                    "The Gherkin steps can't reach into iframes; the action was left out.",
+                )
+                .at_event(action.event_index, action.timestamp)
+                .on_node(action.rrweb_id),
+            );
+            return format!(
+                "    # Left out, inside an iframe: {}\n",
+                action_summary(action)
+            );
+        }
+
+        let mut code = String::new();
+        let name = target_name(&action.description).map(|(name, kind)| (quoted(name), kind));
+        let selector = quoted(&display_selector(action));
+        let value = match &action.parameter {
+            Some(parameter) => format!("'<{}>'", parameter),
+            None => quoted(&replayed_value(action).unwrap_or_default()),
+        };
+        let step = match (&action.action_type, action.control, name) {
+            (ActionType::Click, _, Some((name, kind))) => match kind {
+                "button" | "link" | "checkbox" | "radio button" => {
+                    format!("I click the {} {}", name, kind)
+                }
+                _ => format!("I click {}", name),
+            },
+            (ActionType::Click, _, None) => format!("I click the element at {}", selector),
+            (ActionType::Input, Control::Select, Some((name, _))) => {
+                format!("I select {} from {}", value, name)
+            }
+            (ActionType::Input, Control::Select, None) => {
+                format!("I select {} from the dropdown at {}", value, selector)
+            }
+            (ActionType::Input, Control::Checkbox, name) => {
+                let verb = if action.checked == Some(false) {
+                    "uncheck"
+                } else {
+                    "check"
+                };
+                match name {
+                    Some((name, _)) => format!("I {} {}", verb, name),
+                    None => format!("I {} the checkbox at {}", verb, selector),
+                }
+            }
+            (ActionType::Input, Control::Radio, Some((name, _))) => format!("I choose {}", name),
+            (ActionType::Input, Control::Radio, None) => {
+                format!("I choose the radio button at {}", selector)
+            }
+            (ActionType::Input, Control::Field, Some((name, _))) => {
+                format!("I fill {} with {}", name, value)
+            }
+            (ActionType::Input, Control::Field, None) => {
+                format!("I fill the field at {} with {}", selector, value)
+            }
+            (ActionType::TypeText, _, Some((name, _))) => {
+                format!("I type {} into {}", value, name)
+            }
+            (ActionType::TypeText, _, None) => {
+                format!("I type {} into the editor at {}", value, selector)
+            }
+            (ActionType::KeyPress, _, Some((name, _))) => {
+                format!("I press {} in {}", quoted(pressed_key(action)), name)
+            }
+            (ActionType::KeyPress, _, None) => format!(
+                "I press {} in the element at {}",
+                quoted(pressed_key(action)),
+                selector
+            ),
+            (ActionType::Upload, _, name) => {
+                let fixture_path = upload_fixture(action, options.fixture_files, diagnostics)
+                    .unwrap_or_else(|| {
+                        code.push_str(&format!(
+                            "    # Recorded upload '{}' has no mapped fixture, using placeholder:\n",
+                            action
+                                .value
+                                .as_deref()
+                                .unwrap_or_default()
+                                .replace('\n', " ")
+                        ));
+                        PLACEHOLDER_FIXTURE.to_string()
+                    });
+                match name {
+                    Some((name, _)) => format!("I upload {} to {}", quoted(&fixture_path), name),
+                    None => format!(
+                        "I upload {} to the file input at {}",
+                        quoted(&fixture_path),
+                        selector
+                    ),
+                }
+            }
+        };
+        code.push_str(&format!("    When {}\n", step));
+        if action.navigates {
+            code.push_str("    Then a new page loads\n");
+        }
+        code
+    }
+
+    // A keyword repeating the previous step's reads as "And", the way features are written
+    fn finish(&self, action_sequence: String) -> String {
+        let mut previous = "";
+        let mut lines = Vec::new();
+        for line in action_sequence.lines() {
+            let step = line.trim_start();
+            let indent = &line[..line.len() - step.len()];
+            match step.split_once(' ') {
+                Some((keyword @ ("Given" | "When" | "Then"), rest)) if keyword == previous => {
+                    lines.push(format!("{}And {}", indent, rest));
+                }
+                Some((keyword @ ("Given" | "When" | "Then"), _)) => {
+                    previous = keyword;
+                    lines.push(line.to_string());
+                }
+                _ => lines.push(line.to_string()),
+            }
+        }
+        lines.join("\n")
+    }
+
+    // Parameters are the Scenario Outline's placeholders, with the recorded defaults as its one
+    // example. Other values are replayed by adding rows, so environment variables and data
+    // files don't apply.
+    fn wrap_parameters(
+        &self,
+        parameters: &[Parameter],
+        _source: ParamSource,
+        _initial_url: &str,
+        action_sequence: &str,
+    ) -> String {
+        let columns: Vec<(String, String)> = parameters
+            .iter()
+            .map(|parameter| {
+                let cell = table_cell(&parameter.default_value);
+                let width = parameter.name.chars().count().max(cell.chars().count());
+                (
+                    format!("{:width$}", parameter.name),
+                    format!("{:width$}", cell),
+                )
+            })
+            .collect();
+        let row = |cells: Vec<&str>| format!("      | {} |", cells.join(" | "));
+        format!(
+            "{}\n\n    Examples:\n{}\n{}",
+            action_sequence,
+            row(columns.iter().map(|(name, _)| name.as_str()).collect()),
+            row(columns.iter().map(|(_, cell)| cell.as_str()).collect())
+        )
+    }
+}
+
+// Name and kind of a described target, e.g. ("Checkout", "button") for `"Checkout" button`.
+// Names the description cut short can't be matched on the page, so those targets have none.
+fn target_name(description: &str) -> Option<(&str, &str)> {
+    let (name, kind) = description.strip_prefix('"')?.rsplit_once("\" ")?;
+    (!name.ends_with('…')).then_some((name, kind))
+}
+
+// Argument for a `{string}` step parameter: single-quoted, or double-quoted when the text has
+// an apostrophe but no double quote
+fn quoted(text: &str) -> String {
+    let quote = if text.contains('\'') && !text.contains('"') {
+        '"'
+    } else {
+        '\''
+    };
+    format!("{}{}{}", quote, escaped(text, quote), quote)
+}
+
+// Text between `quote`s. Cucumber unescapes the quote; the step definitions turn `\\` and `\n`
+// back into a backslash and a line break.
+fn escaped(text: &str, quote: char) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            c if c == quote => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Examples table cell. Cells replace `'<name>'` placeholders, so they are escaped for a single-
+// quoted argument first, then with Gherkin's table escapes for backslashes and pipes.
+fn table_cell(value: &str) -> String {
+    escaped(value, '\'')
+        .replace('\\', "\\\\")
+        .replace('|', "\\|")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostics::Severity;
+    use crate::testing::{action, assert_golden, emit_action, emit_recording, shop_recording};
+
+    #[test]
+    fn quotes_arguments_so_cucumber_reads_them_back() {
+        assert_eq!(quoted("Checkout"), "'Checkout'");
+        assert_eq!(quoted("input[name='q']"), "\"input[name='q']\"");
+        assert_eq!(quoted("it's \"here\""), "'it\\'s \"here\"'");
+        assert_eq!(quoted("#user\\:name"), "'#user\\\\:name'");
+        assert_eq!(quoted("two\nlines"), "'two\\nlines'");
+    }
+
+    #[test]
+    fn escapes_example_cells_for_their_quoted_placeholder() {
+        assert_eq!(table_cell("O'Brien"), "O\\\\'Brien");
+        assert_eq!(table_cell("a|b\\c"), "a\\|b\\\\\\\\c");
+        assert_eq!(table_cell("two\nlines"), "two\\\\nlines");
+    }
+
+    #[test]
+    fn writes_selectors_with_apostrophes_as_one_argument() {
+        let search = action(ActionType::Click, Control::Field, "input[name='q']", None);
+        let (code, _) = emit_action(&GherkinEmitter, &search);
+        assert_eq!(
+            code,
+            "    When I click the element at \"input[name='q']\"\n"
+        );
+    }
+
+    #[test]
+    fn reports_actions_inside_iframes_it_leaves_out() {
+        let mut pay = action(ActionType::Click, Control::Field, "#pay", None);
+        pay.frames = vec!["#checkout-frame".to_string()];
+        let (code, diagnostics) = emit_action(&GherkinEmitter, &pay);
+        assert!(code.starts_with("    # Left out, inside an iframe:"));
+        let warnings: Vec<_> = diagnostics.iter().collect();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].severity, Severity::Warning);
+        assert_eq!(warnings[0].code, Code::UnsupportedStep);
+    }
+
+    #[test]
+    fn writes_the_shop_checkout_as_feature_steps() {
+        let code = emit_recording(&GherkinEmitter, &shop_recording());
+        assert_golden("gherkin.feature", &code);
+    }
+}
@@ -0,0 +1,2226 @@
+// imitator: compiles rrweb recordings into runnable browser automation projects.
+//
+// The pipeline runs in four stages, each usable on its own:
+//   load_recording      read an rrweb recording (JSON array of events), a DevTools Recorder
+//                       flow or a Selenium IDE project
+//   preprocess          rebuild the DOM and extract simplified user actions
+//   generate_selectors  attach a stable selector and description to every action
+//   emit                generate the action sequence code for the project's entry file
+//
+// `analyze` runs the first three stages plus redaction and parameterization, and `compile`
+// runs everything and renders a template pack into an in-memory `CompiledProject`.
+// Problems are collected as `diagnostics::Diagnostic`s on the results, and failures are
+// returned as `CompileError`s. Nothing is printed unless `CompileOptions::logger` turns
+// progress output on; rendering the diagnostics is left to the caller.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub mod anonymize;
+pub mod config;
+pub mod cypress;
+pub mod devtools;
+pub mod diagnostics;
+pub mod emitter;
+pub mod gherkin;
+pub mod logging;
+pub mod params;
+pub mod playwright;
+pub mod playwright_test;
+pub mod puppeteer;
+pub mod redact;
+pub mod report;
+pub mod review;
+pub mod selenium_python;
+pub mod side;
+pub mod sourcemap;
+pub mod stagehand;
+mod template_engine;
+pub mod templates;
+#[cfg(test)]
+mod testing;
+
+use config::{Config, Settings};
+pub use diagnostics::CompileError;
+use diagnostics::{Code, Diagnostic, Diagnostics};
+use emitter::EmitterRegistry;
+use logging::Logger;
+use params::Parameter;
+use redact::{FieldHints, Finding, RedactionRules};
+use templates::{TemplateFile, TemplatePack, TemplateSource};
+
+// --- Type Aliases ---
+pub type DomMap = HashMap<i64, NodeInfo>;
+pub type SimplifiedActionList = Vec<SimplifiedAction>;
+// One DOM map per full snapshot, in recording order, and the actions recorded on them
+pub type PreprocessingResultData = (Vec<DomMap>, SimplifiedActionList);
+// rrweb_id -> (text, checked, last_timestamp, last_event_index)
+type InputBuffer = HashMap<i64, (String, Option<bool>, i64, usize)>;
+
+// --- Data Structures ---
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Event {
+    #[serde(rename = "type")]
+    pub event_type: i64,
+    pub data: Value, // Using Value for flexibility initially
+    pub timestamp: i64,
+}
+
+// A loaded recording and the name its generated project is given
+#[derive(Debug, Clone)]
+pub struct Recording {
+    pub name: String,              // Project folder and test name, usually the file stem
+    pub file_name: Option<String>, // Shown in the generated README when known
+    pub path: Option<PathBuf>,     // Absolute path, recorded in the project's source map
+    pub events: Vec<Event>,
+    pub flow: Option<devtools::Flow>, // Set instead of `events` for a DevTools Recorder flow
+    pub side: Option<side::SideProject>, // Set instead of `events` for a Selenium IDE project
+}
+
+impl Recording {
+    pub fn from_json(name: &str, json: &str) -> Result<Self, CompileError> {
+        let invalid = |source| CompileError::InvalidRecording {
+            name: name.to_string(),
+            source,
+        };
+        let (events, flow, side) = if is_event_list(json) {
+            (serde_json::from_str(json).map_err(invalid)?, None, None)
+        } else {
+            // Other tools' formats are JSON objects, told apart by their top-level keys
+            let value: Value = serde_json::from_str(json).map_err(invalid)?;
+            if value.get("tests").is_some() {
+                (
+                    Vec::new(),
+                    None,
+                    Some(serde_json::from_value(value).map_err(invalid)?),
+                )
+            } else {
+                (
+                    Vec::new(),
+                    Some(serde_json::from_value(value).map_err(invalid)?),
+                    None,
+                )
+            }
+        };
+        Ok(Recording {
+            name: name.to_string(),
+            file_name: None,
+            path: None,
+            events,
+            flow,
+            side,
+        })
+    }
+
+    // The Recorder steps or Selenium IDE commands an imported action's event index points
+    // into; None for rrweb recordings
+    pub fn imported_steps(&self) -> Option<Vec<&Value>> {
+        match (&self.flow, &self.side) {
+            (Some(flow), _) => Some(flow.steps.iter().collect()),
+            (None, Some(project)) => Some(project.commands().collect()),
+            (None, None) => None,
+        }
+    }
+}
+
+// rrweb recordings are JSON arrays; flows and Selenium IDE projects are JSON objects
+pub(crate) fn is_event_list(json: &str) -> bool {
+    !json.trim_start().starts_with('{')
+}
+
+#[derive(Debug, Clone)]
+pub struct NodeInfo {
+    pub rrweb_id: i64,
+    pub tag_name: Option<String>,
+    pub attributes: HashMap<String, String>,
+    pub parent_id: Option<i64>,
+    pub child_ids: Vec<i64>, // Kept in document order so text can be reassembled
+    pub text_content: Option<String>,
+    pub is_shadow_host: bool, // rrweb `isShadowHost`: element owns a shadow root
+    pub is_shadow: bool, // rrweb `isShadow`: node is a direct child of its parent's shadow root
+}
+
+#[derive(Debug, Clone)]
+pub enum ActionType {
+    Click,
+    Input,
+    // Typing into a contenteditable host, reconstructed from text mutations
+    TypeText,
+    // File chosen in an <input type="file">; value is the recorded file name
+    Upload,
+    // Key pressed in the element, e.g. Enter to submit a form; value is the key name
+    KeyPress,
+    // Add other types like Scroll, Navigate, etc. later
+}
+
+// Kind of element an action targets, for targets with a command per kind of form control
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Control {
+    Field, // Text-like inputs, textareas, editors and anything that isn't a form control
+    Select,
+    Checkbox,
+    Radio,
+}
+
+#[derive(Debug, Clone)]
+pub struct SimplifiedAction {
+    pub action_type: ActionType,
+    pub rrweb_id: i64,         // ID of the element interacted with
+    pub value: Option<String>, // For input actions
+    pub checked: Option<bool>, // rrweb `isChecked`, for input actions
+    pub timestamp: i64,
+    pub event_index: usize,  // Last rrweb event that contributed to the action
+    pub url: Option<String>, // Page the action happened on, from the latest Meta event
+    pub navigates: bool,     // A new page was loaded before the next action
+    pub page: usize, // Full snapshot the node id refers to; rrweb numbers every page from 1 again
+}
+
+#[derive(Debug, Clone)]
+pub struct ActionWithSelector {
+    pub action_type: ActionType,
+    pub rrweb_id: i64,
+    pub value: Option<String>,
+    pub checked: Option<bool>,
+    pub control: Control,
+    pub select_by_label: bool, // Option named by its visible text rather than its value
+    pub timestamp: i64,
+    pub event_index: usize,
+    pub url: Option<String>,
+    pub navigates: bool,
+    pub page: usize,
+    pub selector: String,                   // CSS or XPath
+    pub strategy: Option<SelectorStrategy>, // Strategy that produced `selector`, None if it failed
+    pub candidates: Vec<SelectorCandidate>, // Every strategy's selector, in priority order
+    pub shadow_hosts: Vec<String>,          // Selectors of enclosing shadow hosts, outermost first
+    pub frames: Vec<String>,                // Selectors of enclosing iframes, outermost first
+    pub redaction: Option<Finding>, // Set when the value is sensitive and must not be emitted
+    pub parameter: Option<String>,  // Name on the generated `params` object, if parameterized
+    pub masked: bool,               // Value was masked by the recorder (e.g. `maskAllInputs`)
+    pub description: String,        // Human-readable target, e.g. `"Checkout" button`
+}
+
+// Attribute-based selector strategies, tried in the configured order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SelectorStrategy {
+    Id,
+    TestId,
+    DataCy,
+    Name,
+    Class,
+    Attribute(String), // Custom test attribute such as `data-qa`
+    Recorder,          // Taken as-is from a DevTools Recorder flow; never configured
+}
+
+impl SelectorStrategy {
+    pub const NAMES: [&'static str; 5] = ["id", "data-testid", "data-cy", "name", "class"];
+    pub const DEFAULT_ORDER: [SelectorStrategy; 5] = [
+        SelectorStrategy::Id,
+        SelectorStrategy::TestId,
+        SelectorStrategy::DataCy,
+        SelectorStrategy::Name,
+        SelectorStrategy::Class,
+    ];
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "id" => Some(SelectorStrategy::Id),
+            "data-testid" => Some(SelectorStrategy::TestId),
+            "data-cy" => Some(SelectorStrategy::DataCy),
+            "name" => Some(SelectorStrategy::Name),
+            "class" => Some(SelectorStrategy::Class),
+            _ if value.len() > "data-".len() && value.starts_with("data-") => {
+                Some(SelectorStrategy::Attribute(value.to_string()))
+            }
+            _ => None,
+        }
+    }
+
+    // How likely the strategy's selectors survive a redeploy, from 0 to 1. Test attributes exist
+    // for automation; class names are the first thing to change.
+    pub fn stability(&self) -> f64 {
+        match self {
+            SelectorStrategy::TestId
+            | SelectorStrategy::DataCy
+            | SelectorStrategy::Attribute(_) => 1.0,
+            SelectorStrategy::Id => 0.9,
+            SelectorStrategy::Name => 0.7,
+            SelectorStrategy::Class => 0.4,
+            // Whatever the Recorder chose, from an id to an nth-child path
+            SelectorStrategy::Recorder => 0.5,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            SelectorStrategy::Id => "id".to_string(),
+            SelectorStrategy::TestId => "data-testid".to_string(),
+            SelectorStrategy::DataCy => "data-cy".to_string(),
+            SelectorStrategy::Name => "name".to_string(),
+            SelectorStrategy::Class => "class".to_string(),
+            SelectorStrategy::Attribute(attribute) => attribute.clone(),
+            SelectorStrategy::Recorder => "recorder".to_string(),
+        }
+    }
+}
+
+// A selector one strategy gives an action's target, as listed in the compile report
+#[derive(Debug, Clone)]
+pub struct SelectorCandidate {
+    pub strategy: SelectorStrategy,
+    pub selector: String,
+    pub shadow_hosts: Vec<String>, // Hosts the selector is scoped to, outermost first
+    pub matches: usize,            // Nodes in the final DOM the same strategy gives this selector
+    pub score: f64,                // Strategy stability divided by `matches`, from 0 to 1
+}
+
+// Selector strategies plus the generated ids and class names that must never be used
+#[derive(Debug, Clone)]
+pub struct SelectorRules {
+    pub strategies: Vec<SelectorStrategy>,
+    pub deny_ids: Vec<String>,
+    pub deny_classes: Vec<String>,
+}
+
+// How each action is emitted in the Stagehand script
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmitMode {
+    Locator, // Playwright locators only
+    Act,     // Stagehand natural-language `page.act()` instructions
+    Hybrid,  // Locators, falling back to `page.act()` when the locator fails
+}
+
+impl EmitMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "locator" => Some(EmitMode::Locator),
+            "act" => Some(EmitMode::Act),
+            "hybrid" => Some(EmitMode::Hybrid),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EmitMode::Locator => "locator",
+            EmitMode::Act => "act",
+            EmitMode::Hybrid => "hybrid",
+        }
+    }
+}
+
+// Everything `emit` needs besides the actions
+#[derive(Debug, Clone)]
+pub struct EmitOptions<'a> {
+    pub mode: EmitMode,
+    // Recorded upload file name -> real fixture file shipped with the generated project
+    pub fixture_files: &'a HashMap<String, PathBuf>,
+    // Button name pattern for the cookie banner dismissal, None to skip it
+    pub cookie_banner: Option<&'a str>,
+    pub start_url: &'a str,
+}
+
+// Options for `analyze` and `compile`
+#[derive(Debug, Clone, Default)]
+pub struct CompileOptions {
+    // Recorded upload file name -> real fixture file to ship with the generated project
+    pub fixture_files: HashMap<String, PathBuf>,
+    pub config: Config,
+    // Command-line flags, applied over the config once the recording's domain is known
+    pub overrides: Settings,
+    pub emitters: EmitterRegistry,
+    pub target: Option<String>, // Name of a registered emitter; None for the default
+    pub template: Option<TemplateSource>, // None for the target's own template pack
+    pub test: Option<String>,   // Test to compile from a Selenium IDE project; None for the first
+    pub logger: Logger,         // Progress output; off by default
+}
+
+// A compiled project, held in memory until it is written
+pub struct CompiledProject {
+    pub name: String,
+    pub initial_url: String,
+    pub settings: Settings, // Config and overrides resolved for the recording's domain
+    pub actions: Vec<ActionWithSelector>,
+    pub parameters: Vec<Parameter>,
+    pub template: String, // Description of the template pack, for progress output
+    pub entry: PathBuf,   // File holding the action sequence, relative to the project
+    pub files: Vec<TemplateFile>, // Every project file, including fixtures and parameter files
+    pub diagnostics: Diagnostics, // From compiling and, once written, post-processing
+    pub report: report::Report,
+    pack: TemplatePack, // Kept for its post-processing commands
+    logger: Logger,
+}
+
+impl CompiledProject {
+    pub fn entry_file(&self) -> Option<&TemplateFile> {
+        self.files.iter().find(|file| file.path == self.entry)
+    }
+
+    // Write the project into `project_dir` and run the pack's post-processing commands
+    pub fn write(&mut self, project_dir: &Path) -> Result<(), CompileError> {
+        templates::write_files(&self.files, project_dir)?;
+        for file in &self.files {
+            detail!(self.logger, "  Wrote project file: {:?}", file.path);
+        }
+        self.pack
+            .run_commands(project_dir, &mut self.diagnostics, &self.logger)
+    }
+}
+
+// Generated project paths used by upload actions, relative to the project root
+pub(crate) const FIXTURES_DIR: &str = "fixtures";
+pub(crate) const PLACEHOLDER_FIXTURE: &str = "fixtures/sample-upload.txt";
+
+// Project path of the fixture mapped to an upload action's recorded file name. Unmapped
+// uploads are reported and get None; emitters fall back to PLACEHOLDER_FIXTURE.
+pub(crate) fn upload_fixture(
+    action: &ActionWithSelector,
+    fixture_files: &HashMap<String, PathBuf>,
+    diagnostics: &mut Diagnostics,
+) -> Option<String> {
+    let recorded_name = action.value.as_deref().unwrap_or_default();
+    if let Some(file_name) = fixture_files
+        .get(recorded_name)
+        .and_then(|path| path.file_name())
+    {
+        return Some(format!("{}/{}", FIXTURES_DIR, file_name.to_string_lossy()));
+    }
+    diagnostics.push(
+        Diagnostic::info(
+            Code::MissingFixture,
+            format!(
+                "Recorded upload '{}' has no mapped fixture; using the placeholder file.",
+                recorded_name.replace('\n', " ")
+            ),
+        )
+        .at_event(action.event_index, action.timestamp)
+        .on_node(action.rrweb_id)
+        .with_hint(format!("Map it with --fixture '{}=<path>'", recorded_name)),
+    );
+    None
+}
+
+// Everything known about a recording before code generation
+pub struct RecordingAnalysis {
+    pub initial_url: String,
+    pub viewport: Option<(i64, i64)>, // From the Meta event, when the recorder stored one
+    pub settings: Settings, // Config and command-line flags resolved for the recording's domain
+    pub selector_rules: SelectorRules, // Resolved for the target, which may prefer its own order
+    pub dom_maps: Vec<DomMap>, // One per full snapshot, indexed by an action's `page`
+    pub actions_with_selectors: Vec<ActionWithSelector>,
+    pub parameters: Vec<Parameter>,
+    pub diagnostics: Diagnostics,
+}
+
+impl RecordingAnalysis {
+    // The recorded node an action targets, on the page it was recorded on
+    pub fn node(&self, action: &ActionWithSelector) -> Option<&NodeInfo> {
+        self.dom_maps.get(action.page)?.get(&action.rrweb_id)
+    }
+}
+
+// What preprocessing and selector generation produce, for recordings made by other tools
+// that list their actions directly
+pub(crate) struct ImportedActions {
+    pub initial_url: Option<String>,
+    pub viewport: Option<(i64, i64)>,
+    pub dom_map: DomMap, // One stand-in node per target, built from its selectors
+    pub actions: Vec<ActionWithSelector>,
+}
+
+// Read a recording from disk, named after its file stem
+pub fn load_recording(path: &Path) -> Result<Recording, CompileError> {
+    let name = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .ok_or_else(|| {
+            CompileError::Config(format!(
+                "Could not get file stem from input path: {}",
+                path.display()
+            ))
+        })?;
+
+    let content = fs::read_to_string(path).map_err(|source| CompileError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+
+    let mut recording = Recording::from_json(name, &content)?;
+    recording.file_name = path
+        .file_name()
+        .map(|file_name| file_name.to_string_lossy().into_owned());
+    recording.path = Some(fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf()));
+    Ok(recording)
+}
+
+// Stages 1 and 2: preprocess, generate selectors, redact and parameterize
+pub fn analyze(
+    recording: &Recording,
+    options: &CompileOptions,
+) -> Result<RecordingAnalysis, CompileError> {
+    let logger = &options.logger;
+    let emitter = options.emitters.resolve(options.target.as_deref())?;
+    let rrweb_events = &recording.events;
+    let mut diagnostics = Diagnostics::default();
+
+    // Recorder flows and Selenium IDE tests already list their actions with selectors, so they
+    // skip stages 1 and 2
+    let imported = match (&recording.flow, &recording.side) {
+        (Some(flow), _) => Some(devtools::import(flow, &mut diagnostics)),
+        (None, Some(project)) => Some(side::import(
+            project,
+            options.test.as_deref(),
+            &mut diagnostics,
+        )?),
+        (None, None) => None,
+    };
+
+    // Extract initial metadata (like starting URL)
+    let meta_event = find_event_by_type(rrweb_events, 4); // Type 4 is Meta
+    let initial_url = match &imported {
+        Some(flow) => flow.initial_url.clone(),
+        None => meta_event
+            .and_then(|event| event.data.get("href"))
+            .and_then(|href| href.as_str())
+            .map(String::from),
+    }
+    .unwrap_or_else(|| {
+        let (message, hint) = match imported {
+            Some(_) => (
+                "Could not find initial URL (navigate step or open command). Using placeholder.",
+                "Start the flow or test by opening the page you want to replay",
+            ),
+            None => (
+                "Could not find initial URL (Meta event type 4 with href). Using placeholder.",
+                "Start recording before the page loads so rrweb records a Meta event",
+            ),
+        };
+        diagnostics.push(Diagnostic::warning(Code::MissingStartUrl, message).with_hint(hint));
+        "http://example.com".to_string()
+    });
+    info!(logger, "Initial URL: {}", initial_url);
+    let viewport = match &imported {
+        Some(flow) => flow.viewport,
+        None => meta_event.and_then(|event| {
+            Some((
+                event.data.get("width")?.as_i64()?,
+                event.data.get("height")?.as_i64()?,
+            ))
+        }),
+    };
+    for domain in options.config.domains_for(&initial_url) {
+        detail!(
+            logger,
+            "Applying config overrides for domain '{}'",
+            domain.pattern
+        );
+    }
+    let settings = options.config.resolve(&initial_url, &options.overrides);
+    let selector_rules = settings.selector_rules(&emitter.selector_order());
+
+    let (dom_maps, mut actions_with_selectors) = match imported {
+        Some(flow) => {
+            info!(logger, "Step 2: Importing recorded steps...");
+            info!(logger, "Imported {} actions.", flow.actions.len());
+            (vec![flow.dom_map], flow.actions)
+        }
+        None => {
+            // --- Stage 1: Pre-processing and Action Extraction ---
+            info!(logger, "Step 2: Pre-processing and extracting actions...");
+            let (dom_maps, simplified_actions) = preprocess(rrweb_events, &mut diagnostics)?;
+            for (page, dom_map) in dom_maps.iter().enumerate() {
+                detail!(
+                    logger,
+                    "  Page {} snapshot has {} nodes.",
+                    page,
+                    dom_map.len()
+                );
+            }
+            info!(
+                logger,
+                "Extracted {} simplified actions.",
+                simplified_actions.len()
+            );
+
+            // --- Stage 2: Selector Generation (LLM-Assisted) ---
+            info!(logger, "Step 3: Generating selectors...");
+            let actions_with_selectors = generate_selectors(
+                &simplified_actions,
+                &dom_maps,
+                &selector_rules,
+                &mut diagnostics,
+            )?;
+            (dom_maps, actions_with_selectors)
+        }
+    };
+    let findings = redact_actions(
+        &mut actions_with_selectors,
+        &dom_maps,
+        &settings.redaction_rules(),
+    );
+    if !findings.is_empty() {
+        // Only kinds and detection sources are logged, never the values themselves
+        info!(logger, "  Redacted {} sensitive value(s).", findings.len());
+        for action in &actions_with_selectors {
+            let Some(finding) = &action.redaction else {
+                continue;
+            };
+            diagnostics.push(
+                Diagnostic::info(
+                    Code::RedactedValue,
+                    format!(
+                        "{} value replaced by a placeholder (detected by {})",
+                        finding.kind, finding.source
+                    ),
+                )
+                .at_event(action.event_index, finding.timestamp)
+                .on_node(finding.rrweb_id),
+            );
+        }
+    }
+
+    let parameters = params::derive_parameters(
+        &mut actions_with_selectors,
+        &dom_maps,
+        settings.param_mode(),
+    );
+    for action in actions_with_selectors.iter().filter(|a| a.masked) {
+        diagnostics.push(
+            Diagnostic::warning(
+                Code::MaskedInput,
+                format!(
+                    "Input was masked by the recorder ({} characters typed); emitting parameter '{}' instead of the mask.",
+                    action.value.as_deref().map_or(0, |v| v.chars().count()),
+                    action.parameter.as_deref().unwrap_or("unknown")
+                ),
+            )
+            .at_event(action.event_index, action.timestamp)
+            .on_node(action.rrweb_id)
+            .with_hint("Set the parameter's value before running the generated project"),
+        );
+    }
+    if !parameters.is_empty() {
+        info!(
+            logger,
+            "  Parameterized {} input value(s).",
+            parameters.len()
+        );
+    }
+
+    Ok(RecordingAnalysis {
+        initial_url,
+        viewport,
+        settings,
+        selector_rules,
+        dom_maps,
+        actions_with_selectors,
+        parameters,
+        diagnostics,
+    })
+}
+
+// All stages: analyze the recording, generate code and render the template pack
+pub fn compile(
+    recording: &Recording,
+    options: &CompileOptions,
+) -> Result<CompiledProject, CompileError> {
+    let logger = &options.logger;
+    let emitter = options.emitters.resolve(options.target.as_deref())?;
+    let mut analysis = analyze(recording, options)?;
+    let settings = &analysis.settings;
+    let parameters = &analysis.parameters;
+
+    // --- Stage 3: Code Generation ---
+    info!(logger, "Step 4: Generating action sequence code...");
+    let mut mode = settings.emit_mode();
+    if mode != EmitMode::Locator && !emitter.supports_act() {
+        analysis.diagnostics.push(
+            Diagnostic::warning(
+                Code::Config,
+                format!(
+                    "Emit mode '{}' needs Stagehand's act(); the {} target emits locators only.",
+                    mode.name(),
+                    emitter.name()
+                ),
+            )
+            .with_hint("Use --mode locator, or --target stagehand"),
+        );
+        mode = EmitMode::Locator;
+    }
+    let emit_options = EmitOptions {
+        mode,
+        fixture_files: &options.fixture_files,
+        cookie_banner: settings.cookie_banner(),
+        start_url: &analysis.initial_url,
+    };
+    let mut action_sequence = emitter::emit_with(
+        emitter,
+        &analysis.actions_with_selectors,
+        &emit_options,
+        &mut analysis.diagnostics,
+    )?;
+    if !parameters.is_empty() {
+        action_sequence = emitter.wrap_parameters(
+            parameters,
+            settings.param_source(),
+            &analysis.initial_url,
+            &action_sequence,
+        );
+    }
+
+    let report = report::build(recording, &analysis, mode);
+
+    // --- Render Template ---
+    let pack = options
+        .template
+        .clone()
+        .unwrap_or_else(|| emitter.template())
+        .load()?;
+    info!(
+        logger,
+        "Step 5: Rendering template pack {}...", pack.description
+    );
+    let slots = template_slots(&analysis, &action_sequence, recording);
+    let mut files = pack.project_files(&slots)?;
+    let entry = PathBuf::from(&pack.manifest.entry);
+    if let Some(entry_file) = files.iter().find(|file| file.path == entry) {
+        let source_map = sourcemap::build(
+            &entry,
+            &String::from_utf8_lossy(&entry_file.contents),
+            &action_sequence,
+            &analysis.actions_with_selectors,
+            recording,
+        );
+        files.push(TemplateFile {
+            path: PathBuf::from(sourcemap::FILE),
+            contents: source_map.to_json().into_bytes(),
+        });
+    }
+    files.extend(fixture_files(&options.fixture_files, logger)?);
+    files.extend(parameter_file(parameters, settings, &pack, logger));
+
+    Ok(CompiledProject {
+        name: recording.name.clone(),
+        entry,
+        template: pack.description.clone(),
+        files,
+        pack,
+        initial_url: analysis.initial_url,
+        settings: analysis.settings,
+        actions: analysis.actions_with_selectors,
+        parameters: analysis.parameters,
+        diagnostics: analysis.diagnostics,
+        report,
+        logger: *logger,
+    })
+}
+
+// Slots available to project templates (see templates.rs and template_engine.rs)
+fn template_slots(
+    analysis: &RecordingAnalysis,
+    action_sequence: &str,
+    recording: &Recording,
+) -> Value {
+    // The bundled template's own viewport, for recordings without one in their Meta event
+    let (width, height) = analysis.viewport.unwrap_or((1024, 768));
+    let settings = &analysis.settings;
+    let (base_url, start_path) = split_origin(&analysis.initial_url);
+    serde_json::json!({
+        "start_url": analysis.initial_url,
+        "base_url": base_url,     // Scheme and host, for test runners' baseURL
+        "start_path": start_path, // The rest of the start URL, relative to base_url
+        // The slot sits on an indented line, so the first line's indent is already there
+        "action_sequence": action_sequence.trim_start(),
+        "test_name": recording.name,
+        "recording_file": recording.file_name,
+        "viewport": { "width": width, "height": height },
+        "actions": analysis
+            .actions_with_selectors
+            .iter()
+            .map(action_summary)
+            .collect::<Vec<_>>(),
+        "parameters": analysis
+            .parameters
+            .iter()
+            .map(|p| serde_json::json!({
+                "name": p.name,
+                "env_var": p.env_var,
+                "sensitive": p.sensitive,
+            }))
+            .collect::<Vec<_>>(),
+        "params_file": settings.param_source().data_file_name(),
+        "llm": {
+            "model": settings.llm_model(),
+            "api_key_env": settings.llm_api_key_env(),
+        },
+    })
+}
+
+// Split an absolute URL into its origin and the path, query and fragment after it
+pub(crate) fn split_origin(url: &str) -> (String, String) {
+    let Some((scheme, rest)) = url.split_once("://") else {
+        return (url.to_string(), "/".to_string());
+    };
+    let authority_end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+    let path = &rest[authority_end..];
+    (
+        format!("{}://{}", scheme, &rest[..authority_end]),
+        if path.starts_with('/') {
+            path.to_string()
+        } else {
+            format!("/{}", path)
+        },
+    )
+}
+
+// The real files mapped to recorded uploads, shipped alongside the placeholder fixture
+fn fixture_files(
+    fixture_files: &HashMap<String, PathBuf>,
+    logger: &Logger,
+) -> Result<Vec<TemplateFile>, CompileError> {
+    let mut files = Vec::new();
+    for fixture_path in fixture_files.values() {
+        let file_name = fixture_path.file_name().ok_or_else(|| {
+            CompileError::Config(format!("Fixture path has no file name: {:?}", fixture_path))
+        })?;
+        let contents = fs::read(fixture_path).map_err(|source| CompileError::Io {
+            path: fixture_path.clone(),
+            source,
+        })?;
+        detail!(logger, "  Added fixture: {:?}", fixture_path);
+        files.push(TemplateFile {
+            path: Path::new(FIXTURES_DIR).join(file_name),
+            contents,
+        });
+    }
+    Ok(files)
+}
+
+// The parameter data file, or the pack's .env.example with the parameters appended
+fn parameter_file(
+    parameters: &[Parameter],
+    settings: &Settings,
+    pack: &TemplatePack,
+    logger: &Logger,
+) -> Option<TemplateFile> {
+    if parameters.is_empty() {
+        return None;
+    }
+    let param_source = settings.param_source();
+    match param_source.data_file_name() {
+        Some(data_file_name) => {
+            let contents = params::data_file_contents(parameters, param_source)?;
+            info!(
+                logger,
+                "  Added {} parameter(s) to {}",
+                parameters.len(),
+                data_file_name
+            );
+            Some(TemplateFile {
+                path: PathBuf::from(data_file_name),
+                contents: contents.into_bytes(),
+            })
+        }
+        None => {
+            // Dotfiles are skipped by the pack's rules, so .env.example is added here
+            let mut env_example = pack.read_to_string(".env.example").unwrap_or_default();
+            if !env_example.is_empty() && !env_example.ends_with('\n') {
+                env_example.push('\n');
+            }
+            env_example.push_str(&params::env_example_entries(parameters));
+            info!(
+                logger,
+                "  Added {} parameter(s) to .env.example",
+                parameters.len()
+            );
+            Some(TemplateFile {
+                path: PathBuf::from(".env.example"),
+                contents: env_example.into_bytes(),
+            })
+        }
+    }
+}
+
+// --- Utility/Placeholder Functions ---
+
+// Find the first event of a specific type
+pub fn find_event_by_type(events: &[Event], event_type: i64) -> Option<&Event> {
+    events.iter().find(|e| e.event_type == event_type)
+}
+
+// Placeholder for recursive DOM snapshot parsing
+fn parse_dom_snapshot(
+    node_data: &Value,
+    dom_map: &mut HashMap<i64, NodeInfo>,
+    parent_id: Option<i64>,
+) {
+    // Needs to handle node structure, attributes, children, text content, etc.
+    if let Some(id) = node_data.get("id").and_then(|v| v.as_i64()) {
+        let mut attributes_map = HashMap::new();
+        if let Some(attrs) = node_data.get("attributes").and_then(|v| v.as_object()) {
+            for (key, value) in attrs {
+                if let Some(val_str) = value.as_str() {
+                    attributes_map.insert(key.clone(), val_str.to_string());
+                } else if value.is_number() || value.is_boolean() {
+                    // Convert numbers/bools to string representation
+                    attributes_map.insert(key.clone(), value.to_string());
+                }
+                // Ignore other value types for attributes for now
+            }
+        }
+
+        let info = NodeInfo {
+            rrweb_id: id,
+            tag_name: node_data
+                .get("tagName")
+                .and_then(|v| v.as_str())
+                .map(String::from),
+            attributes: attributes_map, // Store parsed attributes
+            parent_id,
+            child_ids: node_data
+                .get("childNodes")
+                .and_then(|v| v.as_array())
+                .map(|children| {
+                    children
+                        .iter()
+                        .filter_map(|child| child.get("id").and_then(|v| v.as_i64()))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            text_content: node_data
+                .get("textContent")
+                .and_then(|v| v.as_str())
+                .map(String::from),
+            is_shadow_host: node_data
+                .get("isShadowHost")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            is_shadow: node_data
+                .get("isShadow")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+        };
+        dom_map.insert(id, info);
+
+        if let Some(children) = node_data.get("childNodes").and_then(|v| v.as_array()) {
+            for child_node in children {
+                parse_dom_snapshot(child_node, dom_map, Some(id));
+            }
+        }
+    }
+}
+
+// Apply an incremental mutation (source 0) to the dom_map.
+// Handles removals, additions, attribute changes and text changes, in the order rrweb applies them.
+fn update_dom_map(dom_map: &mut HashMap<i64, NodeInfo>, mutation_data: &Value) {
+    if let Some(removes) = mutation_data.get("removes").and_then(|v| v.as_array()) {
+        for removal in removes {
+            if let Some(id) = removal.get("id").and_then(|v| v.as_i64()) {
+                if let Some(parent_id) = removal.get("parentId").and_then(|v| v.as_i64()) {
+                    if let Some(parent) = dom_map.get_mut(&parent_id) {
+                        parent.child_ids.retain(|child_id| *child_id != id);
+                    }
+                }
+                remove_subtree(dom_map, id);
+            }
+        }
+    }
+
+    if let Some(adds) = mutation_data.get("adds").and_then(|v| v.as_array()) {
+        for addition in adds {
+            let parent_id = addition.get("parentId").and_then(|v| v.as_i64());
+            let next_id = addition.get("nextId").and_then(|v| v.as_i64());
+            if let (Some(p_id), Some(n_data)) = (parent_id, addition.get("node")) {
+                parse_dom_snapshot(n_data, dom_map, Some(p_id));
+                if let Some(id) = n_data.get("id").and_then(|v| v.as_i64()) {
+                    if let Some(parent) = dom_map.get_mut(&p_id) {
+                        parent.child_ids.retain(|child_id| *child_id != id);
+                        // Insert before the recorded next sibling, or append if it's unknown
+                        let position = next_id
+                            .and_then(|next| parent.child_ids.iter().position(|c| *c == next))
+                            .unwrap_or(parent.child_ids.len());
+                        parent.child_ids.insert(position, id);
+                    }
+                }
+            }
+        }
+    }
+
+    if let Some(attribute_changes) = mutation_data.get("attributes").and_then(|v| v.as_array()) {
+        for change in attribute_changes {
+            let id = change.get("id").and_then(|v| v.as_i64());
+            let attrs = change.get("attributes").and_then(|v| v.as_object());
+            if let (Some(id), Some(attrs)) = (id, attrs) {
+                if let Some(node_info) = dom_map.get_mut(&id) {
+                    for (key, value) in attrs {
+                        match value {
+                            Value::String(val_str) => {
+                                node_info.attributes.insert(key.clone(), val_str.clone());
+                            }
+                            Value::Number(_) | Value::Bool(_) => {
+                                node_info.attributes.insert(key.clone(), value.to_string());
+                            }
+                            Value::Null => {
+                                node_info.attributes.remove(key);
+                            }
+                            _ => {} // Style objects and similar aren't tracked
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    if let Some(texts) = mutation_data.get("texts").and_then(|v| v.as_array()) {
+        for text_change in texts {
+            if let Some(id) = text_change.get("id").and_then(|v| v.as_i64()) {
+                if let Some(node_info) = dom_map.get_mut(&id) {
+                    node_info.text_content = text_change
+                        .get("value")
+                        .and_then(|v| v.as_str())
+                        .map(String::from);
+                }
+            }
+        }
+    }
+}
+
+fn remove_subtree(dom_map: &mut HashMap<i64, NodeInfo>, id: i64) {
+    if let Some(node_info) = dom_map.remove(&id) {
+        for child_id in node_info.child_ids {
+            remove_subtree(dom_map, child_id);
+        }
+    }
+}
+
+// Find the closest ancestor-or-self that is an editing host (`contenteditable` other than "false")
+fn contenteditable_host(rrweb_id: i64, dom_map: &HashMap<i64, NodeInfo>) -> Option<i64> {
+    let mut current_id = Some(rrweb_id);
+    while let Some(id) = current_id {
+        let node_info = dom_map.get(&id)?;
+        if let Some(editable) = node_info.attributes.get("contenteditable") {
+            if editable != "false" {
+                return Some(id);
+            }
+        }
+        current_id = node_info.parent_id;
+    }
+    None
+}
+
+// Collect the editing hosts touched by a mutation, resolved against the DOM *before* it is applied
+fn contenteditable_hosts_in_mutation(
+    mutation_data: &Value,
+    dom_map: &HashMap<i64, NodeInfo>,
+) -> Vec<i64> {
+    let mut touched_ids = Vec::new();
+    if let Some(texts) = mutation_data.get("texts").and_then(|v| v.as_array()) {
+        touched_ids.extend(
+            texts
+                .iter()
+                .filter_map(|t| t.get("id").and_then(|v| v.as_i64())),
+        );
+    }
+    for key in ["adds", "removes"] {
+        if let Some(entries) = mutation_data.get(key).and_then(|v| v.as_array()) {
+            touched_ids.extend(
+                entries
+                    .iter()
+                    .filter_map(|entry| entry.get("parentId").and_then(|v| v.as_i64())),
+            );
+        }
+    }
+
+    let mut hosts = Vec::new();
+    for id in touched_ids {
+        if let Some(host_id) = contenteditable_host(id, dom_map) {
+            if !hosts.contains(&host_id) {
+                hosts.push(host_id);
+            }
+        }
+    }
+    hosts
+}
+
+// Reassemble the visible text of a subtree, putting block-level elements on their own lines
+pub(crate) fn collect_text(rrweb_id: i64, dom_map: &HashMap<i64, NodeInfo>) -> String {
+    let Some(node_info) = dom_map.get(&rrweb_id) else {
+        return String::new();
+    };
+    match node_info.tag_name.as_deref() {
+        None => node_info.text_content.clone().unwrap_or_default(),
+        Some("br") => "\n".to_string(),
+        Some(_) => {
+            let mut text = String::new();
+            for child_id in &node_info.child_ids {
+                let is_block = dom_map
+                    .get(child_id)
+                    .and_then(|child| child.tag_name.as_deref())
+                    .is_some_and(|tag| {
+                        matches!(
+                            tag,
+                            "p" | "div" | "li" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "pre"
+                        )
+                    });
+                if is_block && !text.is_empty() && !text.ends_with('\n') {
+                    text.push('\n');
+                }
+                text.push_str(&collect_text(*child_id, dom_map));
+            }
+            text
+        }
+    }
+}
+
+// An in-progress run of typing inside one contenteditable host
+struct EditingSession {
+    host_id: i64,
+    initial_text: String,
+    last_timestamp: i64,
+    last_event_index: usize,
+}
+
+// Turn a finished editing session into a single TypeText action for the text that was added
+fn flush_editing_session(
+    editing_session: &mut Option<EditingSession>,
+    dom_map: &HashMap<i64, NodeInfo>,
+    simplified_actions: &mut Vec<SimplifiedAction>,
+) {
+    if let Some(session) = editing_session.take() {
+        let final_text = collect_text(session.host_id, dom_map);
+        // Only the text after the unchanged prefix was typed during this session
+        let common_prefix_len = session
+            .initial_text
+            .char_indices()
+            .zip(final_text.chars())
+            .take_while(|((_, a), b)| a == b)
+            .last()
+            .map(|((index, c), _)| index + c.len_utf8())
+            .unwrap_or(0);
+        let typed_text = &final_text[common_prefix_len..];
+        if !typed_text.trim().is_empty() {
+            let action = SimplifiedAction {
+                action_type: ActionType::TypeText,
+                rrweb_id: session.host_id,
+                value: Some(typed_text.to_string()),
+                checked: None,
+                timestamp: session.last_timestamp,
+                event_index: session.last_event_index,
+                url: None,
+                navigates: false,
+                page: 0,
+            };
+            add_action(simplified_actions, action);
+        }
+    }
+}
+
+// Placeholder: Flush buffered input actions
+fn flush_input_buffer(
+    current_input_buffer: &mut InputBuffer,
+    dom_map: &HashMap<i64, NodeInfo>,
+    simplified_actions: &mut Vec<SimplifiedAction>,
+) {
+    let mut buffered: Vec<_> = current_input_buffer.drain().collect();
+    // Keep the recorded order; the buffer itself is unordered
+    buffered.sort_by_key(|(_, (_, _, _, last_event_index))| *last_event_index);
+    for (rrweb_id, (text, checked, last_timestamp, last_event_index)) in buffered {
+        let action = if is_file_input(rrweb_id, dom_map) {
+            // Browsers report file inputs as a fake path like `C:\fakepath\report.pdf`
+            let file_name = text.rsplit(['\\', '/']).next().unwrap_or_default();
+            SimplifiedAction {
+                action_type: ActionType::Upload,
+                rrweb_id,
+                value: Some(file_name.to_string()),
+                checked: None,
+                timestamp: last_timestamp,
+                event_index: last_event_index,
+                url: None,
+                navigates: false,
+                page: 0,
+            }
+        } else {
+            SimplifiedAction {
+                action_type: ActionType::Input,
+                rrweb_id,
+                value: Some(text),
+                checked,
+                timestamp: last_timestamp,
+                event_index: last_event_index,
+                url: None,
+                navigates: false,
+                page: 0,
+            }
+        };
+        add_action(simplified_actions, action);
+    }
+}
+
+fn is_file_input(rrweb_id: i64, dom_map: &HashMap<i64, NodeInfo>) -> bool {
+    dom_map.get(&rrweb_id).is_some_and(|node_info| {
+        node_info
+            .tag_name
+            .as_deref()
+            .is_some_and(|tag| tag.eq_ignore_ascii_case("input"))
+            && node_info
+                .attributes
+                .get("type")
+                .is_some_and(|input_type| input_type.eq_ignore_ascii_case("file"))
+    })
+}
+
+// Placeholder: Add action (potentially with simplification logic later)
+fn add_action(action_list: &mut Vec<SimplifiedAction>, new_action: SimplifiedAction) {
+    action_list.push(new_action);
+}
+
+// --- Stage 1 Helper Function ---
+pub fn preprocess(
+    rrweb_events: &[Event],
+    diagnostics: &mut Diagnostics,
+) -> Result<PreprocessingResultData, CompileError> {
+    let mut dom_maps: Vec<DomMap> = Vec::new();
+    let mut dom_map: DomMap = HashMap::new();
+    let mut simplified_actions: SimplifiedActionList = Vec::new();
+    let mut current_input_buffer: InputBuffer = HashMap::new();
+    let mut editing_session: Option<EditingSession> = None;
+
+    // Type 2 is Full Snapshot
+    let snapshots: Vec<usize> = rrweb_events
+        .iter()
+        .enumerate()
+        .filter(|(_, event)| event.event_type == 2)
+        .map(|(index, _)| index)
+        .collect();
+    if snapshots.is_empty() {
+        return Err(CompileError::Recording(
+            Diagnostic::error(
+                Code::MissingSnapshot,
+                "No initial full snapshot (type 2) event found in recording.",
+            )
+            .with_hint("Make sure the recording starts with rrweb's initial snapshot"),
+        ));
+    }
+
+    // Process incremental events
+    for (event_index, event) in rrweb_events.iter().enumerate() {
+        if event.event_type == 2 {
+            // rrweb takes a full snapshot on every page load and numbers its nodes from 1 again,
+            // so the previous page's DOM is kept for its own actions and a new map is started
+            flush_input_buffer(&mut current_input_buffer, &dom_map, &mut simplified_actions);
+            flush_editing_session(&mut editing_session, &dom_map, &mut simplified_actions);
+            if event_index != snapshots[0] {
+                dom_maps.push(std::mem::take(&mut dom_map));
+            }
+            if let Some(node_data) = event.data.get("node") {
+                parse_dom_snapshot(node_data, &mut dom_map, None);
+            } else {
+                diagnostics.push(
+                    Diagnostic::warning(
+                        Code::EmptySnapshot,
+                        "Full snapshot event found but missing 'node' data.",
+                    )
+                    .at_event(event_index, event.timestamp),
+                );
+            }
+        } else if event.event_type == 3 {
+            // Incremental Snapshot
+            if let Some(source_type) = event.data.get("source").and_then(|v| v.as_i64()) {
+                match source_type {
+                    0 => {
+                        // Mutation
+                        // Rich-text editors don't fire Input events; their typing only shows up
+                        // as text mutations under a contenteditable host.
+                        for host_id in contenteditable_hosts_in_mutation(&event.data, &dom_map) {
+                            if editing_session
+                                .as_ref()
+                                .is_some_and(|session| session.host_id != host_id)
+                            {
+                                flush_editing_session(
+                                    &mut editing_session,
+                                    &dom_map,
+                                    &mut simplified_actions,
+                                );
+                            }
+                            if editing_session.is_none() {
+                                flush_input_buffer(
+                                    &mut current_input_buffer,
+                                    &dom_map,
+                                    &mut simplified_actions,
+                                );
+                                editing_session = Some(EditingSession {
+                                    host_id,
+                                    initial_text: collect_text(host_id, &dom_map),
+                                    last_timestamp: event.timestamp,
+                                    last_event_index: event_index,
+                                });
+                            }
+                        }
+                        update_dom_map(&mut dom_map, &event.data);
+                        if let Some(session) = editing_session.as_mut() {
+                            session.last_timestamp = event.timestamp;
+                            session.last_event_index = event_index;
+                        }
+                    }
+                    2 => {
+                        // Mouse Interaction
+                        if let Some(interaction_type) =
+                            event.data.get("type").and_then(|v| v.as_i64())
+                        {
+                            if interaction_type == 2 {
+                                // Click
+                                if let Some(target_id) =
+                                    event.data.get("id").and_then(|v| v.as_i64())
+                                {
+                                    flush_input_buffer(
+                                        &mut current_input_buffer,
+                                        &dom_map,
+                                        &mut simplified_actions,
+                                    ); // Flush inputs before click
+
+                                    // Clicking inside the editor just moves the caret; clicking
+                                    // elsewhere ends the editing session
+                                    if editing_session.as_ref().is_some_and(|session| {
+                                        contenteditable_host(target_id, &dom_map)
+                                            != Some(session.host_id)
+                                    }) {
+                                        flush_editing_session(
+                                            &mut editing_session,
+                                            &dom_map,
+                                            &mut simplified_actions,
+                                        );
+                                    }
+                                    let action = SimplifiedAction {
+                                        action_type: ActionType::Click,
+                                        rrweb_id: target_id,
+                                        value: None,
+                                        checked: None,
+                                        timestamp: event.timestamp,
+                                        event_index,
+                                        url: None,
+                                        navigates: false,
+                                        page: 0,
+                                    };
+                                    add_action(&mut simplified_actions, action);
+                                }
+                            }
+                            // TODO: Handle other mouse interactions if needed (e.g., MouseUp)
+                        }
+                    }
+                    5 => {
+                        // Input
+                        if let (Some(target_id), Some(text)) = (
+                            event.data.get("id").and_then(|v| v.as_i64()),
+                            event.data.get("text").and_then(|v| v.as_str()),
+                        ) {
+                            flush_editing_session(
+                                &mut editing_session,
+                                &dom_map,
+                                &mut simplified_actions,
+                            );
+                            // Buffer input: store last text value and timestamp for this element ID
+                            let checked = event.data.get("isChecked").and_then(|v| v.as_bool());
+                            current_input_buffer.insert(
+                                target_id,
+                                (text.to_string(), checked, event.timestamp, event_index),
+                            );
+                        }
+                    }
+                    // TODO: Handle other source types (Scroll, etc.) if needed
+                    _ => {} // Ignore other incremental sources for now
+                }
+            }
+        }
+        // TODO: Handle Meta events (type 4) for URL changes mid-recording?
+    }
+
+    // Flush any remaining inputs and editing sessions at the end
+    flush_input_buffer(&mut current_input_buffer, &dom_map, &mut simplified_actions);
+    flush_editing_session(&mut editing_session, &dom_map, &mut simplified_actions);
+    dom_maps.push(dom_map);
+
+    // The snapshot each action's node belongs to: the latest one before the action
+    for action in &mut simplified_actions {
+        action.page = snapshots
+            .iter()
+            .filter(|&&index| index <= action.event_index)
+            .count()
+            .saturating_sub(1);
+    }
+
+    // The page each action happened on: the href of the latest Meta event before it
+    let mut page_urls = rrweb_events
+        .iter()
+        .enumerate()
+        .filter(|(_, event)| event.event_type == 4)
+        .filter_map(|(index, event)| Some((index, event.data.get("href")?.as_str()?)))
+        .peekable();
+    let mut url = None;
+    for action in &mut simplified_actions {
+        while let Some((_, href)) = page_urls.next_if(|(index, _)| *index <= action.event_index) {
+            url = Some(href.to_string());
+        }
+        action.url = url.clone();
+    }
+    // rrweb records a Meta event for every page load, so one between two actions means the
+    // first of them navigated
+    let page_loads: Vec<usize> = rrweb_events
+        .iter()
+        .enumerate()
+        .filter(|(_, event)| event.event_type == 4)
+        .map(|(index, _)| index)
+        .collect();
+    let mut next_event_index = rrweb_events.len();
+    for action in simplified_actions.iter_mut().rev() {
+        action.navigates = page_loads
+            .iter()
+            .any(|&index| index > action.event_index && index < next_event_index);
+        next_event_index = action.event_index;
+    }
+
+    Ok((dom_maps, simplified_actions))
+}
+
+// --- Stage 2 Helper Function ---
+pub fn generate_selectors(
+    simplified_actions: &[SimplifiedAction],
+    dom_maps: &[DomMap],
+    rules: &SelectorRules,
+    diagnostics: &mut Diagnostics,
+) -> Result<Vec<ActionWithSelector>, CompileError> {
+    let mut actions_with_selectors = Vec::new();
+    let no_nodes = DomMap::new();
+
+    for action in simplified_actions {
+        let dom_map = dom_maps.get(action.page).unwrap_or(&no_nodes);
+        let mut shadow_hosts = Vec::new();
+        let mut frames = Vec::new();
+        let mut candidates = Vec::new();
+        let mut strategy = None;
+
+        let generated_selector = if let Some(node_info) = dom_map.get(&action.rrweb_id) {
+            // Scope the selector to every shadow root the node sits in. Each host gets its own
+            // selector so the emitted locator can be chained host by host.
+            for host_id in shadow_host_chain(action.rrweb_id, dom_map) {
+                if let Some(host_info) = dom_map.get(&host_id) {
+                    shadow_hosts.push(
+                        selector_for_node(host_info, rules)
+                            // Web component hosts are custom elements, so the tag is a usable fallback
+                            .or_else(|| host_info.tag_name.clone())
+                            .unwrap_or_else(|| "*".to_string()),
+                    );
+                }
+            }
+
+            // Same-origin iframes are recorded inline, their document a child of the iframe
+            for frame_id in frame_chain(action.rrweb_id, dom_map) {
+                if let Some(frame_info) = dom_map.get(&frame_id) {
+                    frames.push(
+                        selector_for_node(frame_info, rules)
+                            .or_else(|| {
+                                let src = frame_info.attributes.get("src")?;
+                                Some(format!("iframe[src = \"{}\"]", src.replace('"', "\\\"")))
+                            })
+                            .unwrap_or_else(|| "iframe".to_string()),
+                    );
+                }
+            }
+
+            candidates = selector_candidates(node_info, dom_map, rules, &shadow_hosts);
+            strategy = candidates
+                .first()
+                .map(|candidate| candidate.strategy.clone());
+
+            // Fallback Strategy: If no preferred selector found, mark as failed.
+            candidates.first().map(|candidate| candidate.selector.clone()).unwrap_or_else(|| {
+                let tag_name = node_info.tag_name.as_deref().unwrap_or("unknown");
+                diagnostics.push(
+                    Diagnostic::warning(
+                        Code::SelectorFailed,
+                        format!(
+                            "Could not generate a stable selector for <{}>; the action will be skipped.",
+                            tag_name
+                        ),
+                    )
+                    .at_event(action.event_index, action.timestamp)
+                    .on_node(action.rrweb_id)
+                    .with_hint(
+                        "Give the element an id or test attribute, or add its attribute to [selectors] test_attributes",
+                    ),
+                );
+                // Use a specific prefix to identify failed selectors later
+                format!("SELECTOR_GENERATION_FAILED::{}", tag_name)
+            })
+        } else {
+            // If node_info is None (shouldn't happen often if preprocessing is robust)
+            diagnostics.push(
+                Diagnostic::warning(
+                    Code::NodeNotFound,
+                    "Targeted node is not in the DOM; the action will be skipped.",
+                )
+                .at_event(action.event_index, action.timestamp)
+                .on_node(action.rrweb_id),
+            );
+            format!(
+                "SELECTOR_GENERATION_FAILED::node_not_found_id_{}",
+                action.rrweb_id
+            )
+        };
+
+        actions_with_selectors.push(ActionWithSelector {
+            action_type: action.action_type.clone(),
+            rrweb_id: action.rrweb_id,
+            value: action.value.clone(),
+            checked: action.checked,
+            control: control_kind(action.rrweb_id, dom_map),
+            select_by_label: false, // rrweb records the option's value
+            timestamp: action.timestamp,
+            event_index: action.event_index,
+            url: action.url.clone(),
+            navigates: action.navigates,
+            page: action.page,
+            selector: generated_selector, // Use the generated or placeholder selector
+            strategy,
+            candidates,
+            shadow_hosts,
+            frames,
+            redaction: None,
+            parameter: None,
+            masked: false, // Set by the redaction pass
+            description: describe_node(action.rrweb_id, dom_map),
+        });
+    }
+
+    Ok(actions_with_selectors)
+}
+
+// Collect the shadow hosts enclosing a node, outermost first.
+// rrweb serializes shadow root children as children of the host, flagged with `isShadow`.
+fn shadow_host_chain(rrweb_id: i64, dom_map: &HashMap<i64, NodeInfo>) -> Vec<i64> {
+    let mut hosts = Vec::new();
+    let mut current_id = rrweb_id;
+    while let Some(node_info) = dom_map.get(&current_id) {
+        let Some(parent_id) = node_info.parent_id else {
+            break;
+        };
+        if node_info.is_shadow
+            && dom_map
+                .get(&parent_id)
+                .is_some_and(|parent| parent.is_shadow_host)
+        {
+            hosts.push(parent_id);
+        }
+        current_id = parent_id;
+    }
+    hosts.reverse();
+    hosts
+}
+
+// Collect the iframes enclosing a node, outermost first
+fn frame_chain(rrweb_id: i64, dom_map: &HashMap<i64, NodeInfo>) -> Vec<i64> {
+    let mut frames = Vec::new();
+    let mut parent_id = dom_map.get(&rrweb_id).and_then(|node| node.parent_id);
+    while let Some(id) = parent_id {
+        let Some(node_info) = dom_map.get(&id) else {
+            break;
+        };
+        if node_info.tag_name.as_deref().is_some_and(|tag| {
+            tag.eq_ignore_ascii_case("iframe") || tag.eq_ignore_ascii_case("frame")
+        }) {
+            frames.push(id);
+        }
+        parent_id = node_info.parent_id;
+    }
+    frames.reverse();
+    frames
+}
+
+// Build a CSS selector for a single node from its own attributes, trying the strategies in order.
+// Returns None when no stable attribute is available.
+fn selector_for_node(node_info: &NodeInfo, rules: &SelectorRules) -> Option<String> {
+    rules
+        .strategies
+        .iter()
+        .find_map(|strategy| strategy_selector(node_info, strategy, rules))
+}
+
+// Every selector the configured strategies give a node, in priority order. Each is scored by
+// the strategy's stability, divided among the nodes in the final DOM that get the same selector.
+fn selector_candidates(
+    node_info: &NodeInfo,
+    dom_map: &HashMap<i64, NodeInfo>,
+    rules: &SelectorRules,
+    shadow_hosts: &[String],
+) -> Vec<SelectorCandidate> {
+    rules
+        .strategies
+        .iter()
+        .filter_map(|strategy| {
+            let selector = strategy_selector(node_info, strategy, rules)?;
+            let matches = dom_map
+                .values()
+                .filter(|other| {
+                    strategy_selector(other, strategy, rules).as_ref() == Some(&selector)
+                })
+                .count();
+            Some(SelectorCandidate {
+                score: strategy.stability() / matches.max(1) as f64,
+                strategy: strategy.clone(),
+                selector,
+                shadow_hosts: shadow_hosts.to_vec(),
+                matches,
+            })
+        })
+        .collect()
+}
+
+// The selector a single strategy gives a node, or None when its attribute is missing or denied
+fn strategy_selector(
+    node_info: &NodeInfo,
+    strategy: &SelectorStrategy,
+    rules: &SelectorRules,
+) -> Option<String> {
+    // Helper function to create attribute selectors, escaping quotes
+    let create_attr_selector = |attr: &str, value: &str| -> String {
+        format!("*[{} = \"{}\"]", attr, value.replace('"', "\\\""))
+    };
+    let non_empty_attr = |attr: &str| {
+        node_info
+            .attributes
+            .get(attr)
+            .filter(|value| !value.is_empty())
+    };
+
+    let denied = |patterns: &[String], value: &str| {
+        patterns
+            .iter()
+            .any(|pattern| templates::wildcard_match(pattern, value))
+    };
+
+    match strategy {
+        // Use ID if available, valid and not generated
+        SelectorStrategy::Id => non_empty_attr("id")
+            .filter(|id_val| !denied(&rules.deny_ids, id_val))
+            .map(|id_val| {
+                if !id_val.contains(char::is_whitespace) {
+                    format!("#{}", id_val)
+                } else {
+                    // Use attribute selector for invalid IDs
+                    create_attr_selector("id", id_val)
+                }
+            }),
+        SelectorStrategy::TestId => {
+            non_empty_attr("data-testid").map(|val| create_attr_selector("data-testid", val))
+        }
+        SelectorStrategy::DataCy => {
+            non_empty_attr("data-cy").map(|val| create_attr_selector("data-cy", val))
+        }
+        // Often useful for form elements
+        SelectorStrategy::Name => {
+            non_empty_attr("name").map(|val| create_attr_selector("name", val))
+        }
+        SelectorStrategy::Attribute(attribute) => {
+            non_empty_attr(attribute).map(|val| create_attr_selector(attribute, val))
+        }
+        SelectorStrategy::Recorder => None,
+        // Use the first allowed class name, as tagname.classname
+        SelectorStrategy::Class => node_info
+            .attributes
+            .get("class")
+            .and_then(|class_val| {
+                class_val
+                    .split_whitespace()
+                    .find(|class_name| !denied(&rules.deny_classes, class_name))
+            })
+            .map(|first_class| {
+                // Escape the class name if it contains special CSS characters (simplistic check)
+                // Basic escaping - might need refinement for full CSS spec
+                let escaped_class = first_class.replace(':', "\\:").replace('.', "\\.");
+                match &node_info.tag_name {
+                    Some(tag) => format!("{}.{}", tag, escaped_class),
+                    None => format!("*.{}", escaped_class),
+                }
+            }),
+    }
+}
+
+fn control_kind(rrweb_id: i64, dom_map: &HashMap<i64, NodeInfo>) -> Control {
+    let Some(node_info) = dom_map.get(&rrweb_id) else {
+        return Control::Field;
+    };
+    let tag = node_info.tag_name.as_deref().unwrap_or_default();
+    let input_type = node_info.attributes.get("type").map(|t| t.to_lowercase());
+    match (tag, input_type.as_deref()) {
+        ("select", _) => Control::Select,
+        ("input", Some("checkbox")) => Control::Checkbox,
+        ("input", Some("radio")) => Control::Radio,
+        _ => Control::Field,
+    }
+}
+
+// Short human-readable description of a target element, e.g. `"Checkout" button`.
+// Used for Stagehand act() instructions and comments.
+fn describe_node(rrweb_id: i64, dom_map: &HashMap<i64, NodeInfo>) -> String {
+    let Some(node_info) = dom_map.get(&rrweb_id) else {
+        return "element".to_string();
+    };
+    let tag = node_info.tag_name.as_deref().unwrap_or("element");
+    let input_type = node_info.attributes.get("type").map(|t| t.to_lowercase());
+    let noun = match (tag, input_type.as_deref()) {
+        (_, _)
+            if node_info
+                .attributes
+                .get("role")
+                .is_some_and(|r| r == "button") =>
+        {
+            "button"
+        }
+        ("button", _) | ("input", Some("button" | "submit" | "reset")) => "button",
+        ("a", _) => "link",
+        ("input", Some("checkbox")) => "checkbox",
+        ("input", Some("radio")) => "radio button",
+        ("input", Some("file")) => "file input",
+        ("input", _) | ("textarea", _) => "field",
+        ("select", _) => "dropdown",
+        _ if node_info.attributes.contains_key("contenteditable") => "editor",
+        _ => tag,
+    };
+
+    // Prefer the accessible label, then visible text, then the value of a submit-like input
+    let name = field_label(rrweb_id, dom_map)
+        .or_else(|| {
+            let text = collect_text(rrweb_id, dom_map)
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ");
+            // Form fields and editors contain what the user typed rather than a name
+            let is_form_field = matches!(noun, "field" | "dropdown" | "file input" | "editor");
+            (!text.is_empty() && !is_form_field).then_some(text)
+        })
+        .or_else(|| node_info.attributes.get("title").cloned())
+        .or_else(|| node_info.attributes.get("name").cloned())
+        .map(|name| {
+            if name.chars().count() > 40 {
+                format!("{}…", name.chars().take(40).collect::<String>())
+            } else {
+                name
+            }
+        });
+
+    match name {
+        Some(name) => format!("\"{}\" {}", name, noun),
+        None => noun.to_string(),
+    }
+}
+
+// Stage 3 for the default Stagehand target; `emitter::emit_with` generates code for any target
+pub fn emit(
+    actions_with_selectors: &[ActionWithSelector],
+    options: &EmitOptions,
+    diagnostics: &mut Diagnostics,
+) -> Result<String, CompileError> {
+    emitter::emit_with(
+        &stagehand::StagehandEmitter,
+        actions_with_selectors,
+        options,
+        diagnostics,
+    )
+}
+
+// How an action ends up in the generated script
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Emission {
+    Locator,        // Playwright locator
+    LocatorWithAct, // Locator with a `page.act()` fallback, in hybrid mode
+    Act,            // Stagehand `page.act()` instruction only
+    Skipped,        // No selector, and no `page.act()` instruction to fall back on
+}
+
+pub fn emission(action: &ActionWithSelector, mode: EmitMode) -> Emission {
+    let selector_failed = action.selector.starts_with("SELECTOR_GENERATION_FAILED::");
+    let has_act = mode != EmitMode::Locator && act_instruction(action).is_some();
+    match (selector_failed, has_act) {
+        (true, false) => Emission::Skipped,
+        (true, true) => Emission::Act,
+        (false, true) if mode == EmitMode::Act => Emission::Act,
+        (false, true) => Emission::LocatorWithAct,
+        (false, false) => Emission::Locator,
+    }
+}
+
+// Human-readable step for READMEs and reports; values are left out since they may be sensitive
+pub fn action_summary(action: &ActionWithSelector) -> String {
+    match action.action_type {
+        ActionType::Click => format!("Click the {}", action.description),
+        ActionType::Input => format!("Fill in the {}", action.description),
+        ActionType::TypeText => format!("Type into the {}", action.description),
+        ActionType::Upload => format!("Upload a file to the {}", action.description),
+        ActionType::KeyPress => format!("Press a key in the {}", action.description),
+    }
+}
+
+// Argument for Stagehand's `page.act()`, or None when the action can't be expressed as an
+// instruction (uploads need a real file). Typed values go through `variables` so secrets and
+// parameters are substituted locally instead of being sent to the LLM.
+pub(crate) fn act_instruction(action: &ActionWithSelector) -> Option<String> {
+    let ts_string = |value: &str| serde_json::to_string(value).unwrap_or_default();
+    match action.action_type {
+        ActionType::Click => Some(ts_string(&format!("click the {}", action.description))),
+        ActionType::Input | ActionType::TypeText => {
+            let value = action.value.as_ref()?;
+            let value_expression = match (&action.parameter, &action.redaction) {
+                (Some(parameter), _) => Some(format!("params.{}", parameter)),
+                (None, Some(finding)) => Some(ts_string(&redact::placeholder(finding.kind))),
+                (None, None) => None,
+            };
+            match value_expression {
+                Some(value_expression) => Some(format!(
+                    "{{ action: {}, variables: {{ value: {} }} }}",
+                    ts_string(&format!("type %value% into the {}", action.description)),
+                    value_expression
+                )),
+                None => Some(ts_string(&format!(
+                    "type \"{}\" into the {}",
+                    value, action.description
+                ))),
+            }
+        }
+        ActionType::Upload => None,
+        ActionType::KeyPress => Some(ts_string(&format!(
+            "press {} in the {}",
+            pressed_key(action),
+            action.description
+        ))),
+    }
+}
+
+// Classify every typed value and mark sensitive ones so emission uses placeholders instead
+fn redact_actions(
+    actions_with_selectors: &mut [ActionWithSelector],
+    dom_maps: &[DomMap],
+    rules: &RedactionRules,
+) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let no_nodes = DomMap::new();
+    for action in actions_with_selectors.iter_mut() {
+        let dom_map = dom_maps.get(action.page).unwrap_or(&no_nodes);
+        if !matches!(action.action_type, ActionType::Input | ActionType::TypeText) {
+            continue;
+        }
+        let Some(value) = &action.value else {
+            continue;
+        };
+        action.masked =
+            matches!(action.action_type, ActionType::Input) && rules.is_masked_value(value);
+        let hints = dom_map
+            .get(&action.rrweb_id)
+            .map(|node_info| {
+                FieldHints::from_attributes(
+                    &node_info.attributes,
+                    field_label(action.rrweb_id, dom_map),
+                )
+            })
+            .unwrap_or_default();
+        if let Some((kind, source)) = redact::classify(&hints, value, rules) {
+            let finding = Finding {
+                kind,
+                source,
+                rrweb_id: action.rrweb_id,
+                timestamp: action.timestamp,
+            };
+            action.redaction = Some(finding.clone());
+            findings.push(finding);
+        }
+    }
+    findings
+}
+
+// Best-effort accessible label for a form field: aria-label, <label for>, wrapping <label>,
+// then placeholder
+fn field_label(rrweb_id: i64, dom_map: &HashMap<i64, NodeInfo>) -> Option<String> {
+    let node_info = dom_map.get(&rrweb_id)?;
+    let non_empty = |text: String| {
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        (!text.is_empty()).then_some(text)
+    };
+
+    if let Some(aria_label) = node_info.attributes.get("aria-label") {
+        if let Some(label) = non_empty(aria_label.clone()) {
+            return Some(label);
+        }
+    }
+
+    if let Some(id_val) = node_info.attributes.get("id") {
+        let label_for = dom_map.iter().find(|(_, candidate)| {
+            candidate.tag_name.as_deref() == Some("label")
+                && candidate.attributes.get("for") == Some(id_val)
+        });
+        if let Some((label_id, _)) = label_for {
+            if let Some(label) = non_empty(collect_text(*label_id, dom_map)) {
+                return Some(label);
+            }
+        }
+    }
+
+    let mut ancestor_id = node_info.parent_id;
+    while let Some(id) = ancestor_id {
+        let Some(ancestor) = dom_map.get(&id) else {
+            break;
+        };
+        if ancestor.tag_name.as_deref() == Some("label") {
+            if let Some(label) = non_empty(collect_text(id, dom_map)) {
+                return Some(label);
+            }
+        }
+        ancestor_id = ancestor.parent_id;
+    }
+
+    node_info
+        .attributes
+        .get("placeholder")
+        .and_then(|placeholder| non_empty(placeholder.clone()))
+}
+
+// Value as shown in comments: sensitive values are replaced by their kind
+pub fn display_value(action: &ActionWithSelector) -> Option<String> {
+    match (&action.redaction, &action.parameter, &action.value) {
+        (Some(finding), Some(parameter), _) => {
+            Some(format!("params.{} [redacted:{}]", parameter, finding.kind))
+        }
+        (Some(finding), None, _) => Some(format!("[redacted:{}]", finding.kind)),
+        (None, Some(parameter), _) if action.masked => {
+            Some(format!("params.{} [masked]", parameter))
+        }
+        (None, _, value) => value.clone(),
+    }
+}
+
+// Value replayed by formats without parameters: the recorded one, or a placeholder when it is
+// sensitive or was masked by the recorder
+pub(crate) fn replayed_value(action: &ActionWithSelector) -> Option<String> {
+    match (&action.redaction, &action.value) {
+        (Some(finding), _) => Some(redact::placeholder(finding.kind)),
+        (None, Some(_)) if action.masked => Some(redact::MASKED_PLACEHOLDER.to_string()),
+        (None, value) => value.clone(),
+    }
+}
+
+// Key a key press action presses, as a DOM `KeyboardEvent.key` name such as `Enter`
+pub(crate) fn pressed_key(action: &ActionWithSelector) -> &str {
+    action.value.as_deref().unwrap_or("Enter")
+}
+
+// Human-readable selector, with shadow hosts shown as Playwright-style `>>` chain segments
+pub fn display_selector(action: &ActionWithSelector) -> String {
+    action
+        .shadow_hosts
+        .iter()
+        .chain(std::iter::once(&action.selector))
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(" >> ")
+}
+
+// --- Other Utility Placeholders ---
+// fn get_node_info(map: &HashMap<i64, NodeInfo>, id: i64) -> Option<&NodeInfo> { map.get(&id) }
+// fn format_node_context_for_llm(node: &NodeInfo, parent: Option<&NodeInfo>) -> String { /* ... */ String::new() }
+// async fn call_llm_selector_api(prompt: &str) -> Result<String, Box<dyn Error>> { Ok("llm_generated_selector".to_string()) }
+// async fn call_llm_code_generation_api(prompt: &str) -> Result<String, Box<dyn Error>> { Ok("llm_generated_code".to_string()) }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{click, element, input, meta, snapshot, two_page_recording};
+    use serde_json::json;
+
+    #[test]
+    fn keeps_one_dom_map_per_full_snapshot() {
+        let recording = two_page_recording();
+        let (dom_maps, actions) =
+            preprocess(&recording.events, &mut Diagnostics::default()).unwrap();
+        assert_eq!(dom_maps.len(), 2);
+        assert_eq!(
+            dom_maps[0][&30].tag_name.as_deref(),
+            Some("a"),
+            "the first page keeps its own node 30"
+        );
+        assert_eq!(dom_maps[1][&30].tag_name.as_deref(), Some("button"));
+        assert!(!dom_maps[1].contains_key(&31));
+
+        let pages: Vec<(i64, usize)> = actions.iter().map(|a| (a.rrweb_id, a.page)).collect();
+        assert_eq!(pages, [(31, 0), (30, 0), (30, 1)]);
+    }
+
+    #[test]
+    fn resolves_actions_after_a_page_load_against_the_new_page() {
+        let recording = two_page_recording();
+        let analysis = analyze(&recording, &CompileOptions::default()).unwrap();
+        let selectors: Vec<&str> = analysis
+            .actions_with_selectors
+            .iter()
+            .map(|action| action.selector.as_str())
+            .collect();
+        assert_eq!(selectors, ["#email", "#help", "#pay"]);
+
+        let actions = &analysis.actions_with_selectors;
+        assert_eq!(actions[2].description, "button");
+        assert!(actions[1].navigates);
+        assert_eq!(
+            actions[2].url.as_deref(),
+            Some("https://shop.test/checkout")
+        );
+        assert!(analysis
+            .node(&actions[2])
+            .is_some_and(|node| node.attributes["id"] == "pay"));
+    }
+
+    #[test]
+    fn scopes_selectors_to_each_enclosing_shadow_host() {
+        // <shop-app id="app"> hosts <cart-panel>, which hosts the checkout button
+        let app = json!({"type": 2, "id": 10, "tagName": "shop-app", "attributes": {"id": "app"},
+        "isShadowHost": true, "childNodes": [
+            {"type": 2, "id": 11, "tagName": "cart-panel", "attributes": {},
+             "isShadow": true, "isShadowHost": true, "childNodes": [
+                {"type": 2, "id": 12, "tagName": "div", "attributes": {},
+                 "isShadow": true, "childNodes": [
+                    element(13, "button", json!({"id": "checkout"})),
+                ]},
+            ]},
+        ]});
+        let events = json!([
+            meta(1000, "https://shop.test/"),
+            snapshot(
+                1001,
+                json!([app, element(20, "button", json!({"id": "help"}))])
+            ),
+            click(1100, 13),
+            click(1200, 20),
+        ]);
+        let recording = Recording::from_json("shadow", &events.to_string()).unwrap();
+        let mut diagnostics = Diagnostics::default();
+        let (dom_maps, actions) = preprocess(&recording.events, &mut diagnostics).unwrap();
+        let rules = Settings::default().selector_rules(&SelectorStrategy::DEFAULT_ORDER);
+        let actions = generate_selectors(&actions, &dom_maps, &rules, &mut diagnostics).unwrap();
+
+        assert_eq!(actions[0].shadow_hosts, ["#app", "cart-panel"]);
+        assert_eq!(actions[0].selector, "#checkout");
+        assert_eq!(
+            display_selector(&actions[0]),
+            "#app >> cart-panel >> #checkout"
+        );
+        assert!(actions[1].shadow_hosts.is_empty());
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn turns_masked_inputs_into_parameters_instead_of_typing_the_mask() {
+        let events = json!([
+            meta(1000, "https://shop.test/login"),
+            snapshot(
+                1001,
+                json!([
+                    element(10, "input", json!({"id": "nickname", "name": "nickname"})),
+                    element(11, "input", json!({"id": "note", "name": "note"})),
+                ])
+            ),
+            input(1100, 10, "********"),
+            input(1200, 11, "5 * 3 stars"),
+        ]);
+        let recording = Recording::from_json("masked", &events.to_string()).unwrap();
+        let analysis = analyze(&recording, &CompileOptions::default()).unwrap();
+
+        let [masked, note] = &analysis.actions_with_selectors[..] else {
+            panic!("expected two actions");
+        };
+        assert!(masked.masked);
+        assert_eq!(masked.parameter.as_deref(), Some("nickname"));
+        assert_eq!(
+            replayed_value(masked).as_deref(),
+            Some(redact::MASKED_PLACEHOLDER)
+        );
+        assert_eq!(analysis.parameters.len(), 1);
+        assert_eq!(
+            analysis.parameters[0].default_value,
+            redact::MASKED_PLACEHOLDER
+        );
+        let warnings: Vec<&Diagnostic> = analysis
+            .diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.code == Code::MaskedInput)
+            .collect();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].node_id, Some(10));
+
+        // A mask character inside real text is just text
+        assert!(!note.masked);
+        assert_eq!(note.parameter, None);
+        assert_eq!(note.value.as_deref(), Some("5 * 3 stars"));
+
+        let project = compile(&recording, &CompileOptions::default()).unwrap();
+        let entry = project
+            .files
+            .iter()
+            .find(|file| file.path == project.entry)
+            .unwrap();
+        let script = String::from_utf8_lossy(&entry.contents);
+        assert!(script.contains("params.nickname"));
+        assert!(!script.contains("********"));
+    }
+
+    // A text node's new value, as rrweb records typing in a contenteditable element
+    fn edit(timestamp: i64, id: i64, value: &str) -> Value {
+        json!({"type": 3, "timestamp": timestamp,
+            "data": {"source": 0, "texts": [{"id": id, "value": value}],
+                     "attributes": [], "adds": [], "removes": []}})
+    }
+
+    // An editor holding "Hi" and a save button outside it
+    fn editor_recording(events: Vec<Value>) -> Recording {
+        let editor = json!({"type": 2, "id": 20, "tagName": "div",
+        "attributes": {"id": "editor", "contenteditable": "true"}, "childNodes": [
+            {"type": 2, "id": 21, "tagName": "b", "attributes": {}, "childNodes": [
+                {"type": 3, "id": 22, "textContent": "Hi"},
+            ]},
+        ]});
+        let mut all = vec![
+            meta(1000, "https://shop.test/notes"),
+            snapshot(
+                1001,
+                json!([editor, element(30, "button", json!({"id": "save"}))]),
+            ),
+        ];
+        all.extend(events);
+        Recording::from_json("editor", &Value::Array(all).to_string()).unwrap()
+    }
+
+    fn typed(recording: &Recording) -> Vec<(String, i64, Option<String>)> {
+        let (_, actions) = preprocess(&recording.events, &mut Diagnostics::default()).unwrap();
+        actions
+            .into_iter()
+            .map(|action| {
+                (
+                    format!("{:?}", action.action_type),
+                    action.rrweb_id,
+                    action.value,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn collapses_editor_text_mutations_into_one_typed_text_action() {
+        let recording = editor_recording(vec![
+            edit(1100, 22, "Hi t"),
+            edit(1110, 22, "Hi th"),
+            edit(1120, 22, "Hi there"),
+        ]);
+        assert_eq!(
+            typed(&recording),
+            [("TypeText".to_string(), 20, Some(" there".to_string()))]
+        );
+    }
+
+    #[test]
+    fn keeps_typing_after_a_click_inside_the_editor() {
+        let recording = editor_recording(vec![
+            edit(1100, 22, "Hi there"),
+            click(1200, 22),
+            edit(1300, 22, "Hi there, you"),
+        ]);
+        assert_eq!(
+            typed(&recording),
+            [
+                ("Click".to_string(), 22, None),
+                ("TypeText".to_string(), 20, Some(" there, you".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn ends_typing_at_a_click_outside_the_editor() {
+        let recording = editor_recording(vec![
+            edit(1100, 22, "Hi there"),
+            click(1200, 30),
+            edit(1300, 22, "Hi there, you"),
+        ]);
+        assert_eq!(
+            typed(&recording),
+            [
+                ("TypeText".to_string(), 20, Some(" there".to_string())),
+                ("Click".to_string(), 30, None),
+                ("TypeText".to_string(), 20, Some(", you".to_string())),
+            ]
+        );
+    }
+
+    fn upload_recording() -> Recording {
+        let events = json!([
+            meta(1000, "https://shop.test/apply"),
+            snapshot(
+                1001,
+                json!([element(
+                    10,
+                    "input",
+                    json!({"id": "resume", "type": "file"})
+                )])
+            ),
+            input(1100, 10, "C:\\fakepath\\x.pdf"),
+        ]);
+        Recording::from_json("upload", &events.to_string()).unwrap()
+    }
+
+    fn entry_script(project: &CompiledProject) -> String {
+        let entry = project
+            .files
+            .iter()
+            .find(|file| file.path == project.entry)
+            .unwrap();
+        String::from_utf8_lossy(&entry.contents).into_owned()
+    }
+
+    #[test]
+    fn records_uploads_by_file_name_without_the_fake_path() {
+        let (_, actions) =
+            preprocess(&upload_recording().events, &mut Diagnostics::default()).unwrap();
+        assert!(matches!(actions[0].action_type, ActionType::Upload));
+        assert_eq!(actions[0].value.as_deref(), Some("x.pdf"));
+    }
+
+    #[test]
+    fn uploads_the_placeholder_fixture_unless_one_is_mapped() {
+        let recording = upload_recording();
+        let project = compile(&recording, &CompileOptions::default()).unwrap();
+        assert!(entry_script(&project).contains(
+            "await page.locator(\"#resume\").setInputFiles(\"fixtures/sample-upload.txt\");"
+        ));
+        assert!(project
+            .diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.code == Code::MissingFixture));
+
+        let fixture = std::env::temp_dir().join(format!("imitator-{}-x.pdf", std::process::id()));
+        fs::write(&fixture, "%PDF-1.4").unwrap();
+        let options = CompileOptions {
+            fixture_files: HashMap::from([("x.pdf".to_string(), fixture.clone())]),
+            ..CompileOptions::default()
+        };
+        let project = compile(&recording, &options);
+        fs::remove_file(&fixture).unwrap();
+        let project = project.unwrap();
+        let shipped = Path::new(FIXTURES_DIR).join(fixture.file_name().unwrap());
+        assert!(entry_script(&project).contains(&format!(
+            "setInputFiles(\"{}\");",
+            shipped.to_string_lossy()
+        )));
+        assert!(project
+            .files
+            .iter()
+            .any(|file| file.path == shipped && file.contents == b"%PDF-1.4"));
+        assert!(project.diagnostics.is_empty());
+    }
+}
@@ -0,0 +1,112 @@
+// Progress and warning output.
+//
+// Everything goes to stderr so stdout stays free for command output (e.g. `--dry-run`).
+// There is no global level: each compile logs through the `Logger` in its `CompileOptions`,
+// which is `Off` by default, so the library stays silent unless the caller asks for output.
+// Rendered warnings are counted so the process exit code can tell "compiled with warnings"
+// apart.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::diagnostics::{Diagnostic, Severity};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    #[default]
+    Off, // Nothing at all
+    Quiet,   // Warnings and errors only
+    Normal,  // Progress steps
+    Verbose, // Timings and per-file details
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json, // One JSON object per line
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Logger {
+    pub level: LogLevel,
+    pub format: LogFormat,
+}
+
+static WARNINGS: AtomicUsize = AtomicUsize::new(0);
+
+// Warnings rendered by any logger in this process
+pub fn warning_count() -> usize {
+    WARNINGS.load(Ordering::Relaxed)
+}
+
+impl Logger {
+    pub fn new(level: LogLevel, format: LogFormat) -> Self {
+        Logger { level, format }
+    }
+
+    fn emit(&self, level_name: &str, text_prefix: &str, message: &str) {
+        if self.level == LogLevel::Off {
+            return;
+        }
+        match self.format {
+            LogFormat::Text => eprintln!("{}{}", text_prefix, message),
+            LogFormat::Json => eprintln!(
+                "{}",
+                serde_json::json!({ "level": level_name, "message": message })
+            ),
+        }
+    }
+
+    pub fn write_info(&self, message: &str) {
+        if self.level >= LogLevel::Normal {
+            self.emit("info", "", message);
+        }
+    }
+
+    pub fn write_detail(&self, message: &str) {
+        if self.level >= LogLevel::Verbose {
+            self.emit("debug", "", message);
+        }
+    }
+
+    pub fn error(&self, message: &str) {
+        self.emit("error", "Error: ", message);
+    }
+
+    // Renders a collected diagnostic. Warnings are counted; info diagnostics follow the
+    // progress level.
+    pub fn diagnostic(&self, diagnostic: &Diagnostic) {
+        let level_name = match diagnostic.severity {
+            _ if self.level == LogLevel::Off => return,
+            Severity::Info if self.level < LogLevel::Normal => return,
+            Severity::Info => "info",
+            Severity::Warning => {
+                WARNINGS.fetch_add(1, Ordering::Relaxed);
+                "warn"
+            }
+            Severity::Error => "error",
+        };
+        match self.format {
+            LogFormat::Text => eprintln!("{}", diagnostic),
+            LogFormat::Json => {
+                let mut line = serde_json::to_value(diagnostic).unwrap_or_default();
+                line["level"] = level_name.into();
+                eprintln!("{}", line);
+            }
+        }
+    }
+}
+
+// Progress step, shown at the normal level
+#[macro_export]
+macro_rules! info {
+    ($logger:expr, $($arg:tt)*) => { $logger.write_info(&format!($($arg)*)) };
+}
+
+// Timings and other detail, shown with --verbose
+#[macro_export]
+macro_rules! detail {
+    ($logger:expr, $($arg:tt)*) => { $logger.write_detail(&format!($($arg)*)) };
+}
+
+pub use crate::{detail, info};
@@ -1,665 +1,1000 @@
-use fs_extra::dir::{copy, CopyOptions};
-use serde::{Deserialize, Serialize};
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::{Args, Parser, Subcommand};
 use serde_json::Value;
 use std::collections::HashMap;
-use std::env;
-use std::error::Error;
+use std::fmt;
 use std::fs;
-use std::path::Path;
+use std::io;
+use std::path::{Path, PathBuf};
 use std::process;
 use std::time::Instant;
 
-// --- Type Aliases ---
-type DomMap = HashMap<i64, NodeInfo>;
-type SimplifiedActionList = Vec<SimplifiedAction>;
-type PreprocessingResultData = (DomMap, SimplifiedActionList);
+use imitator::config::{Config, ConfigError, Settings};
+use imitator::diagnostics::{Code, Diagnostic, Diagnostics};
+use imitator::emitter::EmitterRegistry;
+use imitator::logging::{self, detail, info, LogFormat, LogLevel, Logger};
+use imitator::params::{ParamMode, ParamSource};
+use imitator::report;
+use imitator::review;
+use imitator::sourcemap::{self, MappedAction, SourceMap};
+use imitator::templates::TemplateSource;
+use imitator::{
+    display_selector, display_value, CompileError, CompileOptions, EmitMode, Recording,
+    SelectorStrategy,
+};
+
+// --- Command Line Interface ---
+
+// Exit codes, also documented in `--help`. clap itself exits with 2 on usage errors.
+const EXIT_OK: i32 = 0;
+const EXIT_FAILED: i32 = 1;
+const EXIT_WARNINGS: i32 = 3;
+const EXIT_CONFIG: i32 = 4;
+const EXIT_INPUT: i32 = 5;
+
+const EXIT_CODES_HELP: &str = "Exit codes:
+  0  Completed without warnings
+  1  Failed
+  2  Invalid command line
+  3  Completed with warnings
+  4  Invalid configuration or template pack
+  5  Unusable input (recording, template pack, fixture, project or output directory)";
+
+// Why a command failed. `main` turns each kind into its exit code.
+#[derive(Debug)]
+enum CliError {
+    Config(ConfigError),
+    Compile(CompileError),
+    Io { path: PathBuf, source: io::Error },
+    OutputExists(PathBuf), // Earlier project in the way, and no --force
+    Input(String),         // Arguments or input files the command can't use
+}
 
-// --- Data Structures ---
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Config(error) => write!(f, "{}", error),
+            CliError::Compile(error) => write!(f, "{}", error),
+            CliError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            CliError::OutputExists(dir) => write!(
+                f,
+                "Output directory {:?} already exists; pass --force to overwrite it",
+                dir
+            ),
+            CliError::Input(message) => write!(f, "{}", message),
+        }
+    }
+}
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-struct Event {
-    #[serde(rename = "type")]
-    event_type: i64,
-    data: Value, // Using Value for flexibility initially
-    timestamp: i64,
+impl From<ConfigError> for CliError {
+    fn from(error: ConfigError) -> Self {
+        CliError::Config(error)
+    }
 }
 
-#[derive(Debug, Clone)]
-struct NodeInfo {
-    _rrweb_id: i64,
-    tag_name: Option<String>,
-    attributes: HashMap<String, String>,
-    _parent_id: Option<i64>,
-    _text_content: Option<String>,
+impl From<CompileError> for CliError {
+    fn from(error: CompileError) -> Self {
+        CliError::Compile(error)
+    }
 }
 
-#[derive(Debug, Clone)]
-enum ActionType {
-    Click,
-    Input,
-    // Add other types like Scroll, Navigate, etc. later
+#[derive(Parser, Debug)]
+#[command(
+    name = "imitator",
+    version,
+    about = "Compile rrweb recordings, DevTools Recorder flows and Selenium IDE projects into runnable browser automation projects",
+    after_help = EXIT_CODES_HELP
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+
+    /// Only print warnings and errors
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Print timings and per-file details
+    #[arg(short, long, global = true)]
+    verbose: bool,
+
+    /// Log output format
+    #[arg(long, global = true, default_value = "text", value_parser = ["text", "json"])]
+    log_format: String,
+
+    /// Config file to use instead of the nearest imitator.toml
+    #[arg(long, global = true, value_name = "PATH")]
+    config: Option<PathBuf>,
 }
 
-#[derive(Debug, Clone)]
-struct SimplifiedAction {
-    action_type: ActionType,
-    rrweb_id: i64,         // ID of the element interacted with
-    value: Option<String>, // For input actions
-    timestamp: i64,
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Compile a recording into a Stagehand or Playwright Test project
+    Compile(CompileArgs),
+    /// Print a summary of a recording and the actions extracted from it
+    Inspect(InspectArgs),
+    /// Check that a recording can be compiled, without writing anything
+    Validate(InspectArgs),
+    /// Scrub input values, PII and URL tokens from a recording
+    Anonymize(AnonymizeArgs),
+    /// Show the recorded events and DOM behind a line of a generated project
+    Explain(ExplainArgs),
 }
 
-#[derive(Debug, Clone)]
-struct ActionWithSelector {
-    action_type: ActionType,
-    _rrweb_id: i64,
-    value: Option<String>,
-    timestamp: i64,
-    selector: String, // CSS or XPath
+#[derive(Args, Debug)]
+struct SelectorArgs {
+    /// Selector strategies to try, in order: id, data-testid, data-cy, name, class or any
+    /// data-* attribute [default: id,data-testid,data-cy,name,class]
+    #[arg(long, value_delimiter = ',', value_parser = parse_selector_strategy)]
+    selector_strategy: Vec<SelectorStrategy>,
 }
 
-// --- Main Function ---
+impl SelectorArgs {
+    fn overrides(&self) -> Settings {
+        let mut settings = Settings::default();
+        if !self.selector_strategy.is_empty() {
+            settings.selectors.priority = Some(
+                self.selector_strategy
+                    .iter()
+                    .map(SelectorStrategy::name)
+                    .collect(),
+            );
+        }
+        settings
+    }
+}
+
+fn parse_selector_strategy(value: &str) -> Result<SelectorStrategy, String> {
+    SelectorStrategy::parse(value).ok_or_else(|| {
+        format!(
+            "expected one of {} or a data-* attribute",
+            SelectorStrategy::NAMES.join(", ")
+        )
+    })
+}
+
+#[derive(Args, Debug)]
+struct CompileArgs {
+    /// rrweb recording (JSON array of events), Chrome DevTools Recorder flow or Selenium IDE
+    /// project (.side)
+    recording: PathBuf,
+
+    /// Test to compile from a Selenium IDE project [default: the first one]
+    #[arg(long, value_name = "NAME")]
+    test: Option<String>,
+
+    /// Directory the project folder is created in [default: ./output]
+    #[arg(short, long)]
+    out_dir: Option<PathBuf>,
+
+    /// Template pack: a built-in pack name, a directory or a .zip/.tar.gz archive
+    /// [default: the target's built-in pack]
+    #[arg(long, alias = "template-dir", value_name = "NAME|PATH")]
+    template: Option<String>,
+
+    /// Code generation target: stagehand (a Stagehand script), playwright-test (a Playwright
+    /// Test spec), cypress (a Cypress spec), selenium-python (a pytest + Selenium test),
+    /// puppeteer (a Puppeteer script), chrome-recorder (a Chrome DevTools Recorder flow),
+    /// selenium-ide (a Selenium IDE project) or gherkin (a playwright-bdd feature)
+    /// [default: stagehand]
+    #[arg(long, value_parser = parse_target)]
+    target: Option<String>,
+
+    #[command(flatten)]
+    selectors: SelectorArgs,
+
+    /// How actions are emitted: Playwright locators, Stagehand `act()` instructions, or
+    /// locators with an `act()` fallback [default: locator]
+    #[arg(
+        long,
+        value_parser = PossibleValuesParser::new(["locator", "act", "hybrid"])
+            .map(|s| EmitMode::parse(&s).unwrap())
+    )]
+    mode: Option<EmitMode>,
+
+    /// Print the generated script to stdout instead of writing the project
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Replace the project directory if it already exists, deleting everything in it
+    #[arg(long)]
+    force: bool,
+
+    /// Don't write the compile report and review page into the project
+    #[arg(long)]
+    no_report: bool,
+
+    /// Map a recorded upload file name to a real fixture file (repeatable)
+    #[arg(long = "fixture", value_name = "NAME=PATH", value_parser = parse_fixture_mapping)]
+    fixtures: Vec<(String, PathBuf)>,
+
+    /// Turn input values into named parameters
+    #[arg(
+        long,
+        value_parser = PossibleValuesParser::new(["sensitive", "all"])
+            .map(|s| ParamMode::parse(&s).unwrap())
+    )]
+    params: Option<ParamMode>,
+
+    /// Where parameter values are read from at run time [default: env]
+    #[arg(
+        long,
+        value_parser = PossibleValuesParser::new(["env", "json", "csv"])
+            .map(|s| ParamSource::parse(&s).unwrap())
+    )]
+    params_source: Option<ParamSource>,
+}
+
+#[derive(Args, Debug)]
+struct InspectArgs {
+    /// rrweb recording (JSON array of events), Chrome DevTools Recorder flow or Selenium IDE
+    /// project (.side)
+    recording: PathBuf,
+
+    /// Test to compile from a Selenium IDE project [default: the first one]
+    #[arg(long, value_name = "NAME")]
+    test: Option<String>,
+
+    #[command(flatten)]
+    selectors: SelectorArgs,
+}
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn Error>> {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        eprintln!("Usage: {} <rrweb_json_path>", args[0]);
-        eprintln!("Example: {} recording.json", args[0]);
-        process::exit(1);
+#[derive(Args, Debug)]
+struct AnonymizeArgs {
+    /// rrweb recording (JSON array of events)
+    recording: PathBuf,
+
+    /// Where to write the scrubbed recording (default: <stem>.anonymized.json)
+    #[arg(short, long, conflicts_with = "in_place")]
+    output: Option<PathBuf>,
+
+    /// Overwrite the input recording
+    #[arg(long)]
+    in_place: bool,
+}
+
+#[derive(Args, Debug)]
+struct ExplainArgs {
+    /// Generated project directory (the one holding imitator-map.json)
+    project: PathBuf,
+
+    /// Line of the project's entry file, e.g. from a stack trace
+    line: usize,
+
+    /// Recording to read, if it has moved since the project was compiled
+    #[arg(long)]
+    recording: Option<PathBuf>,
+
+    /// Number of recorded events to show before and after the action's event
+    #[arg(long, default_value_t = 3)]
+    context: usize,
+}
+
+fn parse_target(value: &str) -> Result<String, String> {
+    let emitters = EmitterRegistry::default();
+    match emitters.get(value) {
+        Some(_) => Ok(value.to_string()),
+        None => Err(format!("expected one of {}", emitters.names().join(", "))),
     }
-    let rrweb_json_path = &args[1];
+}
 
-    println!(
-        "Starting conversion for '{}' to Stagehand project...",
-        rrweb_json_path
-    );
+fn parse_fixture_mapping(value: &str) -> Result<(String, PathBuf), String> {
+    value
+        .split_once('=')
+        .map(|(recorded_name, path)| (recorded_name.to_string(), PathBuf::from(path)))
+        .ok_or_else(|| "expected <recorded_file_name>=<path>".to_string())
+}
 
-    let start_time = Instant::now();
+// --- Main Function ---
 
-    // Extract initial URL and generate action sequence string
-    let (initial_url, action_sequence) = convert_rrweb_to_script(rrweb_json_path).await?;
-
-    // --- Determine Paths ---
-
-    // Find the executable's path to locate the template directory reliably
-    let exe_path = env::current_exe()?;
-    let exe_dir = exe_path
-        .parent()
-        .ok_or("Could not get executable's parent directory")?;
-    // Adjust based on where the executable is relative to the project root
-    // Assuming executable is in target/release/ or target/debug/, go up 3 levels.
-    // If run via `cargo run`, cwd might be project root, adjust accordingly.
-    // Let's try finding project root by looking for Cargo.toml
-    let mut project_root = exe_dir.to_path_buf();
-    loop {
-        if project_root.join("Cargo.toml").is_file() {
-            break;
-        }
-        if !project_root.pop() {
-            return Err("Could not find project root (Cargo.toml) from executable path".into());
+fn main() {
+    let cli = Cli::parse();
+    let level = if cli.quiet {
+        LogLevel::Quiet
+    } else if cli.verbose {
+        LogLevel::Verbose
+    } else {
+        LogLevel::Normal
+    };
+    let format = if cli.log_format == "json" {
+        LogFormat::Json
+    } else {
+        LogFormat::Text
+    };
+    let logger = &Logger::new(level, format);
+
+    let config_path = cli.config.as_deref();
+    let result = match cli.command {
+        Command::Compile(args) => run_compile(args, config_path, logger),
+        Command::Inspect(args) => run_inspect(args, config_path, logger),
+        Command::Validate(args) => run_validate(args, config_path, logger),
+        Command::Anonymize(args) => run_anonymize(args, logger),
+        Command::Explain(args) => run_explain(args, logger),
+    };
+
+    // Compile errors carry a code and location, so report them as diagnostics
+    if let Err(e) = &result {
+        match e {
+            CliError::Compile(compile_error) => logger.diagnostic(&compile_error.to_diagnostic()),
+            CliError::Config(_) => {
+                logger.diagnostic(&Diagnostic::error(Code::Config, e.to_string()))
+            }
+            _ => logger.error(&e.to_string()),
         }
     }
+    process::exit(exit_code(&result, logging::warning_count()));
+}
 
-    let template_dir = project_root.join("templates/initial_state");
-    if !template_dir.is_dir() {
-        return Err(format!("Template directory not found at {:?}", template_dir).into());
+// Exit code for a command's result and the number of warnings it reported
+fn exit_code(result: &Result<(), CliError>, warnings: usize) -> i32 {
+    match result {
+        Ok(()) if warnings > 0 => EXIT_WARNINGS,
+        Ok(()) => EXIT_OK,
+        Err(CliError::Config(_) | CliError::Compile(CompileError::Config(_))) => EXIT_CONFIG,
+        Err(
+            CliError::OutputExists(_)
+            | CliError::Input(_)
+            | CliError::Io { .. }
+            | CliError::Compile(
+                CompileError::Io { .. }
+                | CompileError::InvalidRecording { .. }
+                | CompileError::Recording(_),
+            ),
+        ) => EXIT_INPUT,
+        Err(CliError::Compile(CompileError::Template(_))) => EXIT_FAILED,
     }
+}
 
-    let output_base_dir = Path::new("./output"); // Output relative to CWD
+fn run_compile(
+    args: CompileArgs,
+    config_path: Option<&Path>,
+    logger: &Logger,
+) -> Result<(), CliError> {
+    let rrweb_json_path = args.recording.to_string_lossy().into_owned();
+    let config = load_config(config_path, logger)?;
+    let mut overrides = args.selectors.overrides();
+    overrides.emit.mode = args.mode;
+    overrides.emit.params = args.params;
+    overrides.emit.params_source = args.params_source;
+    let out_dir = args
+        .out_dir
+        .or_else(|| config.output.dir.clone())
+        .unwrap_or_else(|| PathBuf::from("./output"));
+    let target = args.target.or_else(|| config.output.target.clone());
+    let template = match args.template.or_else(|| config.output.template.clone()) {
+        Some(template_arg) => {
+            Some(TemplateSource::from_arg(&template_arg).map_err(CliError::Input)?)
+        }
+        None => None,
+    };
+    let options = CompileOptions {
+        fixture_files: args.fixtures.into_iter().collect(),
+        config,
+        overrides,
+        target,
+        template,
+        test: args.test,
+        logger: *logger,
+        ..CompileOptions::default()
+    };
+    let emitter = options.emitters.resolve(options.target.as_deref())?;
+
+    info!(
+        logger,
+        "Starting conversion for '{}' to {} project...",
+        rrweb_json_path,
+        emitter.label()
+    );
 
-    let input_path = Path::new(rrweb_json_path);
-    let input_filename_stem = input_path
-        .file_stem()
-        .ok_or_else(|| {
-            format!(
-                "Could not get file stem from input path: {}",
-                rrweb_json_path
-            )
-        })?
-        .to_str()
-        .unwrap(); // Using unwrap based on user change
+    let start_time = Instant::now();
+    let mut recording = load_recording(&args.recording, logger)?;
+    // Each test of a Selenium IDE project gets a project folder of its own
+    if let (Some(_), Some(test)) = (&recording.side, &options.test) {
+        let test = test.replace(|c: char| !c.is_alphanumeric() && c != '-' && c != '_', "-");
+        recording.name = format!("{}-{}", recording.name, test);
+    }
 
     // Output project directory (e.g., ./output/rrweb-recording-xyz/)
-    let output_project_dir = output_base_dir.join(input_filename_stem);
+    let output_project_dir = out_dir.join(&recording.name);
+    // Refuse to clobber an earlier project unless asked to; dry runs never write
+    if !args.dry_run
+        && !args.force
+        && output_project_dir.is_dir()
+        && fs::read_dir(&output_project_dir)
+            .map_err(|source| CliError::Io {
+                path: output_project_dir.clone(),
+                source,
+            })?
+            .next()
+            .is_some()
+    {
+        return Err(CliError::OutputExists(output_project_dir));
+    }
 
-    println!(
-        "Step 5: Preparing output project directory '{:?}'...",
-        output_project_dir
-    );
-    fs::create_dir_all(&output_project_dir)?;
+    let mut project = imitator::compile(&recording, &options)?;
+    render_diagnostics(logger, project.diagnostics.iter());
 
-    // --- Copy Template Files ---
-    println!(
-        "Step 6: Copying Stagehand template files from {:?}...",
-        template_dir
-    );
-    let mut copy_options = CopyOptions::new();
-    copy_options.overwrite = true;
-    copy_options.content_only = true; // Copy contents, not the 'initial_state' folder itself
-
-    // Remove unused skip_items variable
-    // let skip_items = vec![ ... ];
-
-    // --- TEMPORARY REPLACEMENT FOR fs_extra::copy with filtering ---
-    // Manual selective copy (more robust for skipping)
-    let entries = fs::read_dir(&template_dir) // Use reference to template_dir PathBuf
-        .map_err(|e| {
-            format!(
-                "Failed to read template directory {:?}: {}",
-                template_dir, e
-            )
-        })?;
-    for entry in entries {
-        let entry = entry?;
-        let path = entry.path();
-        let file_name = path.file_name().unwrap().to_str().unwrap();
-
-        // Skip unwanted items
-        if file_name == "node_modules"
-            || file_name == "downloads"
-            || file_name == "cache.json"
-            || file_name.starts_with(".")
-        {
-            // Also skip dotfiles for now unless explicitly needed
-            continue;
+    if args.dry_run {
+        // compile has already checked that the entry file exists
+        if let Some(entry_file) = project.entry_file() {
+            print!("{}", String::from_utf8_lossy(&entry_file.contents));
         }
+        info!(
+            logger,
+            "Dry run completed in {:?}; nothing was written.",
+            start_time.elapsed()
+        );
+        return Ok(());
+    }
 
-        let dest_path = output_project_dir.join(file_name);
-
-        if path.is_dir() {
-            fs::create_dir_all(&dest_path)?;
-            // Use fs_extra::dir::copy for recursive directory copying
-            // Ensure options are set correctly for subdirectories if needed
-            let mut sub_copy_options = CopyOptions::new();
-            sub_copy_options.overwrite = true;
-            copy(&path, &dest_path, &sub_copy_options).map_err(|e| {
-                format!(
-                    "Failed to copy directory {:?} to {:?}: {}",
-                    path, dest_path, e
-                )
-            })?;
-            println!("  Copied directory: {:?} -> {:?}", path, dest_path);
+    info!(
+        logger,
+        "Step 6: Writing project files to '{:?}'...", output_project_dir
+    );
+    // Files an earlier compile wrote but this one doesn't would linger, so --force starts over.
+    // The old project is only removed once the new one compiled.
+    if args.force && output_project_dir.is_dir() {
+        fs::remove_dir_all(&output_project_dir).map_err(|source| CliError::Io {
+            path: output_project_dir.clone(),
+            source,
+        })?;
+    }
+    let compile_diagnostics = project.diagnostics.len();
+    let written = project.write(&output_project_dir);
+    // Post-processing diagnostics are added while writing
+    render_diagnostics(logger, project.diagnostics.iter().skip(compile_diagnostics));
+    written?;
+    if !args.no_report {
+        project.report.write(&output_project_dir)?;
+        info!(
+            logger,
+            "  Wrote compile report: {} and {}",
+            report::JSON_FILE,
+            report::HTML_FILE
+        );
+        // Recorder flows and Selenium IDE tests list steps, not rrweb events the page could replay
+        if recording.events.is_empty() {
+            info!(
+                logger,
+                "  Skipped the review page: the recording has no rrweb events to replay"
+            );
         } else {
-            fs::copy(&path, &dest_path)
-                .map_err(|e| format!("Failed to copy file {:?} to {:?}: {}", path, dest_path, e))?;
+            review::write(&output_project_dir, &project.report, &recording.events)?;
+            info!(logger, "  Wrote review page: {}", review::FILE);
         }
     }
-    // --- END TEMPORARY REPLACEMENT ---
-
-    // --- Fill Template ---
-    println!(
-        "Step 7: Populating template '{:?}'...",
-        output_project_dir.join("index.ts")
-    );
-    let template_index_path = output_project_dir.join("index.ts");
-    let template_content = fs::read_to_string(&template_index_path)?;
-
-    // Replace placeholders
-    let final_content = template_content
-        .replace("__START_URL__", &initial_url)
-        .replace("// __ACTION_SEQUENCE__", &action_sequence); // Replace the comment line
-
-    fs::write(&template_index_path, final_content)?;
 
     let duration = start_time.elapsed();
-    println!("Conversion completed in {:?}", duration);
-    println!("Stagehand project created at: {:?}", output_project_dir);
-    println!(
-        "To run: cd {:?} && npm install && npm run start",
+    info!(logger, "Conversion completed in {:?}", duration);
+    info!(
+        logger,
+        "{} project created at: {:?}",
+        emitter.label(),
         output_project_dir
     );
+    info!(
+        logger,
+        "To run: cd {:?} && {}",
+        output_project_dir,
+        emitter.run_command()
+    );
 
     Ok(())
 }
 
-async fn convert_rrweb_to_script(
-    rrweb_json_path: &str,
-) -> Result<(String, String), Box<dyn Error>> {
-    // Returns (initial_url, action_sequence_string)
-    // Load the recording data
-    println!("Step 1: Loading rrweb events...");
-    let rrweb_events = load_json_from_file(rrweb_json_path)?;
-    println!("Loaded {} events.", rrweb_events.len());
-
-    // Extract initial metadata (like starting URL)
-    let initial_url = find_event_by_type(&rrweb_events, 4) // Type 4 is Meta
-        .and_then(|event| event.data.get("href"))
-        .and_then(|href| href.as_str())
-        .map(String::from)
-        .unwrap_or_else(|| {
-            eprintln!("Warning: Could not find initial URL (Meta event type 4 with href). Using placeholder.");
-            "http://example.com".to_string()
-        });
-    println!("Initial URL: {}", initial_url);
-
-    // --- Stage 1: Pre-processing and Action Extraction ---
-    println!("Step 2: Pre-processing and extracting actions...");
-    let (dom_map, simplified_actions) = preprocess_rrweb_data(&rrweb_events)?;
-    println!("Extracted {} simplified actions.", simplified_actions.len());
-
-    // --- Stage 2: Selector Generation (LLM-Assisted) ---
-    println!("Step 3: Generating selectors...");
-    let actions_with_selectors = generate_selectors_for_actions(&simplified_actions, &dom_map)?;
-
-    // --- Stage 3: Code Generation ---
-    println!("Step 4: Generating action sequence code...");
-    let action_sequence = generate_action_sequence_code(&actions_with_selectors).await?;
-
-    Ok((initial_url, action_sequence)) // Return both URL and action string
+// The library only collects diagnostics, so the CLI shows them once each stage returns
+fn render_diagnostics<'a>(logger: &Logger, diagnostics: impl Iterator<Item = &'a Diagnostic>) {
+    for diagnostic in diagnostics {
+        logger.diagnostic(diagnostic);
+    }
 }
 
-// --- Utility/Placeholder Functions ---
-
-fn load_json_from_file(path: &str) -> Result<Vec<Event>, Box<dyn Error>> {
+fn load_recording(path: &Path, logger: &Logger) -> Result<Recording, CliError> {
+    info!(logger, "Step 1: Loading recording...");
     let start_load = Instant::now();
-    let content = fs::read_to_string(path)?;
-    let load_duration = start_load.elapsed();
-    println!("  Time to load file: {:?}", load_duration);
-
-    let start_parse = Instant::now();
-    let events: Vec<Event> = serde_json::from_str(&content)?;
-    let parse_duration = start_parse.elapsed();
-    println!("  Time to parse JSON: {:?}", parse_duration);
-
-    Ok(events)
+    let recording = imitator::load_recording(path)?;
+    detail!(
+        logger,
+        "  Time to load recording: {:?}",
+        start_load.elapsed()
+    );
+    match (&recording.flow, &recording.side) {
+        (Some(flow), _) => info!(
+            logger,
+            "Loaded a DevTools Recorder flow with {} steps.",
+            flow.steps.len()
+        ),
+        (None, Some(project)) => info!(
+            logger,
+            "Loaded a Selenium IDE project with {} test(s).",
+            project.tests.len()
+        ),
+        (None, None) => info!(logger, "Loaded {} events.", recording.events.len()),
+    }
+    Ok(recording)
 }
 
-// Find the first event of a specific type
-fn find_event_by_type(events: &[Event], event_type: i64) -> Option<&Event> {
-    events.iter().find(|e| e.event_type == event_type)
+fn load_config(config_path: Option<&Path>, logger: &Logger) -> Result<Config, CliError> {
+    let (config, path) = Config::discover(config_path)?;
+    if let Some(path) = path {
+        info!(logger, "Using config file {:?}", path);
+    }
+    Ok(config)
 }
 
-// Placeholder for recursive DOM snapshot parsing
-fn parse_dom_snapshot(
-    node_data: &Value,
-    dom_map: &mut HashMap<i64, NodeInfo>,
-    parent_id: Option<i64>,
-) {
-    // Needs to handle node structure, attributes, children, text content, etc.
-    if let Some(id) = node_data.get("id").and_then(|v| v.as_i64()) {
-        let mut attributes_map = HashMap::new();
-        if let Some(attrs) = node_data.get("attributes").and_then(|v| v.as_object()) {
-            for (key, value) in attrs {
-                if let Some(val_str) = value.as_str() {
-                    attributes_map.insert(key.clone(), val_str.to_string());
-                } else if value.is_number() || value.is_boolean() {
-                    // Convert numbers/bools to string representation
-                    attributes_map.insert(key.clone(), value.to_string());
-                }
-                // Ignore other value types for attributes for now
-            }
+fn run_anonymize(args: AnonymizeArgs, logger: &Logger) -> Result<(), CliError> {
+    let input_path = args.recording.to_string_lossy().into_owned();
+
+    // Default to a sibling file so the original recording is never overwritten by accident
+    let output_path = match (args.in_place, args.output) {
+        (true, _) => args.recording.clone(),
+        (false, Some(path)) => path,
+        (false, None) => {
+            let stem = args
+                .recording
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .ok_or_else(|| {
+                    CliError::Input(format!(
+                        "Could not get file stem from input path: {}",
+                        input_path
+                    ))
+                })?;
+            args.recording
+                .with_file_name(format!("{}.anonymized.json", stem))
         }
+    };
+    let output_path = output_path.to_string_lossy().into_owned();
+
+    info!(logger, "Anonymizing '{}'...", input_path);
+    let stats = imitator::anonymize::anonymize_file(&input_path, &output_path)?;
+    info!(logger, "  Masked {} input value(s)", stats.inputs_masked);
+    info!(
+        logger,
+        "  Scrubbed {} text node(s)", stats.text_nodes_scrubbed
+    );
+    info!(
+        logger,
+        "  Scrubbed {} attribute(s)", stats.attributes_scrubbed
+    );
+    info!(
+        logger,
+        "  Stripped tokens from {} URL(s)", stats.urls_stripped
+    );
+    info!(logger, "Anonymized recording written to: {}", output_path);
+    Ok(())
+}
 
-        let info = NodeInfo {
-            _rrweb_id: id,
-            tag_name: node_data
-                .get("tagName")
-                .and_then(|v| v.as_str())
-                .map(String::from),
-            attributes: attributes_map, // Store parsed attributes
-            _parent_id: parent_id,
-            _text_content: node_data
-                .get("textContent")
-                .and_then(|v| v.as_str())
-                .map(String::from),
-        };
-        dom_map.insert(id, info);
+fn run_inspect(
+    args: InspectArgs,
+    config_path: Option<&Path>,
+    logger: &Logger,
+) -> Result<(), CliError> {
+    let rrweb_json_path = args.recording.to_string_lossy().into_owned();
+    let options = CompileOptions {
+        config: load_config(config_path, logger)?,
+        overrides: args.selectors.overrides(),
+        test: args.test,
+        logger: *logger,
+        ..CompileOptions::default()
+    };
+    let recording = load_recording(&args.recording, logger)?;
+    let analysis = imitator::analyze(&recording, &options)?;
+    render_diagnostics(logger, analysis.diagnostics.iter());
+
+    let node_count: usize = analysis.dom_maps.iter().map(|dom_map| dom_map.len()).sum();
+    let mut events_by_type: std::collections::BTreeMap<i64, usize> = Default::default();
+    for event in &recording.events {
+        *events_by_type.entry(event.event_type).or_insert(0) += 1;
+    }
+    let first_timestamp = recording.events.first().map_or(0, |e| e.timestamp);
+    let last_timestamp = recording.events.last().map_or(0, |e| e.timestamp);
+
+    // This is the command's output, so it goes to stdout rather than through the logger
+    if logger.format == LogFormat::Json {
+        let actions: Vec<Value> = analysis
+            .actions_with_selectors
+            .iter()
+            .map(|action| {
+                serde_json::json!({
+                    "offset_ms": action.timestamp - first_timestamp,
+                    "timestamp": action.timestamp,
+                    "action": format!("{:?}", action.action_type),
+                    "rrweb_id": action.rrweb_id,
+                    "target": action.description,
+                    "selector": display_selector(action),
+                    "value": display_value(action),
+                })
+            })
+            .collect();
+        let summary = serde_json::json!({
+            "recording": rrweb_json_path,
+            "initial_url": analysis.initial_url,
+            "event_count": recording.events.len(),
+            "step_count": recording.imported_steps().map(|steps| steps.len()),
+            "events_by_type": events_by_type
+                .iter()
+                .map(|(event_type, count)| (event_type.to_string(), *count))
+                .collect::<HashMap<_, _>>(),
+            "duration_ms": last_timestamp - first_timestamp,
+            "node_count": node_count,
+            "snapshot_count": analysis.dom_maps.len(),
+            "actions": actions,
+            "diagnostics": analysis.diagnostics.iter().collect::<Vec<_>>(),
+        });
+        println!("{:#}", summary);
+        return Ok(());
+    }
 
-        if let Some(children) = node_data.get("childNodes").and_then(|v| v.as_array()) {
-            for child_node in children {
-                parse_dom_snapshot(child_node, dom_map, Some(id));
+    println!("Recording:   {}", rrweb_json_path);
+    println!("Initial URL: {}", analysis.initial_url);
+    match (&recording.flow, &recording.side) {
+        (Some(flow), _) => println!("Steps:       {} (DevTools Recorder flow)", flow.steps.len()),
+        (None, Some(project)) => println!(
+            "Commands:    {} in {} test(s) (Selenium IDE project)",
+            project.commands().count(),
+            project.tests.len()
+        ),
+        (None, None) => {
+            println!(
+                "Events:      {} over {} ms",
+                recording.events.len(),
+                last_timestamp - first_timestamp
+            );
+            for (event_type, count) in &events_by_type {
+                println!("  type {}: {}", event_type, count);
             }
         }
     }
-}
-
-// Placeholder for applying incremental mutations to the dom_map
-// fn update_dom_map(dom_map: &mut HashMap<i64, NodeInfo>, mutation_data: &Value) {
-//     // TODO: Implement logic based on rrweb mutation data format
-//     // Needs to handle additions, removals, attribute changes, text changes
-//     // Example: Handle added nodes
-//     // if let Some(adds) = mutation_data.get("adds") {
-//     //     for addition in adds.as_array().unwrap_or(&vec![]) {
-//     //          let parent_id = addition.get("parentId").and_then(|v| v.as_i64());
-//     //          let node_data = addition.get("node");
-//     //          if let (Some(p_id), Some(n_data)) = (parent_id, node_data) {
-//     //               parse_dom_snapshot(n_data, dom_map, Some(p_id)); // Need to handle nextId correctly too
-//     //          }
-//     //     }
-//     // }
-//     // ... handle removals, attribute changes etc.
-// }
-
-// Placeholder: Flush buffered input actions
-fn flush_input_buffer(
-    current_input_buffer: &mut HashMap<i64, (String, i64)>, // Map rrweb_id -> (text, last_timestamp)
-    simplified_actions: &mut Vec<SimplifiedAction>,
-) {
-    for (rrweb_id, (text, last_timestamp)) in current_input_buffer.drain() {
-        let action = SimplifiedAction {
-            action_type: ActionType::Input,
-            rrweb_id,
-            value: Some(text),
-            timestamp: last_timestamp,
+    println!(
+        "DOM nodes:   {} in {} snapshot(s)",
+        node_count,
+        analysis.dom_maps.len()
+    );
+    println!("Actions:     {}", analysis.actions_with_selectors.len());
+    for action in &analysis.actions_with_selectors {
+        // Imported actions have no time, only the index of their step or command
+        let at = if recording.events.is_empty() && recording.imported_steps().is_some() {
+            format!("step {}", action.event_index)
+        } else {
+            format!("+{}ms", action.timestamp - first_timestamp)
         };
-        add_action(simplified_actions, action);
+        print!(
+            "  {} {:?} {} -> {}",
+            at,
+            action.action_type,
+            action.description,
+            display_selector(action)
+        );
+        if let Some(value) = display_value(action) {
+            print!(" = '{}'", value.replace('\n', "\\n"));
+        }
+        println!();
     }
+    Ok(())
 }
 
-// Placeholder: Add action (potentially with simplification logic later)
-fn add_action(action_list: &mut Vec<SimplifiedAction>, new_action: SimplifiedAction) {
-    action_list.push(new_action);
+fn run_validate(
+    args: InspectArgs,
+    config_path: Option<&Path>,
+    logger: &Logger,
+) -> Result<(), CliError> {
+    let rrweb_json_path = args.recording.to_string_lossy().into_owned();
+    let options = CompileOptions {
+        config: load_config(config_path, logger)?,
+        overrides: args.selectors.overrides(),
+        test: args.test,
+        logger: *logger,
+        ..CompileOptions::default()
+    };
+    info!(logger, "Validating '{}'...", rrweb_json_path);
+    // Problems come back as warnings on the analysis; hard failures return an error
+    let recording = load_recording(&args.recording, logger)?;
+    let analysis = imitator::analyze(&recording, &options)?;
+    render_diagnostics(logger, analysis.diagnostics.iter());
+    if analysis.actions_with_selectors.is_empty() {
+        logger.diagnostic(
+            &Diagnostic::warning(
+                Code::NoActions,
+                "No actions could be extracted from the recording.",
+            )
+            .with_hint("Only clicks, inputs, typing in editors and uploads are replayed"),
+        );
+    }
+    match logging::warning_count() {
+        0 => info!(
+            logger,
+            "Recording is valid: {} action(s) can be compiled.",
+            analysis.actions_with_selectors.len()
+        ),
+        count => info!(
+            logger,
+            "Recording can be compiled with {} warning(s).", count
+        ),
+    }
+    Ok(())
 }
 
-// --- Stage 1 Helper Function ---
-fn preprocess_rrweb_data(
-    rrweb_events: &[Event],
-) -> Result<PreprocessingResultData, Box<dyn Error>> {
-    let mut dom_map: DomMap = HashMap::new();
-    let mut simplified_actions: SimplifiedActionList = Vec::new();
-    let mut current_input_buffer: HashMap<i64, (String, i64)> = HashMap::new();
-
-    // Process initial snapshot to build the first dom_map
-    if let Some(initial_snapshot_event) = find_event_by_type(rrweb_events, 2) {
-        // Type 2 is Full Snapshot
-        println!("  Processing initial DOM snapshot...");
-        if let Some(node_data) = initial_snapshot_event.data.get("node") {
-            parse_dom_snapshot(node_data, &mut dom_map, None);
-            println!("  Initial dom_map contains {} nodes.", dom_map.len());
-        } else {
-            eprintln!("Warning: Full snapshot event found but missing 'node' data.");
-        }
-    } else {
-        return Err("Error: No initial full snapshot (type 2) event found in recording.".into());
+fn run_explain(args: ExplainArgs, logger: &Logger) -> Result<(), CliError> {
+    let source_map = SourceMap::load(&args.project)?;
+    let Some(action) = source_map.action_at(args.line) else {
+        let span = match (source_map.actions.first(), source_map.actions.last()) {
+            (Some(first), Some(last)) => format!(
+                "; recorded actions are on lines {}-{}",
+                first.start_line, last.end_line
+            ),
+            _ => String::new(),
+        };
+        return Err(CliError::Input(format!(
+            "Line {} of {} is not part of a recorded action{}",
+            args.line,
+            source_map.file.display(),
+            span
+        )));
+    };
+
+    let recording_path = args
+        .recording
+        .or_else(|| source_map.recording.clone())
+        .ok_or_else(|| {
+            CliError::Input(
+                "The source map does not say where the recording is; pass --recording".into(),
+            )
+        })?;
+    let recording = load_recording(&recording_path, logger)?;
+    if let Some(steps) = recording.imported_steps() {
+        return explain_step(
+            &source_map,
+            action,
+            args.line,
+            &recording_path,
+            &steps,
+            logger,
+        );
+    }
+    let events = &recording.events;
+    if action.event_index >= events.len() {
+        return Err(CliError::Input(format!(
+            "{} has only {} events; was the project compiled from another recording?",
+            recording_path.display(),
+            events.len()
+        )));
+    }
+    let first_timestamp = events.first().map_or(0, |e| e.timestamp);
+    let first = action.event_index.saturating_sub(args.context);
+    let last = (action.event_index + args.context).min(events.len() - 1);
+
+    // The DOM as it was when the action's last event was recorded, on the latest page by then
+    let (dom_maps, _) =
+        imitator::preprocess(&events[..=action.event_index], &mut Diagnostics::default())?;
+    let dom = dom_maps
+        .last()
+        .map(|dom_map| sourcemap::dom_neighbourhood(action.node_id, dom_map))
+        .unwrap_or_default();
+
+    // This is the command's output, so it goes to stdout rather than through the logger
+    if logger.format == LogFormat::Json {
+        let context: Vec<Value> = (first..=last)
+            .map(|index| {
+                serde_json::json!({
+                    "event_index": index,
+                    "timestamp": events[index].timestamp,
+                    "offset_ms": events[index].timestamp - first_timestamp,
+                    "description": sourcemap::describe_event(&events[index]),
+                })
+            })
+            .collect();
+        let explanation = serde_json::json!({
+            "file": source_map.file,
+            "line": args.line,
+            "recording": recording_path,
+            "action": action,
+            "events": context,
+            "dom": dom,
+        });
+        println!("{:#}", explanation);
+        return Ok(());
     }
 
-    // Process incremental events
-    println!("  Processing incremental events...");
-    for event in rrweb_events.iter() {
-        if event.event_type == 3 {
-            // Incremental Snapshot
-            if let Some(source_type) = event.data.get("source").and_then(|v| v.as_i64()) {
-                match source_type {
-                    0 => { // Mutation
-                         // update_dom_map(&mut dom_map, &event.data); // TODO: Implement this
-                    }
-                    2 => {
-                        // Mouse Interaction
-                        if let Some(interaction_type) =
-                            event.data.get("type").and_then(|v| v.as_i64())
-                        {
-                            if interaction_type == 2 {
-                                // Click
-                                if let Some(target_id) =
-                                    event.data.get("id").and_then(|v| v.as_i64())
-                                {
-                                    flush_input_buffer(
-                                        &mut current_input_buffer,
-                                        &mut simplified_actions,
-                                    ); // Flush inputs before click
-                                    let action = SimplifiedAction {
-                                        action_type: ActionType::Click,
-                                        rrweb_id: target_id,
-                                        value: None,
-                                        timestamp: event.timestamp,
-                                    };
-                                    add_action(&mut simplified_actions, action);
-                                }
-                            }
-                            // TODO: Handle other mouse interactions if needed (e.g., MouseUp)
-                        }
-                    }
-                    5 => {
-                        // Input
-                        if let (Some(target_id), Some(text)) = (
-                            event.data.get("id").and_then(|v| v.as_i64()),
-                            event.data.get("text").and_then(|v| v.as_str()),
-                        ) {
-                            // Buffer input: store last text value and timestamp for this element ID
-                            current_input_buffer
-                                .insert(target_id, (text.to_string(), event.timestamp));
-                        }
-                    }
-                    // TODO: Handle other source types (Scroll, etc.) if needed
-                    _ => {} // Ignore other incremental sources for now
-                }
-            }
+    print_mapped_action(&source_map, action, args.line);
+    println!(
+        "  Event:    {} at t={} (+{} ms) in {}",
+        action.event_index,
+        action.timestamp,
+        action.timestamp - first_timestamp,
+        recording_path.display()
+    );
+    println!();
+    println!("Recorded events:");
+    for (index, event) in events.iter().enumerate().take(last + 1).skip(first) {
+        println!(
+            "{} {:>5}  {:>10}  {}",
+            if index == action.event_index {
+                ">"
+            } else {
+                " "
+            },
+            index,
+            format!("+{} ms", event.timestamp - first_timestamp),
+            sourcemap::describe_event(event)
+        );
+    }
+    println!();
+    if dom.is_empty() {
+        println!(
+            "Node {} is not in the DOM at event {}.",
+            action.node_id, action.event_index
+        );
+    } else {
+        println!(
+            "DOM around node {} at event {}:",
+            action.node_id, action.event_index
+        );
+        for line in dom {
+            println!("{}", line);
         }
-        // TODO: Handle Meta events (type 4) for URL changes mid-recording?
     }
-
-    // Flush any remaining inputs at the end
-    flush_input_buffer(&mut current_input_buffer, &mut simplified_actions);
-
-    Ok((dom_map, simplified_actions))
+    Ok(())
 }
 
-// --- Stage 2 Helper Function ---
-fn generate_selectors_for_actions(
-    simplified_actions: &[SimplifiedAction],
-    dom_map: &HashMap<i64, NodeInfo>,
-) -> Result<Vec<ActionWithSelector>, Box<dyn Error>> {
-    let mut actions_with_selectors = Vec::new();
-
-    for action in simplified_actions {
-        let mut generated_selector = format!("TODO:selector_for_rrweb_id_{}", action.rrweb_id); // Default placeholder
-
-        if let Some(node_info) = dom_map.get(&action.rrweb_id) {
-            let mut selector_found = false;
-
-            // Helper function to create attribute selectors, escaping quotes
-            let create_attr_selector = |attr: &str, value: &str| -> String {
-                format!("*[{} = \"{}\"]", attr, value.replace('"', "\\\""))
-            };
-
-            // Strategy 1: Use ID if available and valid
-            if let Some(id_val) = node_info.attributes.get("id") {
-                if !id_val.is_empty() && !id_val.contains(char::is_whitespace) {
-                    generated_selector = format!("#{}", id_val);
-                    selector_found = true;
-                } else if !id_val.is_empty() {
-                    // Use attribute selector for invalid IDs
-                    generated_selector = create_attr_selector("id", id_val);
-                    selector_found = true;
-                }
-            }
-
-            // Strategy 2: Use data-testid if ID wasn't found/used
-            if !selector_found {
-                if let Some(test_id_val) = node_info.attributes.get("data-testid") {
-                    if !test_id_val.is_empty() {
-                        generated_selector = create_attr_selector("data-testid", test_id_val);
-                        selector_found = true;
-                    }
-                }
-            }
-
-            // Strategy 3: Use data-cy if still not found
-            if !selector_found {
-                if let Some(cy_id_val) = node_info.attributes.get("data-cy") {
-                    if !cy_id_val.is_empty() {
-                        generated_selector = create_attr_selector("data-cy", cy_id_val);
-                        selector_found = true;
-                    }
-                }
-            }
-
-            // Strategy 4: Use name attribute if still not found (often useful for form elements)
-            if !selector_found {
-                if let Some(name_val) = node_info.attributes.get("name") {
-                    if !name_val.is_empty() {
-                        // Optionally, be more specific for form elements
-                        // if let Some(tag) = &node_info.tag_name {
-                        //    if ["input", "button", "select", "textarea"].contains(&tag.to_lowercase().as_str()) {
-                        //        generated_selector = format!("{}[name=\"{}\"]", tag, name_val.replace('"', "\\\""));
-                        //        selector_found = true;
-                        //    }
-                        // }
-                        // For simplicity, use the general attribute selector for now
-                        if !selector_found {
-                            // Check again in case the more specific one above was used
-                            generated_selector = create_attr_selector("name", name_val);
-                            selector_found = true;
-                        }
-                    }
-                }
-            }
-
-            // Strategy 5: Use the first class name if still not found
-            if !selector_found {
-                if let Some(class_val) = node_info.attributes.get("class") {
-                    if !class_val.trim().is_empty() {
-                        // Split by whitespace and take the first non-empty class
-                        if let Some(first_class) = class_val.split_whitespace().next() {
-                            if !first_class.is_empty() {
-                                // Generate selector like tagname.classname
-                                // Escape the class name if it contains special CSS characters (simplistic check)
-                                let mut escaped_class = first_class.to_string();
-                                // Basic escaping - might need refinement for full CSS spec
-                                escaped_class =
-                                    escaped_class.replace(':', "\\:").replace('.', "\\.");
-
-                                if let Some(tag) = &node_info.tag_name {
-                                    generated_selector = format!("{}.{}", tag, escaped_class);
-                                    selector_found = true;
-                                } else {
-                                    // Fallback to attribute selector if tag name is missing
-                                    generated_selector = format!("*.{}", escaped_class);
-                                    selector_found = true;
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-
-            // Fallback Strategy: If no preferred selector found, mark as failed.
-            if !selector_found {
-                let tag_name = node_info.tag_name.as_deref().unwrap_or("unknown");
-                // Use a specific prefix to identify failed selectors later
-                generated_selector = format!("SELECTOR_GENERATION_FAILED::{}", tag_name);
-            }
-        } else {
-            // If node_info is None (shouldn't happen often if preprocessing is robust)
-            generated_selector = format!(
-                "SELECTOR_GENERATION_FAILED::node_not_found_id_{}",
-                action.rrweb_id
-            );
-        } // Keep the default placeholder if node_info is None
-
-        actions_with_selectors.push(ActionWithSelector {
-            action_type: action.action_type.clone(),
-            _rrweb_id: action.rrweb_id,
-            value: action.value.clone(),
-            timestamp: action.timestamp,
-            selector: generated_selector, // Use the generated or placeholder selector
+// Flows and Selenium IDE projects have no events or DOM, so the step or command the action
+// was imported from is shown instead
+fn explain_step(
+    source_map: &SourceMap,
+    action: &MappedAction,
+    line: usize,
+    recording_path: &Path,
+    steps: &[&Value],
+    logger: &Logger,
+) -> Result<(), CliError> {
+    let step = steps.get(action.event_index).ok_or_else(|| {
+        CliError::Input(format!(
+            "{} has only {} steps; was the project compiled from another recording?",
+            recording_path.display(),
+            steps.len()
+        ))
+    })?;
+
+    if logger.format == LogFormat::Json {
+        let explanation = serde_json::json!({
+            "file": source_map.file,
+            "line": line,
+            "recording": recording_path,
+            "action": action,
+            "step": step,
         });
+        println!("{:#}", explanation);
+        return Ok(());
     }
 
-    Ok(actions_with_selectors)
+    print_mapped_action(source_map, action, line);
+    println!(
+        "  Step:     {} in {}",
+        action.event_index,
+        recording_path.display()
+    );
+    println!();
+    println!("Recorded step:");
+    println!("{:#}", step);
+    Ok(())
+}
+
+fn print_mapped_action(source_map: &SourceMap, action: &MappedAction, line: usize) {
+    println!(
+        "{}:{} is in action {} (lines {}-{}): {} {}",
+        source_map.file.display(),
+        line,
+        action.index + 1,
+        action.start_line,
+        action.end_line,
+        action.action,
+        action.target
+    );
+    println!("  Selector: {}", action.selector);
 }
 
-// --- Stage 3 Helper Function ---
-// Generates the sequence of TypeScript Playwright/Stagehand action lines
-async fn generate_action_sequence_code(
-    actions_with_selectors: &[ActionWithSelector],
-) -> Result<String, Box<dyn Error>> {
-    let mut action_sequence_code = String::new();
-
-    // Prepend standard cookie consent dismissal
-    action_sequence_code.push_str("  // Attempt to dismiss common cookie banners first\n");
-    // Use getByRole with a regex for common button texts and a short timeout.
-    // Add a .catch() to ignore errors if the button isn't found.
-    action_sequence_code.push_str("  await page.getByRole('button', { name: /Accept|Agree|Allow|Got it/i }).click({ timeout: 5000 }).catch(() => { console.log('Cookie banner not found or dismissed already within 5s.'); });\n\n");
-
-    // Loop through actions and generate code lines
-    for action in actions_with_selectors {
-        // Add timestamp comment (indented)
-        action_sequence_code.push_str(&format!("  // Timestamp: {}\n", action.timestamp));
-
-        // Add comment describing the action (indented)
-        action_sequence_code.push_str(&format!(
-            "  // Action: {:?}, Selector: '{}'",
-            action.action_type, action.selector
-        ));
-        if let Some(val) = &action.value {
-            action_sequence_code.push_str(&format!(", Value: '{}'", val));
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_recorded_upload_names_to_fixture_paths() {
+        assert_eq!(
+            parse_fixture_mapping("x.pdf=fixtures/cv.pdf"),
+            Ok(("x.pdf".to_string(), PathBuf::from("fixtures/cv.pdf")))
+        );
+        assert!(parse_fixture_mapping("fixtures/cv.pdf").is_err());
+    }
+
+    #[test]
+    fn maps_results_to_the_documented_exit_codes() {
+        let io = || io::Error::new(io::ErrorKind::NotFound, "missing");
+        assert_eq!(exit_code(&Ok(()), 0), 0);
+        assert_eq!(
+            exit_code(
+                &Err(CliError::Compile(CompileError::Template("npm".into()))),
+                0
+            ),
+            1
+        );
+        assert_eq!(
+            Cli::try_parse_from(["imitator", "compile"])
+                .unwrap_err()
+                .exit_code(),
+            2
+        );
+        assert_eq!(exit_code(&Ok(()), 2), 3);
+        for config_error in [
+            CliError::Config(ConfigError::Io {
+                path: PathBuf::from("imitator.toml"),
+                source: io(),
+            }),
+            CliError::Compile(CompileError::Config("bad".into())),
+        ] {
+            assert_eq!(exit_code(&Err(config_error), 0), 4);
         }
-        action_sequence_code.push('\n');
-
-        // Add actual code generation (indented)
-        if action.selector.starts_with("SELECTOR_GENERATION_FAILED::") {
-            // If selector generation failed, add a comment and skip the action command
-            let failed_tag = action
-                .selector
-                .split("::")
-                .nth(1)
-                .unwrap_or("unknown_element");
-            action_sequence_code.push_str(&format!(
-                "  // Action skipped: Could not generate stable selector for <{}> element.\n",
-                failed_tag
-            ));
-        } else {
-            // If selector exists, generate the command wrapped in try...catch
-            match action.action_type {
-                ActionType::Click => {
-                    let escaped_selector = action.selector.replace('`', "\\`").replace('"', "\\\"");
-                    // Remove the explicit waitForSelector, rely on action timeout + try/catch
-                    action_sequence_code.push_str("  try {\n");
-                    action_sequence_code.push_str(&format!(
-                        "    await page.locator(\"{}\").click();\n",
-                        escaped_selector
-                    ));
-                    action_sequence_code.push_str("  } catch (error) {\n");
-                    action_sequence_code.push_str(&format!(
-                        "    console.warn('Action failed for selector [{}]:', (error as Error).message);\n",
-                        escaped_selector.replace('\\', " ")
-                    )); // Log cleaner selector
-                    action_sequence_code.push_str("  }\n");
-                }
-                ActionType::Input => {
-                    if let Some(val) = &action.value {
-                        let escaped_selector =
-                            action.selector.replace('`', "\\`").replace('"', "\\\"");
-                        let escaped_value = val
-                            .replace('\\', "\\\\")
-                            .replace('`', "\\`")
-                            .replace('"', "\\\"");
-
-                        // Remove the explicit waitForSelector
-
-                        let is_obscured = val.len() > 20
-                            && val.chars().all(|c| {
-                                c.is_ascii_alphanumeric() || c == '=' || c == '+' || c == '/'
-                            });
-
-                        action_sequence_code.push_str("  try {\n");
-                        if is_obscured {
-                            action_sequence_code.push_str(
-                                "    // Input value seems obscured/masked, using placeholder:\n",
-                            );
-                            action_sequence_code.push_str(&format!("    await page.locator(\"{}\").fill(\"TODO: Add realistic test data\");\n", escaped_selector));
-                        } else {
-                            action_sequence_code.push_str(&format!(
-                                "    await page.locator(\"{}\").fill(\"{}\");\n",
-                                escaped_selector, escaped_value
-                            ));
-                        }
-                        action_sequence_code.push_str("  } catch (error) {\n");
-                        action_sequence_code.push_str(&format!("    console.warn('Action failed for selector [{}]:', (error as Error).message);\n", escaped_selector.replace('\\', ""))); // Log cleaner selector
-                        action_sequence_code.push_str("  }\n");
-                    }
-                }
-            }
+        for input_error in [
+            CliError::OutputExists(PathBuf::from("output/rec")),
+            CliError::Input("no such line".into()),
+            CliError::Io {
+                path: PathBuf::from("output"),
+                source: io(),
+            },
+            CliError::Compile(CompileError::Io {
+                path: PathBuf::from("rec.json"),
+                source: io(),
+            }),
+            CliError::Compile(CompileError::Recording(Diagnostic::error(
+                Code::NoActions,
+                "Nothing to replay",
+            ))),
+        ] {
+            assert_eq!(exit_code(&Err(input_error), 0), 5);
         }
-        action_sequence_code.push('\n'); // Add blank line between actions
     }
 
-    Ok(action_sequence_code.trim_end().to_string()) // Trim trailing whitespace/newlines
-}
+    // Compile a one-click recording from a scratch directory with the given extra flags
+    fn compile_in(dir: &Path, flags: &[&str]) -> Result<(), CliError> {
+        let recording = dir.join("rec.json");
+        let events = serde_json::json!([
+            {"type": 4, "timestamp": 1000, "data": {"href": "https://shop.test/"}},
+            {"type": 2, "timestamp": 1001, "data": {"node": {"type": 0, "id": 1, "childNodes": [
+                {"type": 2, "id": 2, "tagName": "button", "attributes": {"id": "buy"},
+                 "childNodes": []}
+            ]}}},
+            {"type": 3, "timestamp": 1100, "data": {"source": 2, "type": 2, "id": 2}},
+        ]);
+        fs::write(&recording, events.to_string()).unwrap();
+        let config = dir.join("imitator.toml");
+        fs::write(&config, "").unwrap();
+        let out_dir = dir.join("output");
+        let mut args = vec!["imitator", "compile", recording.to_str().unwrap()];
+        args.extend(["-o", out_dir.to_str().unwrap(), "--no-report"]);
+        args.extend(flags);
+        let Command::Compile(args) = Cli::try_parse_from(args).unwrap().command else {
+            unreachable!()
+        };
+        run_compile(args, Some(&config), &Logger::default())
+    }
 
-// --- Other Utility Placeholders ---
-// fn get_node_info(map: &HashMap<i64, NodeInfo>, id: i64) -> Option<&NodeInfo> { map.get(&id) }
-// fn format_node_context_for_llm(node: &NodeInfo, parent: Option<&NodeInfo>) -> String { /* ... */ String::new() }
-// async fn call_llm_selector_api(prompt: &str) -> Result<String, Box<dyn Error>> { Ok("llm_generated_selector".to_string()) }
-// async fn call_llm_code_generation_api(prompt: &str) -> Result<String, Box<dyn Error>> { Ok("llm_generated_code".to_string()) }
+    #[test]
+    fn force_replaces_only_the_project_directory() {
+        let dir = std::env::temp_dir().join(format!("imitator-force-{}", std::process::id()));
+        let project = dir.join("output/rec");
+        fs::create_dir_all(&project).unwrap();
+        fs::write(project.join("stale.ts"), "").unwrap();
+        fs::create_dir_all(dir.join("output/other")).unwrap();
+        fs::write(dir.join("output/other/keep.ts"), "").unwrap();
+
+        let refused = compile_in(&dir, &[]);
+        assert!(matches!(refused, Err(CliError::OutputExists(_))));
+        assert_eq!(exit_code(&refused, 0), 5);
+        assert!(project.join("stale.ts").exists());
+
+        compile_in(&dir, &["--force"]).unwrap();
+        assert!(!project.join("stale.ts").exists());
+        assert!(project.join("index.ts").exists());
+        assert!(dir.join("output/other/keep.ts").exists());
+        assert!(dir.join("rec.json").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
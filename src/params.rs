@@ -0,0 +1,428 @@
+// Parameterized inputs: turns recorded input values into named parameters so one recording
+// can drive runs with different data, read from environment variables or a JSON/CSV data file.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::{field_label, redact, ActionType, ActionWithSelector, DomMap, NodeInfo};
+
+// Which inputs become parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ParamMode {
+    Sensitive, // Only values flagged by the redaction pass
+    All,
+}
+
+impl ParamMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "sensitive" => Some(ParamMode::Sensitive),
+            "all" => Some(ParamMode::All),
+            _ => None,
+        }
+    }
+}
+
+// Where the generated script reads parameter values from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ParamSource {
+    Env,
+    Json,
+    Csv,
+}
+
+impl ParamSource {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "env" => Some(ParamSource::Env),
+            "json" => Some(ParamSource::Json),
+            "csv" => Some(ParamSource::Csv),
+            _ => None,
+        }
+    }
+
+    // Data file written next to index.ts, if the source uses one
+    pub fn data_file_name(&self) -> Option<&'static str> {
+        match self {
+            ParamSource::Env => None,
+            ParamSource::Json => Some("params.json"),
+            ParamSource::Csv => Some("params.csv"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Parameter {
+    pub name: String,    // camelCase property on the generated `params` object
+    pub env_var: String, // SCREAMING_SNAKE_CASE environment variable
+    pub default_value: String,
+    pub sensitive: bool, // No usable recorded default: redacted, or masked by the recorder
+}
+
+// Assign a parameter to every selected input action and return the parameter list.
+// Sensitive values never become defaults; they get the redaction placeholder instead.
+// Values masked by the recorder are always parameterized, whatever the mode, since the
+// recording holds nothing worth typing back.
+pub fn derive_parameters(
+    actions_with_selectors: &mut [ActionWithSelector],
+    dom_maps: &[DomMap],
+    mode: Option<ParamMode>,
+) -> Vec<Parameter> {
+    let mut parameters: Vec<Parameter> = Vec::new();
+    let no_nodes = DomMap::new();
+
+    for action in actions_with_selectors.iter_mut() {
+        if !matches!(action.action_type, ActionType::Input | ActionType::TypeText) {
+            continue;
+        }
+        let Some(value) = &action.value else {
+            continue;
+        };
+        let selected = match mode {
+            Some(ParamMode::All) => true,
+            Some(ParamMode::Sensitive) => action.redaction.is_some(),
+            None => false,
+        };
+        if !selected && !action.masked {
+            continue;
+        }
+
+        let words = field_words(action, dom_maps.get(action.page).unwrap_or(&no_nodes));
+        let base_name = to_camel_case(&words);
+        let mut name = base_name.clone();
+        let mut suffix = 2;
+        while parameters.iter().any(|p| p.name == name) {
+            name = format!("{}{}", base_name, suffix);
+            suffix += 1;
+        }
+
+        let default_value = match &action.redaction {
+            Some(finding) => redact::placeholder(finding.kind),
+            None if action.masked => redact::MASKED_PLACEHOLDER.to_string(),
+            None => value.clone(),
+        };
+        parameters.push(Parameter {
+            env_var: to_screaming_snake_case(&name),
+            name: name.clone(),
+            default_value,
+            sensitive: action.redaction.is_some() || action.masked,
+        });
+        action.parameter = Some(name);
+    }
+
+    parameters
+}
+
+// Name source, in order of preference: label, name attribute, id, redaction kind
+fn field_words(action: &ActionWithSelector, dom_map: &HashMap<i64, NodeInfo>) -> Vec<String> {
+    let attributes = dom_map
+        .get(&action.rrweb_id)
+        .map(|node_info| &node_info.attributes);
+    let candidates = [
+        field_label(action.rrweb_id, dom_map),
+        attributes.and_then(|a| a.get("name").cloned()),
+        attributes.and_then(|a| a.get("id").cloned()),
+        action.redaction.as_ref().map(|f| f.kind.to_string()),
+    ];
+    candidates
+        .into_iter()
+        .flatten()
+        .map(|text| split_words(&text))
+        .find(|words| !words.is_empty())
+        .unwrap_or_else(|| vec!["input".to_string()])
+}
+
+// Split on anything that isn't alphanumeric and on lower-to-upper camelCase boundaries
+fn split_words(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut previous_lower = false;
+    for c in text.chars() {
+        if !c.is_ascii_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            previous_lower = false;
+            continue;
+        }
+        if c.is_ascii_uppercase() && previous_lower && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        previous_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        current.push(c.to_ascii_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    // Long labels ("Please enter the email address you signed up with") make unwieldy names
+    words.truncate(4);
+    words
+}
+
+fn to_camel_case(words: &[String]) -> String {
+    let mut name = String::new();
+    for (index, word) in words.iter().enumerate() {
+        if index == 0 {
+            name.push_str(word);
+        } else {
+            let mut chars = word.chars();
+            if let Some(first) = chars.next() {
+                name.push(first.to_ascii_uppercase());
+                name.push_str(chars.as_str());
+            }
+        }
+    }
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert_str(0, "field");
+    }
+    name
+}
+
+fn to_screaming_snake_case(camel_name: &str) -> String {
+    let mut env_var = String::new();
+    for c in camel_name.chars() {
+        if c.is_ascii_uppercase() && !env_var.is_empty() {
+            env_var.push('_');
+        }
+        env_var.push(c.to_ascii_uppercase());
+    }
+    env_var
+}
+
+// JSON string literals are valid TypeScript string literals
+fn ts_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| "\"\"".to_string())
+}
+
+// Wrap the generated action sequence with the `params` declaration. Data-file sources run the
+// whole sequence once per row, navigating back to the start URL between rows.
+pub fn wrap_action_sequence(
+    parameters: &[Parameter],
+    source: ParamSource,
+    initial_url: &str,
+    action_sequence: &str,
+) -> String {
+    let mut code = String::new();
+
+    code.push_str("  interface RecordingParams {\n");
+    for parameter in parameters {
+        code.push_str(&format!("    {}: string;\n", parameter.name));
+    }
+    code.push_str("  }\n\n");
+
+    match source {
+        ParamSource::Env => {
+            code.push_str(
+                "  // Recording parameters, overridable through environment variables (see .env.example)\n",
+            );
+            code.push_str("  const params: RecordingParams = {\n");
+            for parameter in parameters {
+                code.push_str(&format!(
+                    "    {}: process.env.{} || {},\n",
+                    parameter.name,
+                    parameter.env_var,
+                    ts_string(&parameter.default_value)
+                ));
+            }
+            code.push_str("  };\n\n");
+            code.push_str(action_sequence);
+            return code;
+        }
+        ParamSource::Json => {
+            code.push_str("  // One run of the recorded sequence per row in params.json\n");
+            code.push_str("  const { readFileSync } = await import(\"node:fs\");\n");
+            code.push_str(
+                "  const paramRows: RecordingParams[] = JSON.parse(readFileSync(\"params.json\", \"utf8\"));\n",
+            );
+        }
+        ParamSource::Csv => {
+            code.push_str("  // One run of the recorded sequence per row in params.csv (header row = parameter names)\n");
+            code.push_str("  const { readFileSync } = await import(\"node:fs\");\n");
+            code.push_str(CSV_LINE_PARSER);
+            code.push_str(CSV_READER);
+        }
+    }
+
+    code.push_str("\n  for (const [rowIndex, params] of paramRows.entries()) {\n");
+    code.push_str(&format!(
+        "    if (rowIndex > 0) {{\n      await page.goto({});\n    }}\n\n",
+        ts_string(initial_url)
+    ));
+    for line in action_sequence.lines() {
+        if line.is_empty() {
+            code.push('\n');
+        } else {
+            code.push_str("  ");
+            code.push_str(line);
+            code.push('\n');
+        }
+    }
+    code.push_str("  }");
+    code
+}
+
+// Single-line CSV parser: quoted fields may hold commas and doubled quotes, but not newlines
+pub(crate) const CSV_LINE_PARSER: &str = r#"  const parseCsvLine = (line: string): string[] => {
+    const cells: string[] = [];
+    let cell = "";
+    let quoted = false;
+    for (let i = 0; i < line.length; i++) {
+      const char = line[i];
+      if (quoted && char === '"' && line[i + 1] === '"') {
+        cell += '"';
+        i++;
+      } else if (char === '"') {
+        quoted = !quoted;
+      } else if (char === "," && !quoted) {
+        cells.push(cell);
+        cell = "";
+      } else {
+        cell += char;
+      }
+    }
+    cells.push(cell);
+    return cells;
+  };
+"#;
+
+const CSV_READER: &str = r#"  const [headerLine, ...rowLines] = readFileSync("params.csv", "utf8")
+    .split(/\r?\n/)
+    .filter((line) => line.trim() !== "");
+  const header = parseCsvLine(headerLine);
+  const paramRows = rowLines.map((line) => {
+    const cells = parseCsvLine(line);
+    return Object.fromEntries(header.map((name, i) => [name, cells[i] ?? ""]));
+  }) as unknown as RecordingParams[];
+"#;
+
+// `.env.example` lines for the parameters; sensitive ones are left for the user to fill in
+pub fn env_example_entries(parameters: &[Parameter]) -> String {
+    let mut entries = String::from("\n# Recording parameters\n");
+    for parameter in parameters {
+        let example = if parameter.sensitive {
+            String::new()
+        } else {
+            parameter.default_value.clone()
+        };
+        entries.push_str(&format!("{}={}\n", parameter.env_var, ts_string(&example)));
+    }
+    entries
+}
+
+// Contents of the data file holding the recorded values as its first row
+pub fn data_file_contents(parameters: &[Parameter], source: ParamSource) -> Option<String> {
+    match source {
+        ParamSource::Env => None,
+        ParamSource::Json => {
+            let row: serde_json::Map<String, serde_json::Value> = parameters
+                .iter()
+                .map(|p| {
+                    (
+                        p.name.clone(),
+                        serde_json::Value::String(p.default_value.clone()),
+                    )
+                })
+                .collect();
+            serde_json::to_string_pretty(&vec![row]).ok()
+        }
+        ParamSource::Csv => {
+            let csv_cell = |value: &str| {
+                if value.contains([',', '"']) {
+                    format!("\"{}\"", value.replace('"', "\"\""))
+                } else {
+                    value.to_string()
+                }
+            };
+            let header: Vec<String> = parameters.iter().map(|p| csv_cell(&p.name)).collect();
+            let row: Vec<String> = parameters
+                .iter()
+                // The CSV reader is line based, so multi-line values are flattened
+                .map(|p| csv_cell(&p.default_value.replace(['\r', '\n'], " ")))
+                .collect();
+            Some(format!("{}\n{}\n", header.join(","), row.join(",")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parameter(name: &str, default_value: &str, sensitive: bool) -> Parameter {
+        Parameter {
+            name: name.to_string(),
+            env_var: to_screaming_snake_case(name),
+            default_value: default_value.to_string(),
+            sensitive,
+        }
+    }
+
+    #[test]
+    fn reads_parameters_from_the_environment_with_recorded_defaults() {
+        let parameters = [parameter("email", "jane@shop.test", false)];
+        let code = wrap_action_sequence(
+            &parameters,
+            ParamSource::Env,
+            "https://shop.test/",
+            "  await step();\n",
+        );
+        assert!(code.contains("    email: string;\n"));
+        assert!(code.contains("    email: process.env.EMAIL || \"jane@shop.test\",\n"));
+        assert!(code.ends_with("  };\n\n  await step();\n"));
+        assert_eq!(
+            env_example_entries(&[
+                parameters[0].clone(),
+                parameter("cardNumber", "[CREDIT_CARD]", true)
+            ]),
+            "\n# Recording parameters\nEMAIL=\"jane@shop.test\"\nCARD_NUMBER=\"\"\n"
+        );
+        assert_eq!(data_file_contents(&parameters, ParamSource::Env), None);
+    }
+
+    #[test]
+    fn runs_the_sequence_once_per_data_file_row() {
+        let parameters = [parameter("email", "jane@shop.test", false)];
+        let json = wrap_action_sequence(
+            &parameters,
+            ParamSource::Json,
+            "https://shop.test/",
+            "  await step();\n\n  await next();\n",
+        );
+        assert!(json.contains("JSON.parse(readFileSync(\"params.json\", \"utf8\"))"));
+        assert!(json.contains(
+            "    if (rowIndex > 0) {\n      await page.goto(\"https://shop.test/\");\n    }\n"
+        ));
+        assert!(json.ends_with("    await step();\n\n    await next();\n  }"));
+
+        let csv = wrap_action_sequence(
+            &parameters,
+            ParamSource::Csv,
+            "https://shop.test/",
+            "  await step();\n",
+        );
+        assert!(csv.contains(CSV_LINE_PARSER));
+        assert!(csv.contains("readFileSync(\"params.csv\", \"utf8\")"));
+        assert!(csv.ends_with("    await step();\n  }"));
+    }
+
+    #[test]
+    fn writes_the_recorded_values_as_the_first_data_file_row() {
+        let parameters = [
+            parameter("email", "jane@shop.test", false),
+            parameter("street", "1 Main St, \"Rear\"", false),
+            parameter("note", "two\nlines", false),
+        ];
+        let json = data_file_contents(&parameters, ParamSource::Json).unwrap();
+        let rows: Vec<HashMap<String, String>> = serde_json::from_str(&json).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0]["street"], "1 Main St, \"Rear\"");
+        assert_eq!(rows[0]["note"], "two\nlines");
+
+        assert_eq!(
+            data_file_contents(&parameters, ParamSource::Csv).unwrap(),
+            "email,street,note\njane@shop.test,\"1 Main St, \"\"Rear\"\"\",two lines\n"
+        );
+    }
+}
@@ -0,0 +1,219 @@
+// Playwright statements shared by the TypeScript emitters: Stagehand's `page` is a Playwright
+// page, so the Stagehand script and the Playwright Test spec drive elements the same way and
+// differ only in how the statements are wrapped.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::diagnostics::Diagnostics;
+use crate::emitter::Emitter;
+use crate::{
+    display_selector, display_value, pressed_key, redact, upload_fixture, ActionType,
+    ActionWithSelector, Control, PLACEHOLDER_FIXTURE,
+};
+
+// Timestamp and description comments opening every action block (the source map looks for
+// the timestamp line)
+pub fn action_comment(action: &ActionWithSelector) -> String {
+    let mut comment = format!("  // Timestamp: {}\n", action.timestamp);
+    comment.push_str(&format!(
+        "  // Action: {:?}, Selector: '{}'",
+        action.action_type,
+        display_selector(action)
+    ));
+    if let Some(val) = display_value(action) {
+        comment.push_str(&format!(", Value: '{}'", val.replace('\n', "\\n")));
+    }
+    comment.push('\n');
+    comment
+}
+
+// Playwright statements for one action, indented by four spaces to sit inside a block
+pub fn locator_statements(
+    emitter: &dyn Emitter,
+    action: &ActionWithSelector,
+    fixture_files: &HashMap<String, PathBuf>,
+    diagnostics: &mut Diagnostics,
+) -> String {
+    let mut code = String::new();
+    let locator = locator_expression(emitter, action);
+    match action.action_type {
+        ActionType::Click => {
+            // Remove the explicit waitForSelector, rely on action timeout + try/catch
+            code.push_str(&format!("    await {}.click();\n", locator));
+        }
+        // fill() throws on selects, checkboxes and radios, so each control gets its own call
+        ActionType::Input => match action.control {
+            Control::Checkbox if action.checked == Some(false) => {
+                code.push_str(&format!("    await {}.uncheck();\n", locator));
+            }
+            Control::Checkbox | Control::Radio => {
+                code.push_str(&format!("    await {}.check();\n", locator));
+            }
+            Control::Select | Control::Field => {
+                if let Some(value) = value_expression(emitter, action) {
+                    if let (None, Some(finding)) = (&action.parameter, &action.redaction) {
+                        code.push_str(&format!(
+                            "    // Input value redacted ({}, detected by {}), using placeholder:\n",
+                            finding.kind, finding.source
+                        ));
+                    }
+                    let call = match action.control {
+                        Control::Select if action.select_by_label => {
+                            format!("selectOption({{ label: {} }})", value)
+                        }
+                        Control::Select => format!("selectOption({})", value),
+                        _ => format!("fill({})", value),
+                    };
+                    code.push_str(&format!("    await {}.{};\n", locator, call));
+                }
+            }
+        },
+        ActionType::Upload => {
+            // Playwright rejects fill() on file inputs, so hand it a fixture file instead
+            let fixture_path =
+                upload_fixture(action, fixture_files, diagnostics).unwrap_or_else(|| {
+                    code.push_str(&unmapped_upload_comment(action));
+                    PLACEHOLDER_FIXTURE.to_string()
+                });
+            code.push_str(&format!(
+                "    await {}.setInputFiles({});\n",
+                locator,
+                emitter.string_literal(&fixture_path.replace('\\', "/"))
+            ));
+        }
+        ActionType::TypeText => {
+            if let Some(value) = value_expression(emitter, action) {
+                // Focus the editing host, then type so the editor's own key handlers run
+                code.push_str(&format!("    await {}.click();\n", locator));
+                code.push_str(&format!("    await page.keyboard.type({});\n", value));
+            }
+        }
+        ActionType::KeyPress => {
+            code.push_str(&format!(
+                "    await {}.press({});\n",
+                locator,
+                emitter.string_literal(pressed_key(action))
+            ));
+        }
+    }
+    code
+}
+
+// Comment line for an upload without a mapped fixture, indented to sit inside a block
+pub fn unmapped_upload_comment(action: &ActionWithSelector) -> String {
+    format!(
+        "    // Recorded upload '{}' has no mapped fixture, using placeholder:\n",
+        action
+            .value
+            .as_deref()
+            .unwrap_or_default()
+            .replace('\n', " ")
+    )
+}
+
+// Expression for the value an action types: its parameter, the placeholder for a redacted
+// value, or the recorded value itself
+pub fn value_expression(emitter: &dyn Emitter, action: &ActionWithSelector) -> Option<String> {
+    let value = action.value.as_ref()?;
+    Some(match (&action.parameter, &action.redaction) {
+        (Some(parameter), _) => format!("params.{}", parameter),
+        (None, Some(finding)) => emitter.string_literal(&redact::placeholder(finding.kind)),
+        (None, None) => emitter.string_literal(value),
+    })
+}
+
+// Build the Playwright locator expression: one `.frameLocator()` per enclosing iframe, then
+// one `.locator()` per shadow host. Playwright's CSS engine pierces open shadow roots, so each
+// segment scopes the next one.
+pub fn locator_expression(emitter: &dyn Emitter, action: &ActionWithSelector) -> String {
+    let mut expression = String::from("page");
+    for frame in &action.frames {
+        expression.push_str(&format!(".frameLocator({})", emitter.string_literal(frame)));
+    }
+    for segment in action
+        .shadow_hosts
+        .iter()
+        .chain(std::iter::once(&action.selector))
+    {
+        expression.push_str(&format!(".locator({})", emitter.string_literal(segment)));
+    }
+    expression
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::playwright_test::PlaywrightTestEmitter;
+    use crate::testing::action;
+
+    fn statements(action: &ActionWithSelector) -> String {
+        let mut diagnostics = Diagnostics::default();
+        locator_statements(
+            &PlaywrightTestEmitter,
+            action,
+            &HashMap::new(),
+            &mut diagnostics,
+        )
+    }
+
+    #[test]
+    fn fills_text_fields() {
+        let field = action(ActionType::Input, Control::Field, "#email", Some("a@b.c"));
+        assert_eq!(
+            statements(&field),
+            "    await page.locator(\"#email\").fill(\"a@b.c\");\n"
+        );
+    }
+
+    #[test]
+    fn writes_selectors_as_escaped_string_literals() {
+        let mut field = action(ActionType::Click, Control::Field, "#user\\:name", None);
+        field.shadow_hosts = vec!["my-app[data-x=\"1\"]".to_string()];
+        assert_eq!(
+            locator_expression(&PlaywrightTestEmitter, &field),
+            "page.locator(\"my-app[data-x=\\\"1\\\"]\").locator(\"#user\\\\:name\")"
+        );
+    }
+
+    #[test]
+    fn reaches_into_iframes_before_shadow_roots() {
+        let mut field = action(ActionType::Click, Control::Field, "#holder", None);
+        field.frames = vec!["#card".to_string()];
+        field.shadow_hosts = vec!["card-form".to_string()];
+        assert_eq!(
+            locator_expression(&PlaywrightTestEmitter, &field),
+            "page.frameLocator(\"#card\").locator(\"card-form\").locator(\"#holder\")"
+        );
+    }
+
+    #[test]
+    fn selects_options_instead_of_filling() {
+        let select = action(ActionType::Input, Control::Select, "#country", Some("de"));
+        assert_eq!(
+            statements(&select),
+            "    await page.locator(\"#country\").selectOption(\"de\");\n"
+        );
+    }
+
+    #[test]
+    fn checks_and_unchecks_to_the_recorded_state() {
+        let mut checkbox = action(ActionType::Input, Control::Checkbox, "#terms", Some("on"));
+        checkbox.checked = Some(true);
+        assert_eq!(
+            statements(&checkbox),
+            "    await page.locator(\"#terms\").check();\n"
+        );
+        checkbox.checked = Some(false);
+        assert_eq!(
+            statements(&checkbox),
+            "    await page.locator(\"#terms\").uncheck();\n"
+        );
+
+        let radio = action(ActionType::Input, Control::Radio, "#express", Some("on"));
+        assert_eq!(
+            statements(&radio),
+            "    await page.locator(\"#express\").check();\n"
+        );
+    }
+}
@@ -0,0 +1,159 @@
+// Playwright Test emitter: the action sequence as `test.step()`s of an `@playwright/test` spec.
+//
+// Unlike the Stagehand script, nothing is wrapped in try/catch: a failing locator or assertion
+// fails its step, so the test runner reports where the replay diverged. Each step also gets a
+// web-first `expect` assertion: clicked targets must be visible, filled fields and selects must
+// hold the value afterwards, and checkboxes and radios must end up in the recorded state.
+
+use crate::diagnostics::Diagnostics;
+use crate::emitter::Emitter;
+use crate::playwright::{action_comment, locator_expression, locator_statements, value_expression};
+use crate::templates::TemplateSource;
+use crate::{action_summary, ActionType, ActionWithSelector, Control, Emission, EmitOptions};
+
+pub struct PlaywrightTestEmitter;
+
+impl Emitter for PlaywrightTestEmitter {
+    fn name(&self) -> &str {
+        "playwright-test"
+    }
+
+    fn label(&self) -> &str {
+        "Playwright Test"
+    }
+
+    fn template(&self) -> TemplateSource {
+        TemplateSource::Builtin("playwright-test")
+    }
+
+    fn run_command(&self) -> &str {
+        "npm install && npm test"
+    }
+
+    fn prologue(&self, options: &EmitOptions, _diagnostics: &mut Diagnostics) -> String {
+        let Some(button_pattern) = options.cookie_banner else {
+            return String::new();
+        };
+        // The banner may not be shown at all, so this is the one step allowed to fail
+        let mut code = String::from("  // Dismiss common cookie banners first, if one is shown\n");
+        code.push_str(&format!(
+            "  await page.getByRole(\"button\", {{ name: /{}/i }}).click({{ timeout: 5000 }}).catch(() => {{}});\n\n",
+            button_pattern.replace('/', "\\/")
+        ));
+        code
+    }
+
+    fn action(
+        &self,
+        action: &ActionWithSelector,
+        _previous: Option<&ActionWithSelector>,
+        emission: Emission,
+        options: &EmitOptions,
+        diagnostics: &mut Diagnostics,
+    ) -> String {
+        let mut code = action_comment(action);
+        let summary = self.string_literal(&action_summary(action));
+
+        if emission == Emission::Skipped {
+            let failed_tag = action
+                .selector
+                .split("::")
+                .nth(1)
+                .unwrap_or("unknown_element");
+            code.push_str(&format!(
+                "  // Action skipped: Could not generate stable selector for <{}> element.\n",
+                failed_tag
+            ));
+            code.push_str(&format!(
+                "  test.info().annotations.push({{ type: \"skipped-action\", description: {} }});\n",
+                summary
+            ));
+            return code;
+        }
+
+        let locator = locator_expression(self, action);
+        code.push_str(&format!("  await test.step({}, async () => {{\n", summary));
+        if let ActionType::Click = action.action_type {
+            code.push_str(&format!("    await expect({}).toBeVisible();\n", locator));
+        }
+        code.push_str(&locator_statements(
+            self,
+            action,
+            options.fixture_files,
+            diagnostics,
+        ));
+        let value = value_expression(self, action);
+        let assertion = match (&action.action_type, action.control, value) {
+            (ActionType::Input, Control::Checkbox, _) if action.checked == Some(false) => {
+                Some("not.toBeChecked()".to_string())
+            }
+            (ActionType::Input, Control::Checkbox | Control::Radio, _) => {
+                Some("toBeChecked()".to_string())
+            }
+            // A label isn't the select's value, so check the chosen option's text instead
+            (ActionType::Input, Control::Select, Some(value)) if action.select_by_label => {
+                code.push_str(&format!(
+                    "    await expect({}.locator(\"option:checked\")).toHaveText({});\n",
+                    locator, value
+                ));
+                None
+            }
+            (ActionType::Input, _, Some(value)) => Some(format!("toHaveValue({})", value)),
+            (ActionType::TypeText, _, Some(value)) => Some(format!("toContainText({})", value)),
+            _ => None,
+        };
+        if let Some(assertion) = assertion {
+            code.push_str(&format!("    await expect({}).{};\n", locator, assertion));
+        }
+        code.push_str("  });\n");
+        code
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{action, assert_golden, emit_action, emit_recording, shop_recording};
+
+    fn step(action: &ActionWithSelector) -> String {
+        emit_action(&PlaywrightTestEmitter, action).0
+    }
+
+    #[test]
+    fn asserts_selects_by_value_and_checkboxes_by_state() {
+        let select = action(ActionType::Input, Control::Select, "#country", Some("de"));
+        let code = step(&select);
+        assert!(code.contains("await page.locator(\"#country\").selectOption(\"de\");"));
+        assert!(code.contains("await expect(page.locator(\"#country\")).toHaveValue(\"de\");"));
+
+        let mut checkbox = action(ActionType::Input, Control::Checkbox, "#terms", Some("on"));
+        checkbox.checked = Some(false);
+        let code = step(&checkbox);
+        assert!(code.contains("await page.locator(\"#terms\").uncheck();"));
+        assert!(code.contains("await expect(page.locator(\"#terms\")).not.toBeChecked();"));
+        assert!(!code.contains("toHaveValue"));
+    }
+
+    #[test]
+    fn selects_and_asserts_options_imported_by_label() {
+        let mut select = action(
+            ActionType::Input,
+            Control::Select,
+            "#country",
+            Some("Germany"),
+        );
+        select.select_by_label = true;
+        let code = step(&select);
+        assert!(code.contains("selectOption({ label: \"Germany\" });"));
+        assert!(code.contains(
+            "await expect(page.locator(\"#country\").locator(\"option:checked\")).toHaveText(\"Germany\");"
+        ));
+        assert!(!code.contains("toHaveValue"));
+    }
+
+    #[test]
+    fn writes_the_shop_checkout_as_a_playwright_spec() {
+        let code = emit_recording(&PlaywrightTestEmitter, &shop_recording());
+        assert_golden("playwright-test.ts", &code);
+    }
+}
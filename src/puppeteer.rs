@@ -0,0 +1,291 @@
+// Puppeteer emitter: the action sequence as a plain Puppeteer script.
+//
+// Every action waits for its element with `waitForSelector` and then uses the page-level
+// helpers (`click`, `type`, `select`), so a missing element throws and fails the run. Clicks
+// that loaded a new page while recording are paired with `waitForNavigation`, and elements in
+// iframes are driven through the iframe's frame. Shadow roots are pierced with Puppeteer's
+// `>>>` combinator.
+
+use crate::diagnostics::Diagnostics;
+use crate::emitter::Emitter;
+use crate::playwright::{action_comment, unmapped_upload_comment, value_expression};
+use crate::templates::TemplateSource;
+use crate::{
+    pressed_key, upload_fixture, ActionType, ActionWithSelector, Control, Emission, EmitOptions,
+    PLACEHOLDER_FIXTURE,
+};
+
+pub struct PuppeteerEmitter;
+
+impl Emitter for PuppeteerEmitter {
+    fn name(&self) -> &str {
+        "puppeteer"
+    }
+
+    fn label(&self) -> &str {
+        "Puppeteer"
+    }
+
+    fn template(&self) -> TemplateSource {
+        TemplateSource::Builtin("puppeteer")
+    }
+
+    fn run_command(&self) -> &str {
+        "npm install && npm start"
+    }
+
+    fn prologue(&self, options: &EmitOptions, _diagnostics: &mut Diagnostics) -> String {
+        let Some(button_pattern) = options.cookie_banner else {
+            return String::new();
+        };
+        let mut code = String::from("  // Dismiss common cookie banners first, if one is shown\n");
+        code.push_str("  await page.evaluate((pattern) => {\n");
+        code.push_str(
+            "    const button = [...document.querySelectorAll(\"button, [role=button]\")].find((element) =>\n",
+        );
+        code.push_str("      new RegExp(pattern, \"i\").test(element.textContent ?? \"\"),\n");
+        code.push_str("    );\n");
+        code.push_str("    (button as HTMLElement | undefined)?.click();\n");
+        code.push_str(&format!(
+            "  }}, {});\n\n",
+            self.string_literal(button_pattern)
+        ));
+        code
+    }
+
+    fn action(
+        &self,
+        action: &ActionWithSelector,
+        _previous: Option<&ActionWithSelector>,
+        emission: Emission,
+        options: &EmitOptions,
+        diagnostics: &mut Diagnostics,
+    ) -> String {
+        let mut code = action_comment(action);
+
+        if emission == Emission::Skipped {
+            let failed_tag = action
+                .selector
+                .split("::")
+                .nth(1)
+                .unwrap_or("unknown_element");
+            code.push_str(&format!(
+                "  // Action skipped: Could not generate stable selector for <{}> element.\n",
+                failed_tag
+            ));
+            return code;
+        }
+
+        let mut statements = Vec::new();
+        let target = if action.frames.is_empty() {
+            "page"
+        } else {
+            statements.push(format!("const frame = {};", self.frame_expression(action)));
+            "frame"
+        };
+        let selector = self.string_literal(&deep_selector(action));
+        let wait = format!("await {}.waitForSelector({});", target, selector);
+
+        match action.action_type {
+            ActionType::Click => {
+                statements.push(wait);
+                if action.navigates {
+                    // Wait for the page the click loads, started before clicking so it isn't missed
+                    statements.push("await Promise.all([".to_string());
+                    statements.push(format!("  {}.waitForNavigation(),", target));
+                    statements.push(format!("  {}.click({}),", target, selector));
+                    statements.push("]);".to_string());
+                } else {
+                    statements.push(format!("await {}.click({});", target, selector));
+                }
+            }
+            ActionType::Input => {
+                let Some(value) = value_expression(self, action) else {
+                    return code;
+                };
+                if let (None, Some(finding)) = (&action.parameter, &action.redaction) {
+                    code.push_str(&format!(
+                        "  // Input value redacted ({}, detected by {}), using placeholder:\n",
+                        finding.kind, finding.source
+                    ));
+                }
+                statements.push(wait);
+                match action.control {
+                    // select() only takes option values, so a label is looked up first
+                    Control::Select if action.select_by_label => {
+                        statements.push(format!(
+                            "const option = await {}.$eval({}, (element, label) => Array.from((element as HTMLSelectElement).options).find((option) => option.text.trim() === label)?.value, {});",
+                            target, selector, value
+                        ));
+                        statements.push(format!(
+                            "await {}.select({}, option ?? {});",
+                            target, selector, value
+                        ));
+                    }
+                    Control::Select => {
+                        statements
+                            .push(format!("await {}.select({}, {});", target, selector, value));
+                    }
+                    Control::Checkbox | Control::Radio => {
+                        // click() toggles, so only click when the state differs from the recording
+                        statements.push(format!(
+                            "if ((await {}.$eval({}, (element) => (element as HTMLInputElement).checked)) !== {}) {{",
+                            target,
+                            selector,
+                            action.checked != Some(false)
+                        ));
+                        statements.push(format!("  await {}.click({});", target, selector));
+                        statements.push("}".to_string());
+                    }
+                    Control::Field => {
+                        // type() appends, so clear what the field holds first
+                        statements.push(format!(
+                            "await {}.$eval({}, (element) => ((element as HTMLInputElement).value = \"\"));",
+                            target, selector
+                        ));
+                        statements.push(format!("await {}.type({}, {});", target, selector, value));
+                    }
+                }
+            }
+            ActionType::Upload => {
+                let fixture_path = upload_fixture(action, options.fixture_files, diagnostics)
+                    .unwrap_or_else(|| {
+                        // The shared comment is indented for a block; the statements below aren't
+                        code.push_str(&unmapped_upload_comment(action).replacen("  ", "", 1));
+                        PLACEHOLDER_FIXTURE.to_string()
+                    });
+                statements.push(format!(
+                    "const fileInput = await {}.waitForSelector({});",
+                    target, selector
+                ));
+                statements.push(format!(
+                    "await (fileInput as ElementHandle<HTMLInputElement>).uploadFile({});",
+                    self.string_literal(&fixture_path.replace('\\', "/"))
+                ));
+            }
+            ActionType::TypeText => {
+                let Some(value) = value_expression(self, action) else {
+                    return code;
+                };
+                // Focus the editing host, then type so the editor's own key handlers run
+                statements.push(wait);
+                statements.push(format!("await {}.click({});", target, selector));
+                statements.push(format!("await page.keyboard.type({});", value));
+            }
+            ActionType::KeyPress => {
+                statements.push(wait);
+                statements.push(format!("await {}.focus({});", target, selector));
+                let press = format!(
+                    "page.keyboard.press({})",
+                    self.string_literal(pressed_key(action))
+                );
+                if action.navigates {
+                    // Enter usually submits a form, so wait for the page it loads
+                    statements.push("await Promise.all([".to_string());
+                    statements.push(format!("  {}.waitForNavigation(),", target));
+                    statements.push(format!("  {},", press));
+                    statements.push("]);".to_string());
+                } else {
+                    statements.push(format!("await {};", press));
+                }
+            }
+        }
+
+        // Blocks keep `frame`, `fileInput` and `option` local to their action
+        let block = !action.frames.is_empty()
+            || matches!(action.action_type, ActionType::Upload)
+            || (action.control == Control::Select && action.select_by_label);
+        if block {
+            code.push_str("  {\n");
+        }
+        let indent = if block { "    " } else { "  " };
+        for statement in statements {
+            code.push_str(indent);
+            code.push_str(&statement);
+            code.push('\n');
+        }
+        if block {
+            code.push_str("  }\n");
+        }
+        code
+    }
+}
+
+impl PuppeteerEmitter {
+    // The frame of the innermost iframe, reached from the page one iframe at a time
+    fn frame_expression(&self, action: &ActionWithSelector) -> String {
+        let mut expression = String::from("page");
+        for frame in &action.frames {
+            expression = format!(
+                "await (await {}.waitForSelector({}))!.contentFrame()",
+                wrap_await(&expression),
+                self.string_literal(frame)
+            );
+        }
+        expression
+    }
+}
+
+// Parenthesize an `await` expression so a method can be called on its result
+fn wrap_await(expression: &str) -> String {
+    if expression.starts_with("await ") {
+        format!("({})", expression)
+    } else {
+        expression.to_string()
+    }
+}
+
+// The element's selector, prefixed by its shadow hosts' selectors and Puppeteer's `>>>`
+// combinator, which matches inside open shadow roots
+fn deep_selector(action: &ActionWithSelector) -> String {
+    action
+        .shadow_hosts
+        .iter()
+        .chain(std::iter::once(&action.selector))
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join(" >>> ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emitter::emit_with;
+    use crate::testing::{assert_golden, emit_recording, shop_recording, two_page_recording};
+    use crate::{analyze, CompileOptions, EmitMode};
+    use std::collections::HashMap;
+
+    #[test]
+    fn waits_for_the_page_a_click_loads_and_acts_on_the_new_page() {
+        let recording = two_page_recording();
+        let mut analysis = analyze(&recording, &CompileOptions::default()).unwrap();
+        let fixture_files = HashMap::new();
+        let options = EmitOptions {
+            mode: EmitMode::Locator,
+            fixture_files: &fixture_files,
+            cookie_banner: None,
+            start_url: &analysis.initial_url,
+        };
+        let code = emit_with(
+            &PuppeteerEmitter,
+            &analysis.actions_with_selectors,
+            &options,
+            &mut analysis.diagnostics,
+        )
+        .unwrap();
+
+        let navigation = "  await Promise.all([\n    page.waitForNavigation(),\n    page.click(\"#help\"),\n  ]);\n";
+        assert!(code.contains(navigation), "{}", code);
+        // Node 30 is the help link on the first page and the pay button on the second
+        let after_navigation = &code[code.find(navigation).unwrap() + navigation.len()..];
+        assert!(after_navigation.contains("await page.waitForSelector(\"#pay\");"));
+        assert!(after_navigation.contains("await page.click(\"#pay\");"));
+        assert!(!code.contains("skipped"));
+    }
+
+    #[test]
+    fn writes_the_shop_checkout_as_a_puppeteer_script() {
+        let code = emit_recording(&PuppeteerEmitter, &shop_recording());
+        assert_golden("puppeteer.ts", &code);
+    }
+}
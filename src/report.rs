@@ -0,0 +1,431 @@
+// Compile report: what the compiler extracted from a recording and how each action was emitted.
+//
+// Written next to the generated project as `imitator-report.json` for dashboards and as
+// `imitator-report.html` for reviewing the script before trusting it. Input values are never
+// included, only whether they were redacted or parameterized.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::diagnostics::{CompileError, Diagnostic, Severity};
+use crate::{
+    display_selector, emission, Emission, EmitMode, NodeInfo, Recording, RecordingAnalysis,
+};
+
+pub const JSON_FILE: &str = "imitator-report.json";
+pub const HTML_FILE: &str = "imitator-report.html";
+
+// Target attributes worth showing; values and inline styles are left out
+const SHOWN_ATTRIBUTES: &[&str] = &[
+    "id",
+    "class",
+    "name",
+    "type",
+    "role",
+    "aria-label",
+    "placeholder",
+    "title",
+    "href",
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub recording: String,
+    pub initial_url: String,
+    pub emit_mode: EmitMode,
+    pub selector_strategies: Vec<String>, // Configured priority order
+    pub summary: Summary,
+    pub actions: Vec<ActionReport>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Summary {
+    pub actions: usize,
+    pub emitted: usize,
+    pub skipped: usize,
+    pub redacted: usize,
+    pub parameterized: usize,
+    pub warnings: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ActionReport {
+    pub index: usize,
+    pub event_index: usize,
+    pub timestamp: i64,
+    pub offset_ms: i64, // Since the first event of the recording
+    pub action: String,
+    pub target: TargetReport,
+    pub selector: String, // Including shadow hosts, as in the script's comments
+    pub strategy: Option<String>,
+    pub candidates: Vec<CandidateReport>,
+    pub emitted_as: Emission,
+    pub skip_reason: Option<String>,
+    pub redaction: Option<RedactionReport>,
+    pub parameter: Option<String>,
+    pub masked: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TargetReport {
+    pub node_id: i64,
+    pub description: String,
+    pub tag: Option<String>,
+    pub attributes: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CandidateReport {
+    pub strategy: String,
+    pub selector: String,
+    pub matches: usize,
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RedactionReport {
+    pub kind: String,
+    pub source: String,
+}
+
+pub fn build(recording: &Recording, analysis: &RecordingAnalysis, mode: EmitMode) -> Report {
+    let first_timestamp = recording.events.first().map_or(0, |e| e.timestamp);
+    let actions: Vec<ActionReport> = analysis
+        .actions_with_selectors
+        .iter()
+        .enumerate()
+        .map(|(index, action)| {
+            let node_info = analysis.node(action);
+            let emitted_as = emission(action, mode);
+            ActionReport {
+                index,
+                event_index: action.event_index,
+                timestamp: action.timestamp,
+                offset_ms: action.timestamp - first_timestamp,
+                action: format!("{:?}", action.action_type),
+                target: TargetReport {
+                    node_id: action.rrweb_id,
+                    description: action.description.clone(),
+                    tag: node_info.and_then(|node| node.tag_name.clone()),
+                    attributes: node_info.map(shown_attributes).unwrap_or_default(),
+                },
+                selector: display_selector(action),
+                strategy: action.strategy.as_ref().map(|strategy| strategy.name()),
+                candidates: action
+                    .candidates
+                    .iter()
+                    .map(|candidate| CandidateReport {
+                        strategy: candidate.strategy.name(),
+                        selector: candidate.selector.clone(),
+                        matches: candidate.matches,
+                        score: (candidate.score * 100.0).round() / 100.0,
+                    })
+                    .collect(),
+                emitted_as,
+                skip_reason: (emitted_as == Emission::Skipped).then(|| match node_info {
+                    Some(node) => format!(
+                        "No stable selector for <{}>",
+                        node.tag_name.as_deref().unwrap_or("unknown")
+                    ),
+                    None => "Target node is not in the DOM".to_string(),
+                }),
+                redaction: action.redaction.as_ref().map(|finding| RedactionReport {
+                    kind: finding.kind.to_string(),
+                    source: finding.source.to_string(),
+                }),
+                parameter: action.parameter.clone(),
+                masked: action.masked,
+            }
+        })
+        .collect();
+
+    let diagnostics: Vec<Diagnostic> = analysis.diagnostics.iter().cloned().collect();
+    let skipped = actions
+        .iter()
+        .filter(|action| action.emitted_as == Emission::Skipped)
+        .count();
+    Report {
+        recording: recording
+            .file_name
+            .clone()
+            .unwrap_or_else(|| recording.name.clone()),
+        initial_url: analysis.initial_url.clone(),
+        emit_mode: mode,
+        selector_strategies: analysis
+            .selector_rules
+            .strategies
+            .iter()
+            .map(|strategy| strategy.name())
+            .collect(),
+        summary: Summary {
+            actions: actions.len(),
+            emitted: actions.len() - skipped,
+            skipped,
+            redacted: actions.iter().filter(|a| a.redaction.is_some()).count(),
+            parameterized: actions.iter().filter(|a| a.parameter.is_some()).count(),
+            warnings: diagnostics
+                .iter()
+                .filter(|d| d.severity == Severity::Warning)
+                .count(),
+        },
+        actions,
+        diagnostics,
+    }
+}
+
+pub(crate) fn shown_attributes(node_info: &NodeInfo) -> BTreeMap<String, String> {
+    node_info
+        .attributes
+        .iter()
+        .filter(|(key, _)| SHOWN_ATTRIBUTES.contains(&key.as_str()) || key.starts_with("data-"))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect()
+}
+
+impl Report {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    // Static page with no external resources, so it can be archived with the project
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+        html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+        html.push_str(&format!(
+            "<title>Compile report: {}</title>\n",
+            escape_html(&self.recording)
+        ));
+        html.push_str(STYLE);
+        html.push_str("</head>\n<body>\n");
+        html.push_str(&format!(
+            "<h1>Compile report: {}</h1>\n",
+            escape_html(&self.recording)
+        ));
+        html.push_str(&format!(
+            "<p>Start URL: <code>{}</code> &middot; Emit mode: <code>{}</code> &middot; Selector priority: <code>{}</code></p>\n",
+            escape_html(&self.initial_url),
+            self.emit_mode.name(),
+            escape_html(&self.selector_strategies.join(", "))
+        ));
+
+        let summary = &self.summary;
+        html.push_str("<ul class=\"summary\">\n");
+        for (label, count) in [
+            ("actions", summary.actions),
+            ("emitted", summary.emitted),
+            ("skipped", summary.skipped),
+            ("redacted", summary.redacted),
+            ("parameterized", summary.parameterized),
+            ("warnings", summary.warnings),
+        ] {
+            html.push_str(&format!("<li><b>{}</b> {}</li>\n", count, label));
+        }
+        html.push_str("</ul>\n");
+
+        html.push_str("<h2>Actions</h2>\n<table>\n<tr><th>#</th><th>Time</th><th>Action</th><th>Target</th><th>Selector</th><th>Candidates</th><th>Emitted as</th><th>Values</th></tr>\n");
+        for action in &self.actions {
+            let row_class = if action.emitted_as == Emission::Skipped {
+                " class=\"skipped\""
+            } else {
+                ""
+            };
+            html.push_str(&format!("<tr{}>\n", row_class));
+            html.push_str(&format!("<td>{}</td>\n", action.index + 1));
+            html.push_str(&format!(
+                "<td>+{} ms<br><small>event {}</small></td>\n",
+                action.offset_ms, action.event_index
+            ));
+            html.push_str(&format!("<td>{}</td>\n", escape_html(&action.action)));
+
+            let attributes: Vec<String> = action
+                .target
+                .attributes
+                .iter()
+                .map(|(key, value)| format!("{}=\"{}\"", key, value))
+                .collect();
+            html.push_str(&format!(
+                "<td>{}<br><small><code>&lt;{}{}{}&gt;</code> node {}</small></td>\n",
+                escape_html(&action.target.description),
+                escape_html(action.target.tag.as_deref().unwrap_or("?")),
+                if attributes.is_empty() { "" } else { " " },
+                escape_html(&attributes.join(" ")),
+                action.target.node_id
+            ));
+
+            html.push_str(&format!(
+                "<td><code>{}</code>{}</td>\n",
+                escape_html(&action.selector),
+                action
+                    .strategy
+                    .as_ref()
+                    .map(|strategy| format!("<br><small>by {}</small>", escape_html(strategy)))
+                    .unwrap_or_default()
+            ));
+
+            html.push_str("<td>");
+            if action.candidates.is_empty() {
+                html.push_str("<small>none</small>");
+            }
+            for candidate in &action.candidates {
+                html.push_str(&format!(
+                    "<div><code>{}</code> <small>{} &middot; score {:.2} &middot; {} match{}</small></div>",
+                    escape_html(&candidate.selector),
+                    escape_html(&candidate.strategy),
+                    candidate.score,
+                    candidate.matches,
+                    if candidate.matches == 1 { "" } else { "es" }
+                ));
+            }
+            html.push_str("</td>\n");
+
+            let emitted_as = match action.emitted_as {
+                Emission::Locator => "locator".to_string(),
+                Emission::LocatorWithAct => "locator, act() fallback".to_string(),
+                Emission::Act => "act()".to_string(),
+                Emission::Skipped => format!(
+                    "skipped: {}",
+                    action.skip_reason.as_deref().unwrap_or("unknown reason")
+                ),
+            };
+            html.push_str(&format!("<td>{}</td>\n", escape_html(&emitted_as)));
+
+            let mut values = Vec::new();
+            if let Some(redaction) = &action.redaction {
+                values.push(format!(
+                    "redacted {} ({})",
+                    redaction.kind, redaction.source
+                ));
+            }
+            if action.masked {
+                values.push("masked by recorder".to_string());
+            }
+            if let Some(parameter) = &action.parameter {
+                values.push(format!("params.{}", parameter));
+            }
+            html.push_str(&format!("<td>{}</td>\n", escape_html(&values.join(", "))));
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</table>\n");
+
+        if !self.diagnostics.is_empty() {
+            html.push_str("<h2>Diagnostics</h2>\n<ul class=\"diagnostics\">\n");
+            for diagnostic in &self.diagnostics {
+                let severity = match diagnostic.severity {
+                    Severity::Info => "info",
+                    Severity::Warning => "warning",
+                    Severity::Error => "error",
+                };
+                html.push_str(&format!(
+                    "<li class=\"{}\"><pre>{}</pre></li>\n",
+                    severity,
+                    escape_html(&diagnostic.to_string())
+                ));
+            }
+            html.push_str("</ul>\n");
+        }
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+
+    // Write both report files into `dir`
+    pub fn write(&self, dir: &Path) -> Result<(), CompileError> {
+        for (file_name, contents) in [(JSON_FILE, self.to_json()), (HTML_FILE, self.to_html())] {
+            let path = dir.join(file_name);
+            fs::write(&path, contents).map_err(|source| CompileError::Io { path, source })?;
+        }
+        Ok(())
+    }
+}
+
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+const STYLE: &str = "<style>
+body { font-family: system-ui, sans-serif; margin: 2rem; color: #222; }
+code { font-size: 0.85em; background: #f3f3f3; padding: 0 0.2em; }
+table { border-collapse: collapse; width: 100%; }
+th, td { border: 1px solid #ddd; padding: 0.4em; text-align: left; vertical-align: top; }
+th { background: #fafafa; }
+tr.skipped { background: #fff1f0; }
+ul.summary { list-style: none; padding: 0; display: flex; gap: 1.5em; }
+ul.diagnostics { list-style: none; padding: 0; }
+ul.diagnostics pre { margin: 0.3em 0; white-space: pre-wrap; }
+li.warning { color: #a15c00; }
+li.error { color: #b00020; }
+li.info { color: #555; }
+</style>
+";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::params::ParamMode;
+    use crate::testing::{click, element, input, meta, snapshot};
+    use crate::{analyze, CompileOptions};
+    use serde_json::json;
+
+    #[test]
+    fn reports_actions_diagnostics_and_parameters_without_values() {
+        let events = json!([
+            meta(1000, "https://shop.test/signup"),
+            snapshot(
+                1001,
+                json!([
+                    element(
+                        10,
+                        "input",
+                        json!({"id": "email", "name": "email", "type": "email"})
+                    ),
+                    element(11, "button", json!({"id": "join", "class": "btn"})),
+                ])
+            ),
+            input(1100, 10, "jane@shop.test"),
+            click(1200, 11),
+            click(1300, 99),
+        ]);
+        let recording = Recording::from_json("signup", &events.to_string()).unwrap();
+        let mut options = CompileOptions::default();
+        options.overrides.emit.params = Some(ParamMode::Sensitive);
+        let analysis = analyze(&recording, &options).unwrap();
+        let report = build(&recording, &analysis, EmitMode::Locator);
+
+        assert_eq!(report.initial_url, "https://shop.test/signup");
+        let summary = &report.summary;
+        assert_eq!(
+            (summary.actions, summary.emitted, summary.skipped),
+            (3, 2, 1)
+        );
+        assert_eq!((summary.redacted, summary.parameterized), (1, 1));
+        assert_eq!(summary.warnings, 1);
+
+        let email = &report.actions[0];
+        assert_eq!(email.offset_ms, 100);
+        assert_eq!(email.selector, "#email");
+        assert_eq!(email.parameter.as_deref(), Some("email"));
+        assert_eq!(email.redaction.as_ref().unwrap().kind, "email");
+        let join = &report.actions[1];
+        assert_eq!(join.target.description, "button");
+        assert_eq!(join.target.attributes["class"], "btn");
+        let missing = &report.actions[2];
+        assert_eq!(missing.emitted_as, Emission::Skipped);
+        assert_eq!(
+            missing.skip_reason.as_deref(),
+            Some("Target node is not in the DOM")
+        );
+        assert!(report
+            .diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.node_id == Some(99)));
+
+        for output in [report.to_json(), report.to_html()] {
+            assert!(!output.contains("jane@shop.test"));
+        }
+    }
+}
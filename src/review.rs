@@ -0,0 +1,326 @@
+// Review page: the recording replayed next to the actions compiled from it.
+//
+// Written next to the compile report as `imitator-review.html`. The recording and the report's
+// actions are embedded in the page; only the rrweb player itself is loaded from the CDN, like
+// the Replayer in `index.html`. Selecting an action seeks the player to its timestamp and
+// outlines the target node by its rrweb id. Values the compiler redacted are masked in the
+// embedded recording as well, so the page can be shared like the report.
+
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use crate::diagnostics::CompileError;
+use crate::report::{escape_html, Report};
+use crate::{Emission, Event};
+
+pub const FILE: &str = "imitator-review.html";
+
+const RRWEB_SCRIPT: &str = "https://cdn.jsdelivr.net/npm/rrweb@latest/dist/rrweb.min.js";
+const RRWEB_STYLE: &str = "https://cdn.jsdelivr.net/npm/rrweb@latest/dist/style.css";
+
+pub fn to_html(report: &Report, events: &[Event]) -> String {
+    let redacted_nodes: HashSet<i64> = report
+        .actions
+        .iter()
+        .filter(|action| action.redaction.is_some())
+        .map(|action| action.target.node_id)
+        .collect();
+    let events: Vec<Value> = events
+        .iter()
+        .map(|event| {
+            let mut data = event.data.clone();
+            mask_values(&mut data, &redacted_nodes);
+            serde_json::json!({
+                "type": event.event_type,
+                "data": data,
+                "timestamp": event.timestamp,
+            })
+        })
+        .collect();
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!(
+        "<title>Review: {}</title>\n",
+        escape_html(&report.recording)
+    ));
+    html.push_str(&format!(
+        "<link rel=\"stylesheet\" href=\"{}\">\n<script src=\"{}\"></script>\n",
+        RRWEB_STYLE, RRWEB_SCRIPT
+    ));
+    html.push_str(STYLE);
+    html.push_str("</head>\n<body>\n");
+    html.push_str(&format!(
+        "<header><h1>Review: {}</h1><code>{}</code></header>\n",
+        escape_html(&report.recording),
+        escape_html(&report.initial_url)
+    ));
+    html.push_str("<main>\n<section class=\"player\">\n<div id=\"player\"></div>\n");
+    html.push_str("<div class=\"controls\"><button id=\"play\">Play</button><div id=\"timeline\"><div id=\"playhead\"></div></div><span id=\"time\"></span></div>\n");
+    html.push_str("</section>\n<ol id=\"actions\">\n");
+    for action in &report.actions {
+        let status = match action.emitted_as {
+            Emission::Locator => "locator",
+            Emission::LocatorWithAct => "locator + act()",
+            Emission::Act => "act()",
+            Emission::Skipped => "skipped",
+        };
+        html.push_str(&format!(
+            "<li data-index=\"{}\"{}><b>{}</b> {}<br><small>+{} ms &middot; node {} &middot; {}</small><br><code>{}</code></li>\n",
+            action.index,
+            if action.emitted_as == Emission::Skipped {
+                " class=\"skipped\""
+            } else {
+                ""
+            },
+            escape_html(&action.action),
+            escape_html(&action.target.description),
+            action.offset_ms,
+            action.target.node_id,
+            status,
+            escape_html(&action.selector)
+        ));
+    }
+    html.push_str("</ol>\n</main>\n");
+
+    html.push_str(&format!(
+        "<script type=\"application/json\" id=\"recording\">{}</script>\n",
+        script_json(&Value::Array(events))
+    ));
+    html.push_str(&format!(
+        "<script type=\"application/json\" id=\"compiled-actions\">{}</script>\n",
+        script_json(&serde_json::to_value(&report.actions).unwrap_or_default())
+    ));
+    html.push_str(SCRIPT);
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+pub fn write(dir: &Path, report: &Report, events: &[Event]) -> Result<(), CompileError> {
+    let path = dir.join(FILE);
+    fs::write(&path, to_html(report, events)).map_err(|source| CompileError::Io { path, source })
+}
+
+// Mask input values and `value` attributes of the given nodes, keeping their length so the
+// replay still shows typing
+fn mask_values(value: &mut Value, nodes: &HashSet<i64>) {
+    match value {
+        Value::Object(object) => {
+            if object
+                .get("id")
+                .and_then(Value::as_i64)
+                .is_some_and(|id| nodes.contains(&id))
+            {
+                if let Some(Value::String(text)) = object.get_mut("text") {
+                    *text = "*".repeat(text.chars().count());
+                }
+                if let Some(Value::String(text)) = object
+                    .get_mut("attributes")
+                    .and_then(|attributes| attributes.get_mut("value"))
+                {
+                    *text = "*".repeat(text.chars().count());
+                }
+            }
+            for child in object.values_mut() {
+                mask_values(child, nodes);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                mask_values(item, nodes);
+            }
+        }
+        _ => {}
+    }
+}
+
+// JSON that is safe inside a <script> element: `<` only occurs in strings, so escaping it
+// keeps `</script>` and `<!--` in recorded text from ending the element early
+fn script_json(value: &Value) -> String {
+    serde_json::to_string(value)
+        .unwrap_or_default()
+        .replace('<', "\\u003c")
+}
+
+const STYLE: &str = "<style>
+body { font-family: system-ui, sans-serif; margin: 0; color: #222; height: 100vh; display: flex; flex-direction: column; }
+header { padding: 0.6em 1em; border-bottom: 1px solid #ddd; display: flex; gap: 1em; align-items: baseline; }
+header h1 { font-size: 1.1em; margin: 0; }
+main { flex: 1; display: flex; min-height: 0; }
+section.player { flex: 1; display: flex; flex-direction: column; min-width: 0; background: #f0f0f0; }
+#player { flex: 1; overflow: auto; position: relative; }
+.controls { display: flex; gap: 0.6em; align-items: center; padding: 0.5em; background: #fff; border-top: 1px solid #ddd; }
+#timeline { flex: 1; height: 14px; background: #e4e4e4; position: relative; cursor: pointer; }
+#timeline .marker { position: absolute; top: 0; width: 3px; height: 14px; background: #1c7ed6; }
+#timeline .marker.skipped { background: #e03131; }
+#timeline .marker.active { background: #e8590c; width: 5px; }
+#playhead { position: absolute; top: -2px; width: 2px; height: 18px; background: #222; }
+#time { font-variant-numeric: tabular-nums; min-width: 6em; }
+#actions { width: 28em; margin: 0; padding: 0.5em 0.5em 0.5em 2.5em; overflow: auto; border-left: 1px solid #ddd; }
+#actions li { padding: 0.4em; cursor: pointer; border-bottom: 1px solid #eee; }
+#actions li:hover { background: #f5f9ff; }
+#actions li.active { background: #fff4e6; }
+#actions li.skipped small { color: #b00020; }
+code { font-size: 0.85em; background: #f3f3f3; padding: 0 0.2em; word-break: break-all; }
+</style>
+";
+
+const SCRIPT: &str = r##"<script>
+(function () {
+  const events = JSON.parse(document.getElementById("recording").textContent);
+  const actions = JSON.parse(document.getElementById("compiled-actions").textContent);
+  const items = document.querySelectorAll("#actions li");
+  const timeline = document.getElementById("timeline");
+  const playhead = document.getElementById("playhead");
+  const time = document.getElementById("time");
+  const play = document.getElementById("play");
+
+  if (typeof rrweb === "undefined" || events.length < 2) {
+    document.getElementById("player").textContent =
+      typeof rrweb === "undefined"
+        ? "The rrweb player could not be loaded; the actions are still listed."
+        : "The recording has too few events to replay.";
+    return;
+  }
+
+  const replayer = new rrweb.Replayer(events, {
+    root: document.getElementById("player"),
+    mouseTail: false,
+  });
+  const total = Math.max(replayer.getMetaData().totalTime, 1);
+  let playing = false;
+  let highlighted = null;
+
+  const markers = actions.map((action, i) => {
+    const marker = document.createElement("div");
+    marker.className = "marker" + (action.emitted_as === "skipped" ? " skipped" : "");
+    marker.style.left = (Math.min(action.offset_ms, total) / total) * 100 + "%";
+    marker.title = "#" + (i + 1) + " " + action.action + " " + action.target.description;
+    marker.addEventListener("click", (event) => {
+      event.stopPropagation();
+      select(i);
+    });
+    timeline.appendChild(marker);
+    return marker;
+  });
+
+  function showTime(offset) {
+    playhead.style.left = (Math.min(offset, total) / total) * 100 + "%";
+    time.textContent = "+" + Math.round(offset) + " ms";
+  }
+
+  function highlight(nodeId) {
+    if (highlighted) {
+      highlighted.node.style.outline = highlighted.outline;
+      highlighted = null;
+    }
+    const node = replayer.getMirror().getNode(nodeId);
+    if (node && node.nodeType === 1) {
+      highlighted = { node: node, outline: node.style.outline };
+      node.style.outline = "3px solid #e8590c";
+      node.scrollIntoView({ block: "center" });
+    }
+  }
+
+  function select(i) {
+    const action = actions[i];
+    replayer.pause(action.offset_ms);
+    playing = false;
+    play.textContent = "Play";
+    showTime(action.offset_ms);
+    items.forEach((item, j) => item.classList.toggle("active", j === i));
+    markers.forEach((marker, j) => marker.classList.toggle("active", j === i));
+    items[i].scrollIntoView({ block: "nearest" });
+    // The DOM is rebuilt while seeking; highlight once it has settled
+    requestAnimationFrame(() => highlight(action.target.node_id));
+  }
+
+  items.forEach((item, i) => item.addEventListener("click", () => select(i)));
+
+  timeline.addEventListener("click", (event) => {
+    const rect = timeline.getBoundingClientRect();
+    const offset = ((event.clientX - rect.left) / rect.width) * total;
+    replayer.pause(offset);
+    playing = false;
+    play.textContent = "Play";
+    showTime(offset);
+  });
+
+  play.addEventListener("click", () => {
+    if (playing) {
+      replayer.pause();
+    } else {
+      const offset = replayer.getCurrentTime();
+      replayer.play(offset >= total ? 0 : offset);
+    }
+    playing = !playing;
+    play.textContent = playing ? "Pause" : "Play";
+  });
+
+  replayer.on("finish", () => {
+    playing = false;
+    play.textContent = "Play";
+  });
+
+  setInterval(() => {
+    if (playing) {
+      showTime(replayer.getCurrentTime());
+    }
+  }, 100);
+
+  replayer.pause(0);
+  showTime(0);
+})();
+</script>
+"##;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{element, input, meta, snapshot};
+    use crate::{analyze, report, CompileOptions, EmitMode, Recording};
+    use serde_json::json;
+
+    fn review_page(events: Value) -> String {
+        let recording = Recording::from_json("review", &events.to_string()).unwrap();
+        let analysis = analyze(&recording, &CompileOptions::default()).unwrap();
+        let report = report::build(&recording, &analysis, EmitMode::Locator);
+        to_html(&report, &recording.events)
+    }
+
+    #[test]
+    fn keeps_recorded_markup_from_closing_the_embedded_json() {
+        let page = review_page(json!([
+            meta(1000, "https://shop.test/"),
+            snapshot(
+                1001,
+                json!([{"type": 3, "id": 10, "textContent": "</script><script>alert(1)</script><!--"}])
+            ),
+        ]));
+        assert!(!page.contains("alert(1)</script>"));
+        assert!(!page.contains("<!--"));
+        assert!(page.contains("\\u003c/script>\\u003cscript>alert(1)"));
+        // The page's own scripts: the player, the two JSON blocks and the inline script
+        assert_eq!(page.matches("</script>").count(), 4);
+    }
+
+    #[test]
+    fn masks_redacted_values_in_the_embedded_recording() {
+        let page = review_page(json!([
+            meta(1000, "https://shop.test/"),
+            snapshot(
+                1001,
+                json!([element(
+                    10,
+                    "input",
+                    json!({"id": "email", "type": "email"})
+                )])
+            ),
+            input(1100, 10, "jane@shop.test"),
+        ]));
+        assert!(!page.contains("jane@shop.test"));
+        assert!(page.contains("\"text\":\"**************\""));
+    }
+}
@@ -0,0 +1,343 @@
+// Selenium emitter: the action sequence as a pytest test driving Selenium WebDriver in Python.
+//
+// Every element is looked up through `WebDriverWait` with an `expected_conditions` check, so
+// the test waits for the page instead of sleeping, and a lookup that times out fails the test.
+// Python needs its own string literals and comments, and the sequence is indented for the
+// body of the template's test function.
+
+use crate::diagnostics::Diagnostics;
+use crate::emitter::Emitter;
+use crate::params::{ParamSource, Parameter};
+use crate::templates::TemplateSource;
+use crate::{
+    display_selector, display_value, pressed_key, redact, upload_fixture, ActionType,
+    ActionWithSelector, Control, Emission, EmitOptions, PLACEHOLDER_FIXTURE,
+};
+
+pub struct SeleniumPythonEmitter;
+
+impl Emitter for SeleniumPythonEmitter {
+    fn name(&self) -> &str {
+        "selenium-python"
+    }
+
+    fn label(&self) -> &str {
+        "Selenium (Python)"
+    }
+
+    fn template(&self) -> TemplateSource {
+        TemplateSource::Builtin("selenium-python")
+    }
+
+    fn run_command(&self) -> &str {
+        "pip install -r requirements.txt && pytest"
+    }
+
+    fn string_literal(&self, value: &str) -> String {
+        let mut literal = String::from("\"");
+        for c in value.chars() {
+            match c {
+                '\\' => literal.push_str("\\\\"),
+                '"' => literal.push_str("\\\""),
+                '\n' => literal.push_str("\\n"),
+                '\r' => literal.push_str("\\r"),
+                '\t' => literal.push_str("\\t"),
+                c if c.is_control() => literal.push_str(&format!("\\u{:04x}", c as u32)),
+                c => literal.push(c),
+            }
+        }
+        literal.push('"');
+        literal
+    }
+
+    fn prologue(&self, options: &EmitOptions, _diagnostics: &mut Diagnostics) -> String {
+        let Some(button_pattern) = options.cookie_banner else {
+            return String::new();
+        };
+        let mut code = String::from("    # Dismiss common cookie banners first, if one is shown\n");
+        code.push_str(
+            "    for button in driver.find_elements(By.CSS_SELECTOR, \"button, [role=button]\"):\n",
+        );
+        code.push_str(&format!(
+            "        if button.is_displayed() and re.search({}, button.text, re.IGNORECASE):\n",
+            self.string_literal(button_pattern)
+        ));
+        code.push_str("            button.click()\n");
+        code.push_str("            break\n\n");
+        code
+    }
+
+    fn action(
+        &self,
+        action: &ActionWithSelector,
+        _previous: Option<&ActionWithSelector>,
+        emission: Emission,
+        options: &EmitOptions,
+        diagnostics: &mut Diagnostics,
+    ) -> String {
+        let mut code = format!("    # Timestamp: {}\n", action.timestamp);
+        code.push_str(&format!(
+            "    # Action: {:?}, Selector: '{}'",
+            action.action_type,
+            display_selector(action)
+        ));
+        if let Some(val) = display_value(action) {
+            code.push_str(&format!(", Value: '{}'", val.replace('\n', "\\n")));
+        }
+        code.push('\n');
+
+        if emission == Emission::Skipped {
+            let failed_tag = action
+                .selector
+                .split("::")
+                .nth(1)
+                .unwrap_or("unknown_element");
+            code.push_str(&format!(
+                "    # Action skipped: Could not generate stable selector for <{}> element.\n",
+                failed_tag
+            ));
+            return code;
+        }
+        // Inputs without a value have nothing to replay
+        let value = self.value_expression(action);
+        if matches!(action.action_type, ActionType::Input) && value.is_none() {
+            return code;
+        }
+
+        // Elements in iframes are looked up after switching into each frame in turn
+        for frame in &action.frames {
+            code.push_str(&format!(
+                "    wait.until(EC.frame_to_be_available_and_switch_to_it({}))\n",
+                self.locator(frame)
+            ));
+        }
+        match action.action_type {
+            ActionType::Click => {
+                code.push_str(&format!(
+                    "    {}.click()\n",
+                    self.wait_for(action, "element_to_be_clickable")
+                ));
+            }
+            ActionType::Input => {
+                let value = value.unwrap_or_default();
+                if let (None, Some(finding)) = (&action.parameter, &action.redaction) {
+                    code.push_str(&format!(
+                        "    # Input value redacted ({}, detected by {}), using placeholder:\n",
+                        finding.kind, finding.source
+                    ));
+                }
+                let element = self.wait_for(action, "element_to_be_clickable");
+                match action.control {
+                    Control::Select => {
+                        let method = if action.select_by_label {
+                            "select_by_visible_text"
+                        } else {
+                            "select_by_value"
+                        };
+                        code.push_str(&format!(
+                            "    Select({}).{}({})\n",
+                            self.wait_for(action, "visibility_of_element_located"),
+                            method,
+                            value
+                        ));
+                    }
+                    Control::Checkbox | Control::Radio => {
+                        // click() toggles, so only click when the state differs from the recording
+                        let checked = action.checked != Some(false);
+                        code.push_str(&format!("    element = {}\n", element));
+                        code.push_str(if checked {
+                            "    if not element.is_selected():\n"
+                        } else {
+                            "    if element.is_selected():\n"
+                        });
+                        code.push_str("        element.click()\n");
+                    }
+                    Control::Field => {
+                        code.push_str(&format!("    element = {}\n", element));
+                        code.push_str("    element.clear()\n");
+                        code.push_str(&format!("    element.send_keys({})\n", value));
+                    }
+                }
+            }
+            ActionType::Upload => {
+                // File inputs are often hidden behind a styled button, so presence is enough
+                let fixture_path = upload_fixture(action, options.fixture_files, diagnostics)
+                    .unwrap_or_else(|| {
+                        code.push_str(&format!(
+                            "    # Recorded upload '{}' has no mapped fixture, using placeholder:\n",
+                            action
+                                .value
+                                .as_deref()
+                                .unwrap_or_default()
+                                .replace('\n', " ")
+                        ));
+                        PLACEHOLDER_FIXTURE.to_string()
+                    });
+                code.push_str(&format!(
+                    "    {}.send_keys(os.path.abspath({}))\n",
+                    self.wait_for(action, "presence_of_element_located"),
+                    self.string_literal(&fixture_path.replace('\\', "/"))
+                ));
+            }
+            ActionType::TypeText => {
+                if let Some(value) = value {
+                    // Focus the editing host, then type so the editor's own key handlers run
+                    code.push_str(&format!(
+                        "    {}.click()\n",
+                        self.wait_for(action, "element_to_be_clickable")
+                    ));
+                    code.push_str(&format!(
+                        "    driver.switch_to.active_element.send_keys({})\n",
+                        value
+                    ));
+                }
+            }
+            ActionType::KeyPress => {
+                code.push_str(&format!(
+                    "    {}.send_keys(Keys.{})\n",
+                    self.wait_for(action, "element_to_be_clickable"),
+                    pressed_key(action).to_uppercase()
+                ));
+            }
+        }
+        if !action.frames.is_empty() {
+            code.push_str("    driver.switch_to.default_content()\n");
+        }
+        code
+    }
+
+    fn wrap_parameters(
+        &self,
+        parameters: &[Parameter],
+        source: ParamSource,
+        initial_url: &str,
+        action_sequence: &str,
+    ) -> String {
+        let mut code = String::new();
+        match source {
+            ParamSource::Env => {
+                code.push_str(
+                    "    # Recording parameters, overridable through environment variables (see .env.example)\n",
+                );
+                code.push_str("    params = {\n");
+                for parameter in parameters {
+                    code.push_str(&format!(
+                        "        {}: os.environ.get({}) or {},\n",
+                        self.string_literal(&parameter.name),
+                        self.string_literal(&parameter.env_var),
+                        self.string_literal(&parameter.default_value)
+                    ));
+                }
+                code.push_str("    }\n\n");
+                code.push_str(action_sequence);
+                return code;
+            }
+            ParamSource::Json => {
+                code.push_str("    # One run of the recorded sequence per row in params.json\n");
+                code.push_str("    import json\n\n");
+                code.push_str(
+                    "    with open(\"params.json\", encoding=\"utf-8\") as params_file:\n",
+                );
+                code.push_str("        param_rows = json.load(params_file)\n");
+            }
+            ParamSource::Csv => {
+                code.push_str("    # One run of the recorded sequence per row in params.csv (header row = parameter names)\n");
+                code.push_str("    import csv\n\n");
+                code.push_str(
+                    "    with open(\"params.csv\", newline=\"\", encoding=\"utf-8\") as params_file:\n",
+                );
+                code.push_str("        param_rows = list(csv.DictReader(params_file))\n");
+            }
+        }
+
+        code.push_str("\n    for row_index, params in enumerate(param_rows):\n");
+        code.push_str(&format!(
+            "        if row_index > 0:\n            driver.get({})\n\n",
+            self.string_literal(initial_url)
+        ));
+        for line in action_sequence.lines() {
+            if line.is_empty() {
+                code.push('\n');
+            } else {
+                code.push_str("    ");
+                code.push_str(line);
+                code.push('\n');
+            }
+        }
+        code.trim_end().to_string()
+    }
+}
+
+impl SeleniumPythonEmitter {
+    // `wait.until()` call yielding the action's element once `condition` holds. Elements in
+    // shadow roots are found through each host's `shadow_root`, which takes CSS selectors only.
+    fn wait_for(&self, action: &ActionWithSelector, condition: &str) -> String {
+        if action.shadow_hosts.is_empty() {
+            return format!(
+                "wait.until(EC.{}({}))",
+                condition,
+                self.locator(&action.selector)
+            );
+        }
+        let mut lookup = String::from("driver");
+        for segment in action
+            .shadow_hosts
+            .iter()
+            .chain(std::iter::once(&action.selector))
+        {
+            if lookup != "driver" {
+                lookup.push_str(".shadow_root");
+            }
+            lookup.push_str(&format!(
+                ".find_element(By.CSS_SELECTOR, {})",
+                self.string_literal(segment)
+            ));
+        }
+        // WebDriverWait retries while the lookup raises NoSuchElementException
+        format!("wait.until(lambda driver: {})", lookup)
+    }
+
+    // `(By, selector)` tuple for expected_conditions
+    fn locator(&self, selector: &str) -> String {
+        let by = if selector.starts_with('/') || selector.starts_with("(/") {
+            "By.XPATH"
+        } else {
+            "By.CSS_SELECTOR"
+        };
+        format!("({}, {})", by, self.string_literal(selector))
+    }
+
+    // Parameters are looked up in the `params` dict, since their names may be Python keywords
+    fn value_expression(&self, action: &ActionWithSelector) -> Option<String> {
+        let value = action.value.as_ref()?;
+        Some(match (&action.parameter, &action.redaction) {
+            (Some(parameter), _) => format!("params[{}]", self.string_literal(parameter)),
+            (None, Some(finding)) => self.string_literal(&redact::placeholder(finding.kind)),
+            (None, None) => self.string_literal(value),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{action, assert_golden, emit_action, emit_recording, shop_recording};
+
+    #[test]
+    fn selects_by_visible_text_when_the_option_was_named_by_label() {
+        let mut select = action(ActionType::Input, Control::Select, "#country", Some("de"));
+        let (code, _) = emit_action(&SeleniumPythonEmitter, &select);
+        assert!(code.contains(".select_by_value(\"de\")"));
+
+        select.value = Some("Germany".to_string());
+        select.select_by_label = true;
+        let (code, _) = emit_action(&SeleniumPythonEmitter, &select);
+        assert!(code.contains(".select_by_visible_text(\"Germany\")"));
+    }
+
+    #[test]
+    fn writes_the_shop_checkout_as_a_pytest_test() {
+        let code = emit_recording(&SeleniumPythonEmitter, &shop_recording());
+        assert_golden("selenium-python.py", &code);
+    }
+}
@@ -0,0 +1,638 @@
+// Selenium IDE projects: the `.side` JSON files Selenium IDE saves.
+//
+// Like DevTools Recorder flows, `.side` tests are a front end next to rrweb: `import` maps one
+// test's commands (open, click, type, select, check, selectFrame…) to the compiler's actions,
+// with stand-in nodes built from their locators. A project holds several tests; an action's
+// event index is the position of its command among every test's commands, in file order.
+// Waits are dropped, since every target waits for an element before acting on it.
+//
+// `SideEmitter` goes the other way: the `selenium-ide` target writes compiled actions out as a
+// one-test project that Selenium IDE opens and `selenium-side-runner` runs.
+
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::devtools::stand_in_node;
+use crate::diagnostics::{Code, CompileError, Diagnostic, Diagnostics};
+use crate::emitter::Emitter;
+use crate::params::{ParamSource, Parameter};
+use crate::templates::TemplateSource;
+use crate::{
+    control_kind, describe_node, pressed_key, replayed_value, ActionType, ActionWithSelector,
+    Control, Emission, EmitOptions, ImportedActions, SelectorCandidate, SelectorStrategy,
+};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SideProject {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub url: String, // Base URL that `open` targets are relative to
+    pub tests: Vec<SideTest>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SideTest {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub commands: Vec<Value>, // Kept as JSON so `explain` can show them as saved
+}
+
+impl SideProject {
+    // Every test's commands, in file order
+    pub fn commands(&self) -> impl Iterator<Item = &Value> {
+        self.tests.iter().flat_map(|test| test.commands.iter())
+    }
+}
+
+// Import the test named `test_name`, or the first one
+pub(crate) fn import(
+    project: &SideProject,
+    test_name: Option<&str>,
+    diagnostics: &mut Diagnostics,
+) -> Result<ImportedActions, CompileError> {
+    let names = || {
+        project
+            .tests
+            .iter()
+            .map(|test| format!("'{}'", test.name))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let position = match test_name {
+        Some(name) => project
+            .tests
+            .iter()
+            .position(|test| test.name == name)
+            .ok_or_else(|| {
+                CompileError::Config(format!(
+                    "Selenium IDE project has no test '{}'; its tests are {}",
+                    name,
+                    names()
+                ))
+            })?,
+        None => 0,
+    };
+    let test = project.tests.get(position).ok_or_else(|| {
+        CompileError::Recording(Diagnostic::error(
+            Code::NoActions,
+            "Selenium IDE project has no tests.",
+        ))
+    })?;
+    if test_name.is_none() && project.tests.len() > 1 {
+        diagnostics.push(
+            Diagnostic::info(
+                Code::MultipleTests,
+                format!(
+                    "Compiling test '{}', the first of {}: {}",
+                    test.name,
+                    project.tests.len(),
+                    names()
+                ),
+            )
+            .with_hint("Pass --test to compile another one"),
+        );
+    }
+    let first_index: usize = project.tests[..position]
+        .iter()
+        .map(|test| test.commands.len())
+        .sum();
+
+    let mut imported = ImportedActions {
+        initial_url: None,
+        viewport: None,
+        dom_map: HashMap::new(),
+        actions: Vec::new(),
+    };
+    let mut url = None;
+    let mut frames: Vec<String> = Vec::new();
+
+    for (index, command) in test.commands.iter().enumerate() {
+        let index = first_index + index;
+        let field = |key: &str| command.get(key).and_then(Value::as_str).unwrap_or_default();
+        let (name, target, value) = (field("command"), field("target"), field("value"));
+        let unsupported = |diagnostics: &mut Diagnostics, message: String| {
+            diagnostics.push(
+                Diagnostic::warning(Code::UnsupportedStep, message).at_event(index, index as i64),
+            );
+        };
+
+        let mut select_by_label = false;
+        let (action_type, value, checked) = match name {
+            // Commented out in the IDE
+            "" => continue,
+            _ if name.starts_with("//") => continue,
+            "open" if imported.initial_url.is_none() => {
+                imported.initial_url = Some(absolute_url(&project.url, target));
+                url = imported.initial_url.clone();
+                continue;
+            }
+            "open" => {
+                unsupported(
+                    diagnostics,
+                    format!(
+                        "Opening {} is not replayed; only the first open command is the start URL.",
+                        target
+                    ),
+                );
+                url = Some(absolute_url(&project.url, target));
+                continue;
+            }
+            "setWindowSize" => {
+                imported.viewport = target.split_once('x').and_then(|(width, height)| {
+                    Some((width.trim().parse().ok()?, height.trim().parse().ok()?))
+                });
+                continue;
+            }
+            "selectFrame" => {
+                match target {
+                    "relative=top" => frames.clear(),
+                    "relative=parent" => {
+                        frames.pop();
+                    }
+                    _ => match locator(target) {
+                        Some(frame) => frames.push(frame),
+                        None => unsupported(
+                            diagnostics,
+                            format!(
+                                "Frame '{}' can't be turned into a selector; the following commands run in the enclosing frame.",
+                                target
+                            ),
+                        ),
+                    },
+                }
+                continue;
+            }
+            // Every target waits for an element before acting on it, and keeps its own pace
+            "pause" | "echo" | "setSpeed" => continue,
+            _ if name.starts_with("waitForElement") && !name.contains("Not") => continue,
+            "click" | "clickAt" => (ActionType::Click, None, None),
+            "type" => (ActionType::Input, Some(value.to_string()), None),
+            "editContent" => (ActionType::TypeText, Some(value.to_string()), None),
+            // Enter on its own, usually to submit a form; other keys are typed text in the IDE
+            "sendKeys" if value == "${KEY_ENTER}" => {
+                (ActionType::KeyPress, Some("Enter".to_string()), None)
+            }
+            "check" => (ActionType::Input, Some("on".to_string()), Some(true)),
+            "uncheck" => (ActionType::Input, Some("on".to_string()), Some(false)),
+            "select" => {
+                // Options are picked by label unless prefixed with `value=`
+                let option = match value.strip_prefix("value=") {
+                    Some(option_value) => option_value,
+                    None => {
+                        select_by_label = true;
+                        value.strip_prefix("label=").unwrap_or(value)
+                    }
+                };
+                if value.starts_with("index=") || value.starts_with("id=") {
+                    unsupported(
+                        diagnostics,
+                        format!("Option '{}' can't be selected by value or label; the select command was skipped.", value),
+                    );
+                    continue;
+                }
+                (ActionType::Input, Some(option.to_string()), None)
+            }
+            _ => {
+                unsupported(
+                    diagnostics,
+                    format!(
+                        "Selenium IDE command '{}' is not supported and was skipped.",
+                        name
+                    ),
+                );
+                continue;
+            }
+        };
+
+        // The saved locator first, then the alternatives the IDE recorded with it
+        let mut selectors: Vec<String> = Vec::new();
+        let alternatives = command
+            .get("targets")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|alternative| alternative.get(0)?.as_str());
+        for selector in std::iter::once(target)
+            .chain(alternatives)
+            .filter_map(locator)
+        {
+            if !selectors.contains(&selector) {
+                selectors.push(selector);
+            }
+        }
+
+        let rrweb_id = index as i64 + 1;
+        let mut node = stand_in_node(rrweb_id, None);
+        for css in selectors.iter().filter(|selector| !is_xpath(selector)) {
+            let other = stand_in_node(rrweb_id, Some(css));
+            node.tag_name = node.tag_name.or(other.tag_name);
+            for (attribute, value) in other.attributes {
+                node.attributes.entry(attribute).or_insert(value);
+            }
+        }
+        if let Some(text) = target
+            .strip_prefix("linkText=")
+            .or_else(|| target.strip_prefix("partialLinkText="))
+        {
+            node.tag_name = Some("a".to_string());
+            node.attributes
+                .insert("aria-label".to_string(), text.to_string());
+        }
+        // What the command acts on, when the locators don't say
+        let (tag, input_type) = match name {
+            "select" => ("select", None),
+            "check" | "uncheck" => ("input", Some("checkbox")),
+            "type" | "sendKeys" => ("input", None),
+            _ => ("", None),
+        };
+        if node.tag_name.is_none() && !tag.is_empty() {
+            node.tag_name = Some(tag.to_string());
+        }
+        if let Some(input_type) = input_type {
+            node.attributes
+                .entry("type".to_string())
+                .or_insert_with(|| input_type.to_string());
+        }
+        imported.dom_map.insert(rrweb_id, node);
+
+        let selector = selectors.first().cloned().unwrap_or_else(|| {
+            diagnostics.push(
+                Diagnostic::warning(
+                    Code::SelectorFailed,
+                    format!(
+                        "Locator '{}' can't be turned into a CSS or XPath selector; the action will be skipped.",
+                        target
+                    ),
+                )
+                .at_event(index, index as i64)
+                .with_hint("Use an id, name, css, xpath or linkText locator in Selenium IDE"),
+            );
+            "SELECTOR_GENERATION_FAILED::element".to_string()
+        });
+        imported.actions.push(ActionWithSelector {
+            action_type,
+            rrweb_id,
+            value,
+            checked,
+            control: control_kind(rrweb_id, &imported.dom_map),
+            select_by_label,
+            timestamp: index as i64,
+            event_index: index,
+            url: url.clone(),
+            navigates: false, // The IDE waits for page loads without saying which commands cause them
+            page: 0,
+            strategy: (!selectors.is_empty()).then_some(SelectorStrategy::Recorder),
+            selector,
+            candidates: selectors
+                .iter()
+                .map(|selector| SelectorCandidate {
+                    strategy: SelectorStrategy::Recorder,
+                    selector: selector.clone(),
+                    shadow_hosts: Vec::new(),
+                    matches: 1, // Unknown without a DOM
+                    score: SelectorStrategy::Recorder.stability(),
+                })
+                .collect(),
+            shadow_hosts: Vec::new(),
+            frames: frames.clone(),
+            redaction: None,
+            parameter: None,
+            masked: false,
+            description: describe_node(rrweb_id, &imported.dom_map),
+        });
+    }
+
+    Ok(imported)
+}
+
+// `open` targets are usually paths relative to the project's base URL
+fn absolute_url(base_url: &str, target: &str) -> String {
+    if target.contains("://") {
+        target.to_string()
+    } else {
+        format!(
+            "{}/{}",
+            base_url.trim_end_matches('/'),
+            target.trim_start_matches('/')
+        )
+    }
+}
+
+// A CSS or XPath selector for a Selenium IDE locator such as `id=email` or `css=#email`
+fn locator(locator: &str) -> Option<String> {
+    if is_xpath(locator) {
+        return Some(locator.to_string());
+    }
+    let attribute =
+        |name: &str, value: &str| format!("*[{} = \"{}\"]", name, value.replace('"', "\\\""));
+    let (kind, value) = locator.split_once('=')?;
+    match kind {
+        "id" if !value.is_empty()
+            && !value.starts_with(|c: char| c.is_ascii_digit())
+            && value
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '-' || c == '_') =>
+        {
+            Some(format!("#{}", value))
+        }
+        "id" | "name" => Some(attribute(kind, value)),
+        "css" | "xpath" => Some(value.to_string()),
+        "linkText" => Some(format!("//a[normalize-space(.)={}]", xpath_literal(value)?)),
+        "partialLinkText" => Some(format!("//a[contains(., {})]", xpath_literal(value)?)),
+        _ => None,
+    }
+}
+
+fn is_xpath(selector: &str) -> bool {
+    selector.starts_with('/') || selector.starts_with("(/")
+}
+
+// XPath 1.0 string literals can't escape quotes, only use the other kind
+fn xpath_literal(value: &str) -> Option<String> {
+    if !value.contains('"') {
+        Some(format!("\"{}\"", value))
+    } else if !value.contains('\'') {
+        Some(format!("'{}'", value))
+    } else {
+        None
+    }
+}
+
+pub struct SideEmitter;
+
+impl Emitter for SideEmitter {
+    fn name(&self) -> &str {
+        "selenium-ide"
+    }
+
+    fn label(&self) -> &str {
+        "Selenium IDE"
+    }
+
+    fn template(&self) -> TemplateSource {
+        TemplateSource::Builtin("selenium-ide")
+    }
+
+    fn run_command(&self) -> &str {
+        "npm install && npm test"
+    }
+
+    // Commands are array elements, so each starts with the comma that follows the previous one
+    fn separator(&self) -> &str {
+        ""
+    }
+
+    fn action(
+        &self,
+        action: &ActionWithSelector,
+        _previous: Option<&ActionWithSelector>,
+        emission: Emission,
+        _options: &EmitOptions,
+        diagnostics: &mut Diagnostics,
+    ) -> String {
+        // Skipped actions were reported when their selector failed, and JSON has no comments
+        if emission == Emission::Skipped {
+            return String::new();
+        }
+        let unsupported = |diagnostics: &mut Diagnostics, message: &str| {
+            diagnostics.push(
+                Diagnostic::warning(Code::UnsupportedStep, message)
+                    .at_event(action.event_index, action.timestamp)
+                    .on_node(action.rrweb_id),
+            );
+        };
+        if !action.shadow_hosts.is_empty() {
+            unsupported(
+                diagnostics,
+                "Selenium IDE locators can't reach into shadow roots; the action was left out.",
+            );
+            return String::new();
+        }
+
+        // Parameters are IDE variables, stored at the start of the test
+        let value = match &action.parameter {
+            Some(parameter) => format!("${{{}}}", parameter),
+            None => replayed_value(action).unwrap_or_default(),
+        };
+        let (command, value) = match (&action.action_type, action.control) {
+            (ActionType::Click, _) => ("click", String::new()),
+            (ActionType::Input, Control::Select) if action.select_by_label => {
+                ("select", format!("label={}", value))
+            }
+            (ActionType::Input, Control::Select) => ("select", format!("value={}", value)),
+            (ActionType::Input, Control::Checkbox) if action.checked == Some(false) => {
+                ("uncheck", String::new())
+            }
+            (ActionType::Input, Control::Checkbox | Control::Radio) => ("check", String::new()),
+            (ActionType::Input, Control::Field) => ("type", value),
+            (ActionType::TypeText, _) => ("editContent", value),
+            (ActionType::KeyPress, _) => (
+                "sendKeys",
+                format!("${{KEY_{}}}", pressed_key(action).to_uppercase()),
+            ),
+            (ActionType::Upload, _) => {
+                unsupported(
+                    diagnostics,
+                    "Selenium IDE can't choose files to upload; the upload was left out.",
+                );
+                return String::new();
+            }
+        };
+
+        let targets: Vec<(String, &str)> = action
+            .candidates
+            .iter()
+            .map(|candidate| candidate.selector.as_str())
+            .chain(
+                action
+                    .candidates
+                    .is_empty()
+                    .then_some(action.selector.as_str()),
+            )
+            .map(|selector| {
+                let kind = if is_xpath(selector) {
+                    "xpath:attributes"
+                } else {
+                    "css:finder"
+                };
+                (side_locator(selector), kind)
+            })
+            .collect();
+
+        // Elements in iframes are reached by selecting each frame, then back to the page
+        let id = |n: usize| format!("action-{}-{}", action.event_index, n);
+        let mut code = String::new();
+        for (n, frame) in action.frames.iter().enumerate() {
+            code.push_str(&self.command(&id(n + 1), "selectFrame", &side_locator(frame), &[], ""));
+        }
+        let n = action.frames.len() + 1;
+        code.push_str(&self.command(
+            &id(n),
+            command,
+            &side_locator(&action.selector),
+            &targets,
+            &value,
+        ));
+        if !action.frames.is_empty() {
+            code.push_str(&self.command(&id(n + 1), "selectFrame", "relative=top", &[], ""));
+        }
+        code
+    }
+
+    // Variables are stored from the parameters' defaults; the IDE has no environment or data
+    // files to read them from
+    fn wrap_parameters(
+        &self,
+        parameters: &[Parameter],
+        _source: ParamSource,
+        _initial_url: &str,
+        action_sequence: &str,
+    ) -> String {
+        let mut code: String = parameters
+            .iter()
+            .map(|parameter| {
+                let id = format!("store-{}", parameter.name);
+                self.command(&id, "store", &parameter.default_value, &[], &parameter.name)
+            })
+            .collect();
+        code.push_str(action_sequence);
+        code
+    }
+}
+
+impl SideEmitter {
+    // One command of the test, led by the comma that separates it from the previous one
+    fn command(
+        &self,
+        id: &str,
+        command: &str,
+        target: &str,
+        targets: &[(String, &str)],
+        value: &str,
+    ) -> String {
+        let targets: Vec<String> = targets
+            .iter()
+            .map(|(target, kind)| {
+                format!(
+                    "[{}, {}]",
+                    self.string_literal(target),
+                    self.string_literal(kind)
+                )
+            })
+            .collect();
+        let mut code = String::from(",\n        {\n");
+        code.push_str(&format!("          \"id\": {},\n", self.string_literal(id)));
+        code.push_str("          \"comment\": \"\",\n");
+        code.push_str(&format!(
+            "          \"command\": {},\n",
+            self.string_literal(command)
+        ));
+        code.push_str(&format!(
+            "          \"target\": {},\n",
+            self.string_literal(target)
+        ));
+        code.push_str(&format!(
+            "          \"targets\": [{}],\n",
+            targets.join(", ")
+        ));
+        code.push_str(&format!(
+            "          \"value\": {}\n",
+            self.string_literal(value)
+        ));
+        code.push_str("        }");
+        code
+    }
+}
+
+// The Selenium IDE locator for a CSS or XPath selector
+fn side_locator(selector: &str) -> String {
+    if is_xpath(selector) {
+        format!("xpath={}", selector)
+    } else {
+        format!("css={}", selector)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{
+        assert_golden, emit_action, emit_recording, replayed_steps, round_trip, shop_recording,
+    };
+    use crate::Recording;
+
+    fn project(commands: Value) -> SideProject {
+        serde_json::from_value(serde_json::json!({
+            "name": "Shop",
+            "url": "https://shop.test",
+            "tests": [{"id": "t1", "name": "checkout", "commands": commands}],
+        }))
+        .unwrap()
+    }
+
+    fn command(command: &str, target: &str, value: &str) -> Value {
+        serde_json::json!({"command": command, "target": target, "targets": [], "value": value})
+    }
+
+    #[test]
+    fn keeps_whether_an_option_was_selected_by_label_or_value() {
+        let project = project(serde_json::json!([
+            command("open", "/", ""),
+            command("select", "id=country", "label=Germany"),
+            command("select", "id=country", "value=de"),
+            command("select", "id=country", "Germany"),
+        ]));
+        let imported = import(&project, None, &mut Diagnostics::default()).unwrap();
+        let selects: Vec<(&str, bool)> = imported
+            .actions
+            .iter()
+            .map(|action| (action.value.as_deref().unwrap(), action.select_by_label))
+            .collect();
+        assert_eq!(
+            selects,
+            [("Germany", true), ("de", false), ("Germany", true)]
+        );
+
+        let exported: Vec<String> = imported
+            .actions
+            .iter()
+            .map(|action| emit_action(&SideEmitter, action).0)
+            .collect();
+        assert!(exported[0].contains("\"value\": \"label=Germany\""));
+        assert!(exported[1].contains("\"value\": \"value=de\""));
+    }
+
+    #[test]
+    fn writes_the_shop_checkout_as_selenium_ide_commands() {
+        let code = emit_recording(&SideEmitter, &shop_recording());
+        assert_golden("selenium-ide.json", &code);
+    }
+
+    #[test]
+    fn exports_imported_tests_as_the_same_commands() {
+        let project = serde_json::json!({
+            "name": "Shop",
+            "url": "https://shop.test",
+            "tests": [{"id": "t1", "name": "checkout", "commands": [
+                command("open", "/cart", ""),
+                command("setWindowSize", "1280x720", ""),
+                command("select", "id=country", "label=France"),
+                command("select", "css=#currency", "value=eur"),
+                command("check", "id=terms", ""),
+                command("selectFrame", "css=#card", ""),
+                command("type", "name=holder", "Jane \"J\" Doe"),
+                command("selectFrame", "relative=top", ""),
+                command("sendKeys", "id=coupon", "${KEY_ENTER}"),
+                command("click", "xpath=//button[@id='pay']", ""),
+            ]}],
+        });
+        let recording = Recording::from_json("checkout", &project.to_string()).unwrap();
+        let exported = round_trip(&recording, "selenium-ide");
+        let steps = replayed_steps(&recording);
+        assert_eq!(steps.len(), 6);
+        assert_eq!(replayed_steps(&exported), steps);
+    }
+}
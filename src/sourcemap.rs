@@ -0,0 +1,367 @@
+// Source map: which lines of the generated entry file came from which recorded events.
+//
+// Written into the project as `imitator-map.json`, so a failing line can be traced back to the
+// recording with `imitator explain <project> <line>`. Every action block starts with its
+// `Timestamp:` comment (`//` or `#`, depending on the target language); blocks are located
+// by those comments once the template has been rendered, since template packs and parameter
+// wrapping move and reindent them.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::diagnostics::CompileError;
+use crate::report::shown_attributes;
+use crate::{collect_text, display_selector, ActionWithSelector, Event, NodeInfo, Recording};
+
+pub const FILE: &str = "imitator-map.json";
+const VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceMap {
+    pub version: u32,
+    pub file: PathBuf,              // Entry file, relative to the project
+    pub recording: Option<PathBuf>, // Absolute path of the recording, when it was read from disk
+    pub first_timestamp: i64,
+    pub actions: Vec<MappedAction>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MappedAction {
+    pub index: usize,
+    pub start_line: usize, // 1-based and inclusive, like editors and stack traces
+    pub end_line: usize,
+    pub event_index: usize, // Last rrweb event that contributed to the action
+    pub timestamp: i64,
+    pub node_id: i64,
+    pub action: String,
+    pub target: String,
+    pub selector: String,
+}
+
+pub fn build(
+    file: &Path,
+    entry: &str,
+    action_sequence: &str,
+    actions: &[ActionWithSelector],
+    recording: &Recording,
+) -> SourceMap {
+    // Block lengths come from the emitted sequence, where a blank line ends every block
+    let mut block_lengths = Vec::new();
+    let mut current: Option<usize> = None;
+    for line in action_sequence.lines() {
+        let line = line.trim();
+        if timestamp_marker(line).is_some() {
+            block_lengths.extend(current.take());
+            current = Some(1);
+        } else if line.is_empty() {
+            block_lengths.extend(current.take());
+        } else if let Some(length) = current.as_mut() {
+            *length += 1;
+        }
+    }
+    block_lengths.extend(current);
+
+    let lines: Vec<&str> = entry.lines().collect();
+    let mut cursor = 0;
+    let mut mapped = Vec::new();
+    for (index, (action, length)) in actions.iter().zip(block_lengths).enumerate() {
+        let marker = action.timestamp.to_string();
+        let Some(start) = (cursor..lines.len())
+            .find(|&i| timestamp_marker(lines[i].trim()) == Some(marker.as_str()))
+        else {
+            continue; // The pack left the action sequence out of its entry file
+        };
+        cursor = start + length;
+        mapped.push(MappedAction {
+            index,
+            start_line: start + 1,
+            end_line: start + length,
+            event_index: action.event_index,
+            timestamp: action.timestamp,
+            node_id: action.rrweb_id,
+            action: format!("{:?}", action.action_type),
+            target: action.description.clone(),
+            selector: display_selector(action),
+        });
+    }
+
+    SourceMap {
+        version: VERSION,
+        file: file.to_path_buf(),
+        recording: recording.path.clone(),
+        first_timestamp: recording.events.first().map_or(0, |e| e.timestamp),
+        actions: mapped,
+    }
+}
+
+// The timestamp in an action block's opening comment
+fn timestamp_marker(line: &str) -> Option<&str> {
+    line.strip_prefix("//")
+        .or_else(|| line.strip_prefix('#'))?
+        .trim_start()
+        .strip_prefix("Timestamp: ")
+}
+
+impl SourceMap {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    // Read the map of a generated project
+    pub fn load(project_dir: &Path) -> Result<Self, CompileError> {
+        let path = project_dir.join(FILE);
+        let content = fs::read_to_string(&path).map_err(|source| CompileError::Io {
+            path: path.clone(),
+            source,
+        })?;
+        serde_json::from_str(&content).map_err(|e| {
+            CompileError::Config(format!(
+                "{} is not an imitator source map: {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+
+    // The action whose block contains `line` (1-based)
+    pub fn action_at(&self, line: usize) -> Option<&MappedAction> {
+        self.actions
+            .iter()
+            .find(|action| (action.start_line..=action.end_line).contains(&line))
+    }
+}
+
+// One-line summary of a recorded event. Input values are left out, as in compile output.
+pub fn describe_event(event: &Event) -> String {
+    let data = &event.data;
+    let number = |key: &str| data.get(key).and_then(Value::as_i64);
+    match event.event_type {
+        0 => "DOMContentLoaded".to_string(),
+        1 => "Load".to_string(),
+        2 => "Full snapshot".to_string(),
+        4 => format!(
+            "Meta {}",
+            data.get("href").and_then(Value::as_str).unwrap_or("")
+        ),
+        5 => format!(
+            "Custom {}",
+            data.get("tag").and_then(Value::as_str).unwrap_or("")
+        ),
+        6 => "Plugin".to_string(),
+        3 => match number("source") {
+            Some(0) => {
+                let count = |key: &str| data.get(key).and_then(Value::as_array).map_or(0, Vec::len);
+                format!(
+                    "Mutation: {} added, {} removed, {} attribute change(s), {} text change(s)",
+                    count("adds"),
+                    count("removes"),
+                    count("attributes"),
+                    count("texts")
+                )
+            }
+            Some(1) | Some(6) => "Mouse move".to_string(),
+            Some(2) => {
+                let interaction = match number("type") {
+                    Some(0) => "MouseUp",
+                    Some(1) => "MouseDown",
+                    Some(2) => "Click",
+                    Some(3) => "ContextMenu",
+                    Some(4) => "DblClick",
+                    Some(5) => "Focus",
+                    Some(6) => "Blur",
+                    Some(7) => "TouchStart",
+                    Some(9) => "TouchEnd",
+                    Some(10) => "TouchCancel",
+                    _ => "Interaction",
+                };
+                format!("{} on node {}", interaction, number("id").unwrap_or(-1))
+            }
+            Some(3) => format!("Scroll on node {}", number("id").unwrap_or(-1)),
+            Some(4) => format!(
+                "Viewport resize to {}x{}",
+                number("width").unwrap_or(0),
+                number("height").unwrap_or(0)
+            ),
+            Some(5) => format!(
+                "Input on node {} ({} characters)",
+                number("id").unwrap_or(-1),
+                data.get("text")
+                    .and_then(Value::as_str)
+                    .map_or(0, |text| text.chars().count())
+            ),
+            Some(source) => format!("Incremental event (source {})", source),
+            None => "Incremental event".to_string(),
+        },
+        other => format!("Event of type {}", other),
+    }
+}
+
+// The node's ancestors, siblings and children as indented lines, the node itself marked with `>`
+pub fn dom_neighbourhood(node_id: i64, dom_map: &HashMap<i64, NodeInfo>) -> Vec<String> {
+    const ANCESTORS: usize = 3;
+    const SIBLINGS: usize = 2;
+    const CHILDREN: usize = 5;
+
+    let Some(node) = dom_map.get(&node_id) else {
+        return Vec::new();
+    };
+    let mut ancestors = Vec::new();
+    let mut parent_id = node.parent_id;
+    while let Some(id) = parent_id {
+        if ancestors.len() == ANCESTORS {
+            break;
+        }
+        ancestors.push(id);
+        parent_id = dom_map.get(&id).and_then(|parent| parent.parent_id);
+    }
+    ancestors.reverse();
+
+    let mut lines = Vec::new();
+    for (depth, id) in ancestors.iter().enumerate() {
+        lines.push(format!(
+            "  {}{}",
+            "  ".repeat(depth),
+            render_node(*id, dom_map, false)
+        ));
+    }
+    let depth = ancestors.len();
+    let siblings: Vec<i64> = node
+        .parent_id
+        .and_then(|id| dom_map.get(&id))
+        .map(|parent| parent.child_ids.clone())
+        .unwrap_or_else(|| vec![node_id]);
+    let position = siblings.iter().position(|&id| id == node_id).unwrap_or(0);
+    let first = position.saturating_sub(SIBLINGS);
+    let last = (position + SIBLINGS).min(siblings.len().saturating_sub(1));
+    for &id in &siblings[first..=last] {
+        let marker = if id == node_id { ">" } else { " " };
+        lines.push(format!(
+            "{} {}{}",
+            marker,
+            "  ".repeat(depth),
+            render_node(id, dom_map, true)
+        ));
+        if id == node_id {
+            for &child in node.child_ids.iter().take(CHILDREN) {
+                lines.push(format!(
+                    "  {}{}",
+                    "  ".repeat(depth + 1),
+                    render_node(child, dom_map, true)
+                ));
+            }
+            if node.child_ids.len() > CHILDREN {
+                lines.push(format!(
+                    "  {}... {} more",
+                    "  ".repeat(depth + 1),
+                    node.child_ids.len() - CHILDREN
+                ));
+            }
+        }
+    }
+    lines
+}
+
+// Ancestors are rendered without text, which would repeat most of the page
+fn render_node(id: i64, dom_map: &HashMap<i64, NodeInfo>, with_text: bool) -> String {
+    let Some(node) = dom_map.get(&id) else {
+        return format!("(node {} missing)", id);
+    };
+    let Some(tag) = &node.tag_name else {
+        return match &node.text_content {
+            Some(text) => format!("{:?} (node {})", truncate(text.trim(), 40), id),
+            None => format!("#document (node {})", id),
+        };
+    };
+    let mut rendered = format!("<{}", tag);
+    for (key, value) in shown_attributes(node) {
+        rendered.push_str(&format!(" {}=\"{}\"", key, truncate(&value, 40)));
+    }
+    rendered.push('>');
+    let text = if with_text {
+        collect_text(id, dom_map)
+    } else {
+        String::new()
+    };
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if !text.is_empty() {
+        rendered.push_str(&format!(" {:?}", truncate(&text, 40)));
+    }
+    format!("{} (node {})", rendered, id)
+}
+
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        text.to_string()
+    } else {
+        format!("{}...", text.chars().take(max).collect::<String>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::params::{ParamMode, ParamSource};
+    use crate::testing::two_page_recording;
+    use crate::{compile, CompileOptions};
+
+    // The entry file and its source map for the two-page recording compiled for `target`
+    fn compiled(target: &str, options: CompileOptions) -> (String, SourceMap) {
+        let options = CompileOptions {
+            target: Some(target.to_string()),
+            ..options
+        };
+        let project = compile(&two_page_recording(), &options).unwrap();
+        let file = |path: &Path| {
+            let file = project.files.iter().find(|file| file.path == path).unwrap();
+            String::from_utf8_lossy(&file.contents).into_owned()
+        };
+        let source_map = serde_json::from_str(&file(Path::new(FILE))).unwrap();
+        (file(&project.entry), source_map)
+    }
+
+    // Every mapped block opens with its action's timestamp comment and ends right before
+    // a blank line
+    fn assert_blocks_match(entry: &str, source_map: &SourceMap) {
+        let lines: Vec<&str> = entry.lines().collect();
+        assert_eq!(source_map.actions.len(), 3, "{}", entry);
+        for action in &source_map.actions {
+            assert_eq!(
+                timestamp_marker(lines[action.start_line - 1].trim()),
+                Some(action.timestamp.to_string().as_str())
+            );
+            assert!(!lines[action.end_line - 1].trim().is_empty());
+            assert!(lines[action.end_line].trim().is_empty() || action.index == 2);
+            let middle = (action.start_line + action.end_line) / 2;
+            assert_eq!(source_map.action_at(middle).unwrap().index, action.index);
+        }
+        let first = &source_map.actions[0];
+        assert!(source_map.action_at(first.start_line - 1).is_none());
+    }
+
+    #[test]
+    fn maps_each_action_block_to_its_lines() {
+        for target in [
+            "stagehand",
+            "playwright-test",
+            "selenium-python",
+            "puppeteer",
+        ] {
+            let (entry, source_map) = compiled(target, CompileOptions::default());
+            assert_blocks_match(&entry, &source_map);
+            assert_eq!(source_map.actions[2].selector, "#pay");
+        }
+    }
+
+    #[test]
+    fn maps_blocks_that_parameters_reindented() {
+        let mut options = CompileOptions::default();
+        options.overrides.emit.params = Some(ParamMode::All);
+        options.overrides.emit.params_source = Some(ParamSource::Json);
+        let (entry, source_map) = compiled("stagehand", options);
+        assert!(entry.contains("for (const [rowIndex, params]"));
+        assert_blocks_match(&entry, &source_map);
+    }
+}
@@ -0,0 +1,127 @@
+// Stagehand emitter: the default target. Actions run in the quickstart's `main()`, each
+// wrapped in try/catch so one stale selector doesn't end the run; in act and hybrid modes
+// Stagehand's `page.act()` finds elements from natural-language instructions instead.
+
+use crate::diagnostics::Diagnostics;
+use crate::emitter::Emitter;
+use crate::playwright::{action_comment, locator_statements};
+use crate::templates::TemplateSource;
+use crate::{act_instruction, display_selector, ActionWithSelector, Emission, EmitOptions};
+
+pub struct StagehandEmitter;
+
+impl Emitter for StagehandEmitter {
+    fn name(&self) -> &str {
+        "stagehand"
+    }
+
+    fn label(&self) -> &str {
+        "Stagehand"
+    }
+
+    fn template(&self) -> TemplateSource {
+        TemplateSource::Builtin("stagehand")
+    }
+
+    fn run_command(&self) -> &str {
+        "npm install && npm run start"
+    }
+
+    fn supports_act(&self) -> bool {
+        true
+    }
+
+    fn prologue(&self, options: &EmitOptions, _diagnostics: &mut Diagnostics) -> String {
+        // Prepend standard cookie consent dismissal, unless the config turned it off
+        let Some(button_pattern) = options.cookie_banner else {
+            return String::new();
+        };
+        let mut code = String::from("  // Attempt to dismiss common cookie banners first\n");
+        // Use getByRole with a regex for common button texts and a short timeout.
+        // Add a .catch() to ignore errors if the button isn't found.
+        code.push_str(&format!("  await page.getByRole('button', {{ name: /{}/i }}).click({{ timeout: 5000 }}).catch(() => {{ console.log('Cookie banner not found or dismissed already within 5s.'); }});\n\n", button_pattern.replace('/', "\\/")));
+        code
+    }
+
+    fn action(
+        &self,
+        action: &ActionWithSelector,
+        _previous: Option<&ActionWithSelector>,
+        emission: Emission,
+        options: &EmitOptions,
+        diagnostics: &mut Diagnostics,
+    ) -> String {
+        let mut code = action_comment(action);
+        let act_call = act_instruction(action).unwrap_or_default();
+
+        if emission == Emission::Skipped {
+            // If selector generation failed, add a comment and skip the action command
+            let failed_tag = action
+                .selector
+                .split("::")
+                .nth(1)
+                .unwrap_or("unknown_element");
+            code.push_str(&format!(
+                "  // Action skipped: Could not generate stable selector for <{}> element.\n",
+                failed_tag
+            ));
+        } else if emission == Emission::Act {
+            // Let Stagehand find the element from a natural-language instruction
+            code.push_str("  try {\n");
+            code.push_str(&format!("    await page.act({});\n", act_call));
+            code.push_str("  } catch (error) {\n");
+            code.push_str(&format!(
+                "    console.warn({}, (error as Error).message);\n",
+                self.string_literal(&format!("act() failed for [{}]:", action.description))
+            ));
+            code.push_str("  }\n");
+        } else {
+            // If selector exists, generate the command wrapped in try...catch
+            code.push_str("  try {\n");
+            code.push_str(&locator_statements(
+                self,
+                action,
+                options.fixture_files,
+                diagnostics,
+            ));
+            code.push_str("  } catch (error) {\n");
+            code.push_str(&format!(
+                "    console.warn({}, (error as Error).message);\n",
+                self.string_literal(&format!(
+                    "Action failed for selector [{}]:",
+                    display_selector(action)
+                ))
+            ));
+            if emission == Emission::LocatorWithAct {
+                // Hybrid mode: the recorded selector went stale, ask Stagehand instead
+                code.push_str("    console.warn('Falling back to act()');\n");
+                code.push_str(&format!("    await page.act({});\n", act_call));
+            }
+            code.push_str("  }\n");
+        }
+        code
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{action, assert_golden, emit_action, emit_recording, shop_recording};
+    use crate::{ActionType, Control};
+
+    #[test]
+    fn quotes_selectors_in_code_and_log_messages_alike() {
+        let search = action(ActionType::Click, Control::Field, "input[name='q']", None);
+        let (code, _) = emit_action(&StagehandEmitter, &search);
+        assert!(code.contains("await page.locator(\"input[name='q']\").click();"));
+        assert!(code.contains(
+            "console.warn(\"Action failed for selector [input[name='q']]:\", (error as Error).message);"
+        ));
+    }
+
+    #[test]
+    fn writes_the_shop_checkout_as_a_stagehand_script() {
+        let code = emit_recording(&StagehandEmitter, &shop_recording());
+        assert_golden("stagehand.ts", &code);
+    }
+}
@@ -0,0 +1,489 @@
+// Minimal template engine for project templates.
+//
+// Syntax (chosen so it doesn't collide with TypeScript, JSON or Markdown):
+//   {{slot}}, {{viewport.width}}     insert a slot value (dotted paths reach into objects)
+//   {{slot|js}}                       insert it escaped for the inside of a string literal:
+//                                     `js` (double-quoted, single-quoted or template literal),
+//                                     `json` or `py`; the template keeps its own quotes
+//   {{#if slot}} .. {{else}} .. {{/if}}
+//   {{#each list}} .. {{/each}}       inside the loop, `{{this}}` is the item, `{{@index}}` its
+//                                     0-based position, and the item's fields are slots
+//   \{{                               a literal `{{`, e.g. for a TSX `style=\{{ color: "red" }}`
+//
+// Slot values come from a JSON object. Without a filter, values are inserted as-is.
+// A block tag alone on its line removes the whole line, so templates can lay out blocks
+// without leaving blank lines in the output.
+
+use serde_json::Value;
+use std::borrow::Cow;
+
+#[derive(Debug)]
+enum Node {
+    Text(String),
+    Slot(String, Option<Filter>),
+    If {
+        condition: String,
+        then_branch: Vec<Node>,
+        else_branch: Vec<Node>,
+    },
+    Each {
+        list: String,
+        body: Vec<Node>,
+    },
+}
+
+enum Tag {
+    Slot(String, Option<Filter>),
+    If(String),
+    Else,
+    EndIf,
+    Each(String),
+    EndEach,
+}
+
+// Escaping for a slot that sits inside a string literal
+#[derive(Debug, Clone, Copy)]
+enum Filter {
+    Js,
+    Json,
+    Py,
+}
+
+impl Filter {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "js" => Some(Filter::Js),
+            "json" => Some(Filter::Json),
+            "py" => Some(Filter::Py),
+            _ => None,
+        }
+    }
+
+    fn apply(self, text: &str) -> String {
+        match self {
+            Filter::Json => {
+                let literal = serde_json::to_string(text).unwrap_or_default();
+                literal[1..literal.len() - 1].to_string()
+            }
+            Filter::Js | Filter::Py => {
+                let mut escaped = String::with_capacity(text.len());
+                for c in text.chars() {
+                    match c {
+                        '\\' => escaped.push_str("\\\\"),
+                        '"' => escaped.push_str("\\\""),
+                        '\'' => escaped.push_str("\\'"),
+                        '\n' => escaped.push_str("\\n"),
+                        '\r' => escaped.push_str("\\r"),
+                        '\t' => escaped.push_str("\\t"),
+                        // Template literals end at a backtick and interpolate `${`
+                        '`' | '$' if matches!(self, Filter::Js) => {
+                            escaped.push('\\');
+                            escaped.push(c);
+                        }
+                        '\u{2028}' | '\u{2029}' if matches!(self, Filter::Js) => {
+                            escaped.push_str(&format!("\\u{:04x}", c as u32));
+                        }
+                        _ => escaped.push(c),
+                    }
+                }
+                escaped
+            }
+        }
+    }
+}
+
+// A parsed template, reusable across renders
+#[derive(Debug)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let tokens = tokenize(source)?;
+        let mut position = 0;
+        let (nodes, end) = parse_nodes(&tokens, &mut position)?;
+        match end {
+            None => Ok(Template { nodes }),
+            Some((tag, line)) => Err(format!("line {}: unexpected {{{{{}}}}}", line, tag)),
+        }
+    }
+
+    pub fn render(&self, slots: &Value) -> Result<String, String> {
+        let mut output = String::new();
+        let mut scopes = vec![Scope {
+            value: slots,
+            index: None,
+        }];
+        render_nodes(&self.nodes, &mut scopes, &mut output)?;
+        Ok(output)
+    }
+}
+
+// --- Tokenizer ---
+
+enum Token {
+    Text(String),
+    Tag(Tag, usize), // Tag and its 1-based line number
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let line_at = |offset: usize| source[..offset].matches('\n').count() + 1;
+    // Offset of the first byte not yet turned into a token
+    let mut consumed = 0;
+
+    while let Some(found) = source[consumed..].find("{{") {
+        let tag_start = consumed + found;
+        // An escaped `\{{` is text; the `}}` closing it is left alone as well
+        if source[consumed..tag_start].ends_with('\\') {
+            tokens.push(Token::Text(format!(
+                "{}{{{{",
+                &source[consumed..tag_start - 1]
+            )));
+            consumed = tag_start + 2;
+            continue;
+        }
+        let Some(length) = source[tag_start + 2..].find("}}") else {
+            return Err(format!("line {}: unclosed {{{{", line_at(tag_start)));
+        };
+        let mut tag_end = tag_start + 2 + length + 2;
+        let tag = parse_tag(source[tag_start + 2..tag_end - 2].trim())
+            .map_err(|e| format!("line {}: {}", line_at(tag_start), e))?;
+
+        // Standalone block tags take their whole line with them
+        let mut text_end = tag_start;
+        if !matches!(tag, Tag::Slot(..)) {
+            let line_start = source[..tag_start].rfind('\n').map_or(0, |i| i + 1);
+            let line_end = source[tag_end..]
+                .find('\n')
+                .map_or(source.len(), |i| tag_end + i);
+            if line_start >= consumed
+                && source[line_start..tag_start].trim().is_empty()
+                && source[tag_end..line_end].trim().is_empty()
+            {
+                text_end = line_start;
+                tag_end = (line_end + 1).min(source.len());
+            }
+        }
+
+        if text_end > consumed {
+            tokens.push(Token::Text(source[consumed..text_end].to_string()));
+        }
+        tokens.push(Token::Tag(tag, line_at(tag_start)));
+        consumed = tag_end;
+    }
+    if consumed < source.len() {
+        tokens.push(Token::Text(source[consumed..].to_string()));
+    }
+    Ok(tokens)
+}
+
+fn parse_tag(inner: &str) -> Result<Tag, String> {
+    let (keyword, argument) = match inner.split_once(char::is_whitespace) {
+        Some((keyword, argument)) => (keyword, argument.trim()),
+        None => (inner, ""),
+    };
+    let require_argument = |tag: fn(String) -> Tag| {
+        if argument.is_empty() || argument.contains(char::is_whitespace) {
+            Err(format!("{{{{{}}}}} takes exactly one slot name", keyword))
+        } else {
+            Ok(tag(argument.to_string()))
+        }
+    };
+    match keyword {
+        "#if" => require_argument(Tag::If),
+        "#each" => require_argument(Tag::Each),
+        "else" if argument.is_empty() => Ok(Tag::Else),
+        "/if" if argument.is_empty() => Ok(Tag::EndIf),
+        "/each" if argument.is_empty() => Ok(Tag::EndEach),
+        _ if keyword.starts_with(['#', '/']) => Err(format!("unknown block tag {{{{{}}}}}", inner)),
+        _ if !inner.is_empty() && !inner.contains(char::is_whitespace) => {
+            match inner.split_once('|') {
+                Some((path, name)) => match Filter::parse(name) {
+                    Some(filter) if !path.is_empty() => {
+                        Ok(Tag::Slot(path.to_string(), Some(filter)))
+                    }
+                    Some(_) => Err(format!("invalid slot name {{{{{}}}}}", inner)),
+                    None => Err(format!(
+                        "unknown filter '{}' in {{{{{}}}}}; expected js, json or py",
+                        name, inner
+                    )),
+                },
+                None => Ok(Tag::Slot(inner.to_string(), None)),
+            }
+        }
+        _ => Err(format!("invalid slot name {{{{{}}}}}", inner)),
+    }
+}
+
+// --- Parser ---
+
+// Closing or else tag that ended a run of nodes, with its line number
+type BlockEnd = Option<(&'static str, usize)>;
+
+// Parse nodes until the end of input or a closing/else tag, which is returned to the caller
+fn parse_nodes(tokens: &[Token], position: &mut usize) -> Result<(Vec<Node>, BlockEnd), String> {
+    let mut nodes = Vec::new();
+    while let Some(token) = tokens.get(*position) {
+        *position += 1;
+        let (tag, line) = match token {
+            Token::Text(text) => {
+                nodes.push(Node::Text(text.clone()));
+                continue;
+            }
+            Token::Tag(tag, line) => (tag, *line),
+        };
+        match tag {
+            Tag::Slot(name, filter) => nodes.push(Node::Slot(name.clone(), *filter)),
+            Tag::If(condition) => {
+                let (then_branch, end) = parse_nodes(tokens, position)?;
+                let else_branch = match end {
+                    Some(("else", _)) => match parse_nodes(tokens, position)? {
+                        (else_branch, Some(("/if", _))) => else_branch,
+                        _ => return Err(format!("line {}: {{{{#if}}}} is never closed", line)),
+                    },
+                    Some(("/if", _)) => Vec::new(),
+                    _ => return Err(format!("line {}: {{{{#if}}}} is never closed", line)),
+                };
+                nodes.push(Node::If {
+                    condition: condition.clone(),
+                    then_branch,
+                    else_branch,
+                });
+            }
+            Tag::Each(list) => match parse_nodes(tokens, position)? {
+                (body, Some(("/each", _))) => nodes.push(Node::Each {
+                    list: list.clone(),
+                    body,
+                }),
+                _ => return Err(format!("line {}: {{{{#each}}}} is never closed", line)),
+            },
+            Tag::Else => return Ok((nodes, Some(("else", line)))),
+            Tag::EndIf => return Ok((nodes, Some(("/if", line)))),
+            Tag::EndEach => return Ok((nodes, Some(("/each", line)))),
+        }
+    }
+    Ok((nodes, None))
+}
+
+// --- Renderer ---
+
+struct Scope<'a> {
+    value: &'a Value,
+    index: Option<usize>,
+}
+
+fn render_nodes<'a>(
+    nodes: &[Node],
+    scopes: &mut Vec<Scope<'a>>,
+    output: &mut String,
+) -> Result<(), String> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => output.push_str(text),
+            Node::Slot(path, filter) => match lookup(scopes, path).as_deref() {
+                Some(Value::String(text)) => match filter {
+                    Some(filter) => output.push_str(&filter.apply(text)),
+                    None => output.push_str(text),
+                },
+                Some(Value::Number(number)) => output.push_str(&number.to_string()),
+                Some(Value::Bool(flag)) => output.push_str(&flag.to_string()),
+                Some(Value::Array(_) | Value::Object(_)) => {
+                    return Err(format!(
+                        "slot '{}' is a list or object; use {{{{#each}}}} or a dotted path",
+                        path
+                    ))
+                }
+                Some(Value::Null) | None => return Err(format!("unknown slot '{}'", path)),
+            },
+            Node::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                // Missing slots are falsy so templates can test for optional data
+                let branch = if lookup(scopes, condition).is_some_and(|v| is_truthy(&v)) {
+                    then_branch
+                } else {
+                    else_branch
+                };
+                render_nodes(branch, scopes, output)?;
+            }
+            Node::Each { list, body } => {
+                let items: &[Value] = match lookup(scopes, list) {
+                    Some(Cow::Borrowed(Value::Array(items))) => items,
+                    Some(Cow::Borrowed(Value::Null)) | None => &[],
+                    Some(_) => return Err(format!("slot '{}' is not a list", list)),
+                };
+                for (index, item) in items.iter().enumerate() {
+                    scopes.push(Scope {
+                        value: item,
+                        index: Some(index),
+                    });
+                    let result = render_nodes(body, scopes, output);
+                    scopes.pop();
+                    result?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+// Resolve a slot path against the innermost scope that has its first segment
+fn lookup<'a>(scopes: &[Scope<'a>], path: &str) -> Option<Cow<'a, Value>> {
+    let innermost = scopes.last()?;
+    if path == "this" {
+        return Some(Cow::Borrowed(innermost.value));
+    }
+    if path == "@index" {
+        return innermost.index.map(|index| Cow::Owned(Value::from(index)));
+    }
+    let mut segments = path.split('.');
+    let first = segments.next()?;
+    let mut value = scopes
+        .iter()
+        .rev()
+        .find_map(|scope| scope.value.get(first))?;
+    for segment in segments {
+        value = value.get(segment)?;
+    }
+    Some(Cow::Borrowed(value))
+}
+
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(flag) => *flag,
+        Value::Number(number) => number.as_f64().is_some_and(|n| n != 0.0),
+        Value::String(text) => !text.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(fields) => !fields.is_empty(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn render(source: &str, slots: Value) -> Result<String, String> {
+        Template::parse(source)?.render(&slots)
+    }
+
+    #[test]
+    fn inserts_slots_and_dotted_paths() {
+        let slots = json!({"name": "checkout", "viewport": {"width": 1280}, "ok": true});
+        assert_eq!(
+            render("{{name}} {{viewport.width}} {{ok}}", slots).unwrap(),
+            "checkout 1280 true"
+        );
+    }
+
+    #[test]
+    fn keeps_escaped_braces_as_text() {
+        let slots = json!({"color": "red"});
+        assert_eq!(
+            render(
+                "<div style=\\{{ color: \"{{color}}\" }}>\\{{x}}</div>",
+                slots
+            )
+            .unwrap(),
+            "<div style={{ color: \"red\" }}>{{x}}</div>"
+        );
+        assert!(render("<div style={{ color: 'red' }}>", json!({})).is_err());
+    }
+
+    #[test]
+    fn renders_conditionals_and_loops() {
+        let slots = json!({"items": ["a", "b"], "empty": [], "people": [{"name": "Jo"}]});
+        assert_eq!(
+            render("{{#if items}}yes{{else}}no{{/if}}", slots.clone()).unwrap(),
+            "yes"
+        );
+        assert_eq!(
+            render("{{#if empty}}yes{{else}}no{{/if}}", slots.clone()).unwrap(),
+            "no"
+        );
+        assert_eq!(
+            render("{{#if missing}}yes{{/if}}", slots.clone()).unwrap(),
+            ""
+        );
+        assert_eq!(
+            render(
+                "{{#each items}}{{@index}}={{this}};{{/each}}",
+                slots.clone()
+            )
+            .unwrap(),
+            "0=a;1=b;"
+        );
+        assert_eq!(
+            render("{{#each people}}{{name}}{{/each}}", slots).unwrap(),
+            "Jo"
+        );
+    }
+
+    #[test]
+    fn drops_lines_holding_only_a_block_tag() {
+        let source = "start\n  {{#each items}}\n- {{this}}\n  {{/each}}\nend\n";
+        assert_eq!(
+            render(source, json!({"items": ["a", "b"]})).unwrap(),
+            "start\n- a\n- b\nend\n"
+        );
+    }
+
+    #[test]
+    fn reports_template_errors_with_lines() {
+        assert_eq!(
+            Template::parse("a\n{{#if x}}").unwrap_err(),
+            "line 2: {{#if}} is never closed"
+        );
+        assert_eq!(
+            Template::parse("{{/each}}").unwrap_err(),
+            "line 1: unexpected {{/each}}"
+        );
+        assert!(Template::parse("{{name").is_err());
+        assert!(render("{{missing}}", json!({})).is_err());
+        assert!(render("{{list}}", json!({"list": []})).is_err());
+    }
+
+    #[test]
+    fn escapes_filtered_slots_for_string_literals() {
+        let slots = json!({"name": "a \"b\" \\ `c` ${d} 'e'\nf"});
+        assert_eq!(
+            render("\"{{name|json}}\"", slots.clone()).unwrap(),
+            r#""a \"b\" \\ `c` ${d} 'e'\nf""#
+        );
+        assert_eq!(
+            render("`{{name|js}}`", slots.clone()).unwrap(),
+            r#"`a \"b\" \\ \`c\` \${d} \'e\'\nf`"#
+        );
+        assert_eq!(
+            render("\"{{name|py}}\"", slots.clone()).unwrap(),
+            r#""a \"b\" \\ `c` ${d} \'e\'\nf""#
+        );
+        // Unfiltered slots are left alone
+        assert_eq!(
+            render("{{name}}", slots).unwrap(),
+            "a \"b\" \\ `c` ${d} 'e'\nf"
+        );
+    }
+
+    #[test]
+    fn escaped_json_slots_parse_back() {
+        let name = "quote\" back\\slash\ttab";
+        let rendered = render("{\"title\": \"{{name|json}}\"}", json!({ "name": name })).unwrap();
+        let parsed: Value = serde_json::from_str(&rendered).unwrap();
+        assert_eq!(parsed["title"], name);
+    }
+
+    #[test]
+    fn rejects_unknown_filters() {
+        assert_eq!(
+            Template::parse("{{name|html}}").unwrap_err(),
+            "line 1: unknown filter 'html' in {{name|html}}; expected js, json or py"
+        );
+        assert!(Template::parse("{{|js}}").is_err());
+    }
+}
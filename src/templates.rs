@@ -0,0 +1,656 @@
+// Template packs: the project skeleton a recording is compiled into.
+//
+// A pack is a directory or a `.zip`/`.tar.gz` archive with an optional `template.json`
+// manifest at its root. One pack per target is embedded in the binary at build time, so an
+// installed binary doesn't need the source tree: `stagehand` (the Stagehand quickstart),
+// `playwright-test`, `cypress`, `selenium-python`, `puppeteer`, `chrome-recorder`,
+// `selenium-ide` and `gherkin` (see `BUILTIN_PACKS`).
+//
+// Manifest fields (all optional):
+//   name            display name used in progress output
+//   entry           file receiving the action sequence, printed by `--dry-run` (default index.ts)
+//   files           globs of files to include; empty means every file
+//   skip            globs of files or directories to leave out (default: node_modules,
+//                   downloads, cache.json and dotfiles at the root)
+//   render          globs of files passed through the template engine (the entry always is)
+//   required_slots  slots the compiler must provide, checked before anything is rendered
+//   post_process    rules applied to the generated project, in order:
+//                     { "type": "replace", "files": ["*.ts"], "from": "..", "to": ".." }
+//                     { "type": "command", "command": ["npx", "prettier", "--write", "."],
+//                       "optional": true }
+//
+// Globs match paths relative to the pack root: `*` and `?` stay within one path segment and
+// `**` matches any number of segments. A skipped directory skips everything below it.
+// Commands run in the generated project directory, so only use packs you trust.
+
+use flate2::read::GzDecoder;
+use include_dir::{include_dir, Dir};
+use serde::Deserialize;
+use serde_json::Value;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+
+use crate::diagnostics::{Code, CompileError, Diagnostic, Diagnostics};
+use crate::logging::{info, Logger};
+use crate::template_engine::Template;
+
+static STAGEHAND_PACK: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/templates/initial_state");
+static PLAYWRIGHT_TEST_PACK: Dir<'_> =
+    include_dir!("$CARGO_MANIFEST_DIR/templates/playwright_test");
+static CYPRESS_PACK: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/templates/cypress");
+static PUPPETEER_PACK: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/templates/puppeteer");
+static CHROME_RECORDER_PACK: Dir<'_> =
+    include_dir!("$CARGO_MANIFEST_DIR/templates/chrome_recorder");
+static SELENIUM_IDE_PACK: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/templates/selenium_ide");
+static GHERKIN_PACK: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/templates/gherkin");
+static SELENIUM_PYTHON_PACK: Dir<'_> =
+    include_dir!("$CARGO_MANIFEST_DIR/templates/selenium_python");
+
+pub const MANIFEST_FILE: &str = "template.json";
+
+// Packs built into the binary, selectable by name
+pub const BUILTIN_PACKS: &[&str] = &[
+    "stagehand",
+    "playwright-test",
+    "cypress",
+    "selenium-python",
+    "puppeteer",
+    "chrome-recorder",
+    "selenium-ide",
+    "gherkin",
+];
+
+const DEFAULT_SKIP: &[&str] = &["node_modules", "downloads", "cache.json", ".*"];
+
+#[derive(Debug, Clone)]
+pub enum TemplateSource {
+    Builtin(&'static str), // One of BUILTIN_PACKS
+    Directory(PathBuf),
+    Archive(PathBuf),
+}
+
+impl Default for TemplateSource {
+    fn default() -> Self {
+        TemplateSource::Builtin(BUILTIN_PACKS[0])
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TemplateManifest {
+    pub name: Option<String>,
+    pub entry: String,
+    pub files: Vec<String>,
+    pub skip: Vec<String>,
+    pub render: Vec<String>,
+    pub required_slots: Vec<String>,
+    pub post_process: Vec<PostProcessRule>,
+}
+
+impl Default for TemplateManifest {
+    fn default() -> Self {
+        TemplateManifest {
+            name: None,
+            entry: "index.ts".to_string(),
+            files: Vec::new(),
+            skip: DEFAULT_SKIP.iter().map(|s| s.to_string()).collect(),
+            render: Vec::new(),
+            required_slots: vec!["start_url".to_string(), "action_sequence".to_string()],
+            post_process: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum PostProcessRule {
+    // Literal text replacement in matching files, applied before anything is written
+    Replace {
+        files: Vec<String>,
+        from: String,
+        to: String,
+    },
+    // External command run in the project directory after it is written
+    Command {
+        command: Vec<String>,
+        #[serde(default)]
+        optional: bool, // Failures are warnings instead of errors
+    },
+}
+
+// One file of a template, with its path relative to the template root
+#[derive(Clone)]
+pub struct TemplateFile {
+    pub path: PathBuf,
+    pub contents: Vec<u8>,
+}
+
+// A loaded pack: its manifest and every file in it, before skip rules are applied
+pub struct TemplatePack {
+    pub description: String,
+    pub manifest: TemplateManifest,
+    files: Vec<TemplateFile>,
+}
+
+impl TemplateSource {
+    // Resolve a `--template` value: a built-in pack name, a directory or an archive
+    pub fn from_arg(value: &str) -> Result<Self, String> {
+        let path = PathBuf::from(value);
+        if path.is_dir() {
+            return Ok(TemplateSource::Directory(path));
+        }
+        if path.is_file() {
+            return if is_archive(&path) {
+                Ok(TemplateSource::Archive(path))
+            } else {
+                Err(format!(
+                    "Template pack {:?} is not a directory, .zip or .tar.gz archive",
+                    path
+                ))
+            };
+        }
+        if let Some(name) = BUILTIN_PACKS.iter().find(|name| **name == value) {
+            return Ok(TemplateSource::Builtin(name));
+        }
+        Err(format!(
+            "Template pack '{}' not found; expected a directory, an archive or one of: {}",
+            value,
+            BUILTIN_PACKS.join(", ")
+        ))
+    }
+
+    pub fn load(&self) -> Result<TemplatePack, CompileError> {
+        let (description, mut files) = match self {
+            TemplateSource::Builtin(name) => {
+                let dir = match *name {
+                    "playwright-test" => &PLAYWRIGHT_TEST_PACK,
+                    "cypress" => &CYPRESS_PACK,
+                    "selenium-python" => &SELENIUM_PYTHON_PACK,
+                    "puppeteer" => &PUPPETEER_PACK,
+                    "chrome-recorder" => &CHROME_RECORDER_PACK,
+                    "selenium-ide" => &SELENIUM_IDE_PACK,
+                    "gherkin" => &GHERKIN_PACK,
+                    _ => &STAGEHAND_PACK,
+                };
+                let mut files = Vec::new();
+                collect_embedded(dir, &mut files);
+                (format!("built-in {} pack", name), files)
+            }
+            TemplateSource::Directory(dir) => {
+                // node_modules can be huge, so skipped directories are pruned while walking
+                let manifest = match fs::read_to_string(dir.join(MANIFEST_FILE)) {
+                    Ok(content) => parse_manifest(&content)?,
+                    Err(_) => TemplateManifest::default(),
+                };
+                let mut files = Vec::new();
+                collect_directory(dir, dir, &manifest.skip, &mut files)?;
+                (format!("{:?}", dir), files)
+            }
+            TemplateSource::Archive(path) => (format!("{:?}", path), read_archive(path)?),
+        };
+
+        let manifest = match files
+            .iter()
+            .position(|file| file.path == Path::new(MANIFEST_FILE))
+        {
+            Some(index) => parse_manifest(&String::from_utf8_lossy(&files.remove(index).contents))?,
+            None => TemplateManifest::default(),
+        };
+        let description = match &manifest.name {
+            Some(name) => format!("'{}' ({})", name, description),
+            None => description,
+        };
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(TemplatePack {
+            description,
+            manifest,
+            files,
+        })
+    }
+}
+
+impl TemplatePack {
+    // Read any pack file as text, including ones the skip rules leave out of the project
+    pub fn read_to_string(&self, relative_path: &str) -> Option<String> {
+        self.files
+            .iter()
+            .find(|file| file.path == Path::new(relative_path))
+            .map(|file| String::from_utf8_lossy(&file.contents).into_owned())
+    }
+
+    // Files to write into the generated project, rendered and post-processed
+    pub fn project_files(&self, slots: &Value) -> Result<Vec<TemplateFile>, CompileError> {
+        let manifest = &self.manifest;
+        let mut files: Vec<TemplateFile> = self
+            .files
+            .iter()
+            .filter(|file| !is_skipped(&file.path, &manifest.skip))
+            .filter(|file| manifest.files.is_empty() || matches_any(&manifest.files, &file.path))
+            .cloned()
+            .collect();
+        if !files
+            .iter()
+            .any(|file| file.path == Path::new(&manifest.entry))
+        {
+            return Err(CompileError::Config(format!(
+                "Template entry file '{}' is missing or skipped",
+                manifest.entry
+            )));
+        }
+
+        render_files(&mut files, manifest, slots)?;
+
+        for rule in &manifest.post_process {
+            if let PostProcessRule::Replace {
+                files: patterns,
+                from,
+                to,
+            } = rule
+            {
+                for file in files.iter_mut() {
+                    if matches_any(patterns, &file.path) {
+                        let text = String::from_utf8_lossy(&file.contents).replace(from, to);
+                        file.contents = text.into_bytes();
+                    }
+                }
+            }
+        }
+        Ok(files)
+    }
+
+    // Run the manifest's commands in the written project directory
+    pub fn run_commands(
+        &self,
+        project_dir: &Path,
+        diagnostics: &mut Diagnostics,
+        logger: &Logger,
+    ) -> Result<(), CompileError> {
+        for rule in &self.manifest.post_process {
+            let PostProcessRule::Command { command, optional } = rule else {
+                continue;
+            };
+            let Some((program, args)) = command.split_first() else {
+                continue;
+            };
+            info!(
+                logger,
+                "  Running post-processing command: {}",
+                command.join(" ")
+            );
+            let failure = match Command::new(program)
+                .args(args)
+                .current_dir(project_dir)
+                .status()
+            {
+                Ok(status) if status.success() => None,
+                Ok(status) => Some(format!("exited with {}", status)),
+                Err(e) => Some(e.to_string()),
+            };
+            match failure {
+                None => {}
+                Some(reason) if *optional => diagnostics.push(
+                    Diagnostic::warning(
+                        Code::PostProcessFailed,
+                        format!("Post-processing command '{}' failed: {}", program, reason),
+                    )
+                    .with_hint(
+                        "The project was written; run the command yourself once it is fixed",
+                    ),
+                ),
+                Some(reason) => {
+                    return Err(CompileError::Template(format!(
+                        "Post-processing command '{}' failed: {}",
+                        program, reason
+                    )))
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_manifest(content: &str) -> Result<TemplateManifest, CompileError> {
+    serde_json::from_str(content).map_err(|e| {
+        CompileError::Config(format!(
+            "Invalid template manifest {}: {}",
+            MANIFEST_FILE, e
+        ))
+    })
+}
+
+fn io_error(path: &Path) -> impl FnOnce(io::Error) -> CompileError + '_ {
+    move |source| CompileError::Io {
+        path: path.to_path_buf(),
+        source,
+    }
+}
+
+// Render the entry and `render` files in place, failing on missing slots before anything is written
+fn render_files(
+    files: &mut [TemplateFile],
+    manifest: &TemplateManifest,
+    slots: &Value,
+) -> Result<(), CompileError> {
+    let provided = |name: &str| slots.get(name).is_some_and(|value| !value.is_null());
+    let missing: Vec<&str> = manifest
+        .required_slots
+        .iter()
+        .map(String::as_str)
+        .filter(|name| !provided(name))
+        .collect();
+    if !missing.is_empty() {
+        return Err(CompileError::Config(format!(
+            "Template requires slot(s) this compiler does not provide: {}",
+            missing.join(", ")
+        )));
+    }
+
+    for pattern in &manifest.render {
+        if !files.iter().any(|file| glob_match(pattern, &file.path)) {
+            return Err(CompileError::Config(format!(
+                "Template manifest renders '{}', which matches no file in the pack",
+                pattern
+            )));
+        }
+    }
+
+    for file in files.iter_mut() {
+        if file.path != Path::new(&manifest.entry) && !matches_any(&manifest.render, &file.path) {
+            continue;
+        }
+        let path = file.path.display().to_string();
+        let invalid = |e: &dyn std::fmt::Display| CompileError::Config(format!("{}: {}", path, e));
+        let source = String::from_utf8(std::mem::take(&mut file.contents))
+            .map_err(|_| invalid(&"template file is not valid UTF-8"))?;
+        let template =
+            Template::parse(&upgrade_legacy_placeholders(&source)).map_err(|e| invalid(&e))?;
+        let rendered = template.render(slots).map_err(|e| invalid(&e))?;
+        file.contents = rendered.into_bytes();
+    }
+    Ok(())
+}
+
+// Templates written before the template engine used two fixed markers
+fn upgrade_legacy_placeholders(source: &str) -> String {
+    source
+        .replace("__START_URL__", "{{start_url}}")
+        .replace("// __ACTION_SEQUENCE__", "{{action_sequence}}")
+}
+
+// Write template files under the project directory, creating subdirectories as needed
+pub fn write_files(files: &[TemplateFile], output_dir: &Path) -> Result<(), CompileError> {
+    for file in files {
+        let dest_path = output_dir.join(&file.path);
+        let parent = dest_path.parent().unwrap_or(output_dir);
+        fs::create_dir_all(parent)
+            .and_then(|_| fs::write(&dest_path, &file.contents))
+            .map_err(|source| CompileError::Io {
+                path: dest_path.clone(),
+                source,
+            })?;
+    }
+    Ok(())
+}
+
+// --- Loading ---
+
+fn collect_embedded(dir: &Dir<'_>, files: &mut Vec<TemplateFile>) {
+    for file in dir.files() {
+        files.push(TemplateFile {
+            path: file.path().to_path_buf(),
+            contents: file.contents().to_vec(),
+        });
+    }
+    for sub_dir in dir.dirs() {
+        collect_embedded(sub_dir, files);
+    }
+}
+
+fn collect_directory(
+    root: &Path,
+    dir: &Path,
+    skip: &[String],
+    files: &mut Vec<TemplateFile>,
+) -> Result<(), CompileError> {
+    for entry in fs::read_dir(dir).map_err(io_error(dir))? {
+        let path = entry.map_err(io_error(dir))?.path();
+        // read_dir yields paths below `dir`, which is below `root`
+        let relative_path = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+        if path.is_dir() {
+            if !is_skipped(&relative_path, skip) {
+                collect_directory(root, &path, skip, files)?;
+            }
+        } else {
+            // Skipped files are still loaded so the compiler can read them (e.g. .env.example)
+            let contents = fs::read(&path).map_err(io_error(&path))?;
+            files.push(TemplateFile {
+                path: relative_path,
+                contents,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn is_archive(path: &Path) -> bool {
+    let name = path.to_string_lossy().to_ascii_lowercase();
+    name.ends_with(".zip") || name.ends_with(".tar.gz") || name.ends_with(".tgz")
+}
+
+fn read_archive(path: &Path) -> Result<Vec<TemplateFile>, CompileError> {
+    let file = File::open(path).map_err(io_error(path))?;
+    let mut files = Vec::new();
+    let unsafe_path = |entry_path: &dyn std::fmt::Debug| {
+        CompileError::Config(format!(
+            "Unsafe path in template pack {:?}: {:?}",
+            path, entry_path
+        ))
+    };
+
+    if path
+        .to_string_lossy()
+        .to_ascii_lowercase()
+        .ends_with(".zip")
+    {
+        let zip_error = |e: zip::result::ZipError| match e {
+            zip::result::ZipError::Io(source) => io_error(path)(source),
+            e => CompileError::Config(format!(
+                "Template pack {:?} is not a valid zip archive: {}",
+                path, e
+            )),
+        };
+        let mut archive = zip::ZipArchive::new(file).map_err(zip_error)?;
+        for index in 0..archive.len() {
+            let mut entry = archive.by_index(index).map_err(zip_error)?;
+            if entry.is_dir() {
+                continue;
+            }
+            let entry_path = entry
+                .enclosed_name()
+                .ok_or_else(|| unsafe_path(&entry.name()))?;
+            let mut contents = Vec::new();
+            entry.read_to_end(&mut contents).map_err(io_error(path))?;
+            files.push(TemplateFile {
+                path: entry_path,
+                contents,
+            });
+        }
+    } else {
+        let mut archive = tar::Archive::new(GzDecoder::new(file));
+        for entry in archive.entries().map_err(io_error(path))? {
+            let mut entry = entry.map_err(io_error(path))?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let entry_path = entry.path().map_err(io_error(path))?.into_owned();
+            if !entry_path
+                .components()
+                .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+            {
+                return Err(unsafe_path(&entry_path));
+            }
+            let mut contents = Vec::new();
+            entry.read_to_end(&mut contents).map_err(io_error(path))?;
+            files.push(TemplateFile {
+                path: entry_path.components().collect(),
+                contents,
+            });
+        }
+    }
+
+    // Archives made with `zip -r pack.zip my-pack/` nest everything in one directory
+    let manifest_at_root = files
+        .iter()
+        .any(|file| file.path == Path::new(MANIFEST_FILE));
+    let single_top_level = files
+        .first()
+        .and_then(|file| file.path.components().next())
+        .filter(|first| {
+            files.iter().all(|file| {
+                file.path.components().count() > 1 && file.path.components().next() == Some(*first)
+            })
+        })
+        .map(|first| PathBuf::from(first.as_os_str()));
+    if let (false, Some(prefix)) = (manifest_at_root, single_top_level) {
+        for file in files.iter_mut() {
+            // Every path starts with the prefix, as checked above
+            file.path = file
+                .path
+                .strip_prefix(&prefix)
+                .unwrap_or(&file.path)
+                .to_path_buf();
+        }
+    }
+    Ok(files)
+}
+
+// --- Globs ---
+
+// A path is skipped when it, or any directory above it, matches a skip pattern
+fn is_skipped(relative_path: &Path, skip: &[String]) -> bool {
+    relative_path
+        .ancestors()
+        .filter(|ancestor| !ancestor.as_os_str().is_empty())
+        .any(|ancestor| matches_any(skip, ancestor))
+}
+
+fn matches_any(patterns: &[String], path: &Path) -> bool {
+    patterns.iter().any(|pattern| glob_match(pattern, path))
+}
+
+fn glob_match(pattern: &str, path: &Path) -> bool {
+    let pattern_segments: Vec<&str> = pattern.split('/').filter(|s| !s.is_empty()).collect();
+    let path_segments: Vec<String> = path
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    let path_segments: Vec<&str> = path_segments.iter().map(String::as_str).collect();
+    match_segments(&pattern_segments, &path_segments)
+}
+
+fn match_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| match_segments(rest, &path[skip..])),
+        Some((segment, rest)) => {
+            !path.is_empty() && wildcard_match(segment, path[0]) && match_segments(rest, &path[1..])
+        }
+    }
+}
+
+// `*` matches any run of characters and `?` a single one. Also used for config patterns.
+// Runs in O(pattern * name): on a mismatch only the most recent `*` is retried, one character
+// further along the name, since earlier stars can't help once a later one has matched.
+pub fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Position of the last `*` seen, and the name position it currently swallows up to
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    star = Some((star_p, star_n + 1));
+                    p = star_p + 1;
+                    n = star_n + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_wildcards_against_whole_names() {
+        assert!(wildcard_match("*.shop.test", "www.shop.test"));
+        assert!(!wildcard_match("*.shop.test", "shop.test.evil.test"));
+        assert!(wildcard_match("shop.tes?", "shop.test"));
+        assert!(!wildcard_match("shop.tes?", "shop.tests"));
+        assert!(wildcard_match("*", ""));
+        assert!(wildcard_match("a*b*c", "a-b-b-c"));
+        assert!(!wildcard_match("a*b*c", "a-c-b"));
+        assert!(wildcard_match("café*", "café.test"));
+    }
+
+    #[test]
+    fn matches_stars_without_backtracking_blowup() {
+        assert!(wildcard_match("*", ""));
+        assert!(wildcard_match("**a*b", "xxaxxb"));
+        assert!(!wildcard_match("a*b", "a"));
+        assert!(wildcard_match("*.test*", "shop.test.test"));
+        // Recursive backtracking takes exponential time on this
+        let name = "a".repeat(200);
+        assert!(!wildcard_match(&format!("{}b", "a*".repeat(30)), &name));
+    }
+
+    #[test]
+    fn skips_paths_under_a_matching_directory() {
+        let skip = ["node_modules".to_string(), "**/*.log".to_string()];
+        assert!(is_skipped(Path::new("node_modules/pkg/index.js"), &skip));
+        assert!(is_skipped(Path::new("logs/today/run.log"), &skip));
+        assert!(!is_skipped(Path::new("src/node_modules.ts"), &skip));
+    }
+
+    #[test]
+    fn reports_unreadable_packs_as_io_errors() {
+        use std::error::Error;
+
+        let dir =
+            std::env::temp_dir().join(format!("imitator-missing-pack-{}", std::process::id()));
+        let error = TemplateSource::Directory(dir.clone()).load().err().unwrap();
+        assert!(matches!(&error, CompileError::Io { path, .. } if *path == dir));
+        assert!(error.source().is_some());
+    }
+
+    #[test]
+    fn reports_invalid_packs_as_config_errors() {
+        let dir =
+            std::env::temp_dir().join(format!("imitator-invalid-pack-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(MANIFEST_FILE), "{ not json").unwrap();
+        let error = TemplateSource::Directory(dir.clone()).load().err().unwrap();
+        assert!(matches!(error, CompileError::Config(_)));
+
+        fs::write(dir.join(MANIFEST_FILE), r#"{ "entry": "missing.ts" }"#).unwrap();
+        let pack = TemplateSource::Directory(dir.clone()).load().unwrap();
+        let error = pack.project_files(&Value::Null).err().unwrap();
+        assert!(matches!(error, CompileError::Config(_)));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,277 @@
+// Builders shared by the unit tests
+
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::diagnostics::Diagnostics;
+use crate::emitter::{emit_with, Emitter};
+use crate::{
+    analyze, compile, ActionType, ActionWithSelector, CompileOptions, Control, Emission, EmitMode,
+    EmitOptions, Recording,
+};
+
+// An action on `selector` with a working selector and nothing sensitive about it
+pub(crate) fn action(
+    action_type: ActionType,
+    control: Control,
+    selector: &str,
+    value: Option<&str>,
+) -> ActionWithSelector {
+    ActionWithSelector {
+        action_type,
+        rrweb_id: 1,
+        value: value.map(str::to_string),
+        checked: None,
+        control,
+        select_by_label: false,
+        timestamp: 1000,
+        event_index: 0,
+        url: None,
+        navigates: false,
+        page: 0,
+        selector: selector.to_string(),
+        strategy: None,
+        candidates: Vec::new(),
+        shadow_hosts: Vec::new(),
+        frames: Vec::new(),
+        redaction: None,
+        parameter: None,
+        masked: false,
+        description: "element".to_string(),
+    }
+}
+
+// What `emitter` writes for one action with a working locator, and what it reported
+pub(crate) fn emit_action(
+    emitter: &dyn Emitter,
+    action: &ActionWithSelector,
+) -> (String, Diagnostics) {
+    let fixture_files = HashMap::new();
+    let mut diagnostics = Diagnostics::default();
+    let code = emitter.action(
+        action,
+        None,
+        Emission::Locator,
+        &emit_options(&fixture_files),
+        &mut diagnostics,
+    );
+    (code, diagnostics)
+}
+
+// The action sequence `emitter` writes for `actions`, and what it reported
+pub(crate) fn emit_actions(
+    emitter: &dyn Emitter,
+    actions: &[ActionWithSelector],
+) -> (String, Diagnostics) {
+    let fixture_files = HashMap::new();
+    let mut diagnostics = Diagnostics::default();
+    let code = emit_with(
+        emitter,
+        actions,
+        &emit_options(&fixture_files),
+        &mut diagnostics,
+    )
+    .unwrap();
+    (code, diagnostics)
+}
+
+fn emit_options(fixture_files: &HashMap<String, PathBuf>) -> EmitOptions<'_> {
+    EmitOptions {
+        mode: EmitMode::Locator,
+        fixture_files,
+        cookie_banner: None,
+        start_url: "https://shop.test/",
+    }
+}
+
+// A document holding `body`'s children, with rrweb's node ids
+pub(crate) fn snapshot(timestamp: i64, body: Value) -> Value {
+    json!({"type": 2, "timestamp": timestamp, "data": {"node": {
+        "type": 0, "id": 1, "childNodes": [
+            {"type": 2, "id": 2, "tagName": "html", "attributes": {}, "childNodes": [
+                {"type": 2, "id": 3, "tagName": "body", "attributes": {}, "childNodes": body}
+            ]}
+        ]
+    }}})
+}
+
+pub(crate) fn meta(timestamp: i64, href: &str) -> Value {
+    json!({"type": 4, "timestamp": timestamp, "data": {"href": href, "width": 1280, "height": 720}})
+}
+
+pub(crate) fn click(timestamp: i64, id: i64) -> Value {
+    json!({"type": 3, "timestamp": timestamp, "data": {"source": 2, "type": 2, "id": id}})
+}
+
+pub(crate) fn input(timestamp: i64, id: i64, text: &str) -> Value {
+    json!({"type": 3, "timestamp": timestamp, "data": {"source": 5, "id": id, "text": text, "isChecked": false}})
+}
+
+pub(crate) fn element(id: i64, tag: &str, attributes: Value) -> Value {
+    json!({"type": 2, "id": id, "tagName": tag, "attributes": attributes, "childNodes": []})
+}
+
+// Both pages number their nodes from 1, so node 30 is a different element on each
+pub(crate) fn two_page_recording() -> Recording {
+    let events = json!([
+        meta(1000, "https://shop.test/cart"),
+        snapshot(
+            1001,
+            json!([
+                element(30, "a", json!({"id": "help", "href": "/help"})),
+                element(31, "input", json!({"id": "email", "type": "email"})),
+            ])
+        ),
+        input(1100, 31, "jane"),
+        click(1200, 30),
+        meta(2000, "https://shop.test/checkout"),
+        snapshot(2001, json!([element(30, "button", json!({"id": "pay"}))])),
+        click(2100, 30),
+    ]);
+    Recording::from_json("two-pages", &events.to_string()).unwrap()
+}
+
+pub(crate) fn check(timestamp: i64, id: i64, checked: bool) -> Value {
+    json!({"type": 3, "timestamp": timestamp, "data": {"source": 5, "id": id, "text": "on", "isChecked": checked}})
+}
+
+fn text(id: i64, content: &str) -> Value {
+    json!({"type": 3, "id": id, "textContent": content})
+}
+
+// A cart page with a dropdown, a checkbox and a checkout button in a shop-cart shadow root,
+// then a payment page with a card holder field in a same-origin iframe
+pub(crate) fn shop_recording() -> Recording {
+    let country = json!({"type": 2, "id": 10, "tagName": "select",
+    "attributes": {"id": "country", "name": "country"}, "childNodes": [
+        {"type": 2, "id": 11, "tagName": "option", "attributes": {"value": "de"},
+         "childNodes": [text(12, "Germany")]},
+        {"type": 2, "id": 13, "tagName": "option", "attributes": {"value": "fr"},
+         "childNodes": [text(14, "France")]},
+    ]});
+    let cart = json!({"type": 2, "id": 20, "tagName": "shop-cart", "attributes": {},
+    "isShadowHost": true, "childNodes": [
+        {"type": 2, "id": 21, "tagName": "button", "attributes": {"id": "checkout"},
+         "isShadow": true, "childNodes": [text(22, "Checkout")]},
+    ]});
+    let card_frame = json!({"type": 2, "id": 30, "tagName": "iframe",
+    "attributes": {"id": "card", "src": "/card"}, "childNodes": [
+        {"type": 0, "id": 31, "childNodes": [
+            {"type": 2, "id": 32, "tagName": "html", "attributes": {}, "childNodes": [
+                {"type": 2, "id": 33, "tagName": "body", "attributes": {}, "childNodes": [
+                    element(34, "input", json!({"id": "holder", "name": "holder"})),
+                ]},
+            ]},
+        ]},
+    ]});
+    let pay = json!({"type": 2, "id": 40, "tagName": "button", "attributes": {"id": "pay"},
+        "childNodes": [text(41, "Pay")]});
+
+    let events = json!([
+        meta(1000, "https://shop.test/cart"),
+        snapshot(
+            1001,
+            json!([
+                country,
+                element(
+                    15,
+                    "input",
+                    json!({"id": "terms", "name": "terms", "type": "checkbox"})
+                ),
+                cart,
+            ])
+        ),
+        input(1100, 10, "fr"),
+        check(1200, 15, true),
+        click(1300, 21),
+        meta(2000, "https://shop.test/payment"),
+        snapshot(2001, json!([card_frame, pay])),
+        input(2100, 34, "Jane Doe"),
+        click(2200, 40),
+    ]);
+    Recording::from_json("shop", &events.to_string()).unwrap()
+}
+
+// The action sequence `emitter` writes for a whole recording, with the selectors it prefers
+pub(crate) fn emit_recording(emitter: &dyn Emitter, recording: &Recording) -> String {
+    let options = CompileOptions {
+        target: Some(emitter.name().to_string()),
+        ..CompileOptions::default()
+    };
+    let mut analysis = analyze(recording, &options).unwrap();
+    let fixture_files = HashMap::new();
+    emit_with(
+        emitter,
+        &analysis.actions_with_selectors,
+        &EmitOptions {
+            start_url: &analysis.initial_url,
+            ..emit_options(&fixture_files)
+        },
+        &mut analysis.diagnostics,
+    )
+    .unwrap()
+}
+
+// Compare `actual` with testdata/golden/`name`. Run with UPDATE_GOLDEN=1 to rewrite the file
+// after a deliberate change to the output.
+pub(crate) fn assert_golden(name: &str, actual: &str) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("testdata/golden")
+        .join(name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("Could not read {}: {}", path.display(), e));
+    assert!(
+        actual == expected,
+        "{} differs from the emitted code:\n{}",
+        path.display(),
+        actual
+    );
+}
+
+// The recording `target` writes for `recording`, read back from the compiled project's entry
+pub(crate) fn round_trip(recording: &Recording, target: &str) -> Recording {
+    let options = CompileOptions {
+        target: Some(target.to_string()),
+        ..CompileOptions::default()
+    };
+    let project = compile(recording, &options).unwrap();
+    let entry = project
+        .files
+        .iter()
+        .find(|file| file.path == project.entry)
+        .unwrap();
+    Recording::from_json(target, &String::from_utf8_lossy(&entry.contents)).unwrap()
+}
+
+// What each imported action replays, for comparing imports of the same steps
+pub(crate) fn replayed_steps(recording: &Recording) -> Vec<String> {
+    let analysis = analyze(recording, &CompileOptions::default()).unwrap();
+    assert!(
+        analysis.diagnostics.is_empty(),
+        "{:?}",
+        analysis.diagnostics
+    );
+    analysis
+        .actions_with_selectors
+        .iter()
+        .map(|action| {
+            format!(
+                "{:?} {:?} {} {:?} checked={:?} by_label={} navigates={}",
+                action.action_type,
+                action.frames,
+                action.selector,
+                action.value,
+                action.checked,
+                action.select_by_label,
+                action.navigates
+            )
+        })
+        .collect()
+}
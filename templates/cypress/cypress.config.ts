@@ -0,0 +1,28 @@
+import "dotenv/config";
+import { defineConfig } from "cypress";
+
+// See https://docs.cypress.io/app/references/configuration
+export default defineConfig({
+  e2e: {
+    // Where the recording started; the spec visits paths relative to it
+    baseUrl: "{{base_url|js}}",
+    specPattern: "cypress/e2e/**/*.cy.ts",
+    supportFile: false,
+    // Upload fixtures are kept in fixtures/ rather than cypress/fixtures
+    fixturesFolder: "fixtures",
+    // The viewport the recording was made in
+    viewportWidth: {{viewport.width}},
+    viewportHeight: {{viewport.height}},
+  },
+{{#if parameters}}
+{{#if params_file}}
+{{else}}
+  // Recording parameters from .env or the environment, read in the spec with Cypress.env()
+  env: {
+{{#each parameters}}
+    {{env_var}}: process.env.{{env_var}},
+{{/each}}
+  },
+{{/if}}
+{{/if}}
+});
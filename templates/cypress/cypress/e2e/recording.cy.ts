@@ -0,0 +1,8 @@
+it("{{test_name|js}}", () => {
+  // Visit the recording's starting URL (relative to baseUrl in cypress.config.ts)
+  cy.visit("{{start_path|js}}");
+
+  // --- Generated Action Sequence Start ---
+  {{action_sequence}}
+  // --- Generated Action Sequence End ---
+});
@@ -0,0 +1,5 @@
+Feature: {{test_name}}
+  Replays the browser session recorded{{#if recording_file}} in {{recording_file}}{{/if}}, starting at {{start_url}}.
+
+  {{#if parameters}}Scenario Outline{{else}}Scenario{{/if}}: {{test_name}}
+    {{action_sequence}}
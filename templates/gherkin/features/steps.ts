@@ -0,0 +1,134 @@
+import { type Page } from "@playwright/test";
+import { createBdd } from "playwright-bdd";
+
+// Step definitions for the steps imitator writes. Named elements are found the way a user
+// finds them: by role and accessible name, label, placeholder or visible text. Elements the
+// recording had no name for are found by their recorded selector ("... at '#menu'").
+const { Given, When, Then } = createBdd();
+
+// Step arguments write backslashes as \\ and line breaks as \n; Cucumber already unescaped
+// the quotes
+const text = (value: string) => value.replace(/\\([\\n])/g, (_, c: string) => (c === "n" ? "\n" : "\\"));
+
+// Fallback for controls named by their name attribute only
+const byName = (page: Page, name: string) => page.locator(`[name="${name.replace(/"/g, '\\"')}"]`);
+
+// A form control by its label, placeholder, title or name attribute
+const control = (page: Page, name: string) =>
+  page
+    .getByLabel(name, { exact: true })
+    .or(page.getByPlaceholder(name, { exact: true }))
+    .or(page.getByTitle(name, { exact: true }))
+    .or(byName(page, name))
+    .first();
+
+Given("I am on {string}", async ({ page }, url: string) => {
+  await page.goto(text(url));
+});
+
+Given("I dismiss any cookie banner matching {string}", async ({ page }, pattern: string) => {
+  // The banner may not be shown at all, so this is the one step allowed to fail
+  await page
+    .getByRole("button", { name: new RegExp(text(pattern), "i") })
+    .first()
+    .click({ timeout: 5000 })
+    .catch(() => {});
+});
+
+When("I click the {string} button", async ({ page }, name: string) => {
+  await page.getByRole("button", { name: text(name), exact: true }).or(byName(page, text(name))).first().click();
+});
+
+When("I click the {string} link", async ({ page }, name: string) => {
+  await page.getByRole("link", { name: text(name), exact: true }).first().click();
+});
+
+When("I click the {string} checkbox", async ({ page }, name: string) => {
+  await page.getByRole("checkbox", { name: text(name), exact: true }).or(byName(page, text(name))).first().click();
+});
+
+When("I click the {string} radio button", async ({ page }, name: string) => {
+  await page.getByRole("radio", { name: text(name), exact: true }).or(byName(page, text(name))).first().click();
+});
+
+When("I click {string}", async ({ page }, name: string) => {
+  await page
+    .getByText(text(name), { exact: true })
+    .or(page.getByLabel(text(name), { exact: true }))
+    .or(page.getByTitle(text(name), { exact: true }))
+    .or(byName(page, text(name)))
+    .first()
+    .click();
+});
+
+When("I click the element at {string}", async ({ page }, selector: string) => {
+  await page.locator(text(selector)).click();
+});
+
+When("I fill {string} with {string}", async ({ page }, name: string, value: string) => {
+  await control(page, text(name)).fill(text(value));
+});
+
+When("I fill the field at {string} with {string}", async ({ page }, selector: string, value: string) => {
+  await page.locator(text(selector)).fill(text(value));
+});
+
+When("I select {string} from {string}", async ({ page }, value: string, name: string) => {
+  await control(page, text(name)).selectOption(text(value));
+});
+
+When("I select {string} from the dropdown at {string}", async ({ page }, value: string, selector: string) => {
+  await page.locator(text(selector)).selectOption(text(value));
+});
+
+When("I check {string}", async ({ page }, name: string) => {
+  await page.getByRole("checkbox", { name: text(name), exact: true }).or(byName(page, text(name))).first().check();
+});
+
+When("I check the checkbox at {string}", async ({ page }, selector: string) => {
+  await page.locator(text(selector)).check();
+});
+
+When("I uncheck {string}", async ({ page }, name: string) => {
+  await page.getByRole("checkbox", { name: text(name), exact: true }).or(byName(page, text(name))).first().uncheck();
+});
+
+When("I uncheck the checkbox at {string}", async ({ page }, selector: string) => {
+  await page.locator(text(selector)).uncheck();
+});
+
+When("I choose {string}", async ({ page }, name: string) => {
+  await page.getByRole("radio", { name: text(name), exact: true }).or(byName(page, text(name))).first().check();
+});
+
+When("I choose the radio button at {string}", async ({ page }, selector: string) => {
+  await page.locator(text(selector)).check();
+});
+
+When("I type {string} into {string}", async ({ page }, value: string, name: string) => {
+  await control(page, text(name)).pressSequentially(text(value));
+});
+
+When("I type {string} into the editor at {string}", async ({ page }, value: string, selector: string) => {
+  await page.locator(text(selector)).pressSequentially(text(value));
+});
+
+When("I upload {string} to {string}", async ({ page }, path: string, name: string) => {
+  await control(page, text(name)).setInputFiles(text(path));
+});
+
+When("I upload {string} to the file input at {string}", async ({ page }, path: string, selector: string) => {
+  await page.locator(text(selector)).setInputFiles(text(path));
+});
+
+When("I press {string} in {string}", async ({ page }, key: string, name: string) => {
+  await control(page, text(name)).press(text(key));
+});
+
+When("I press {string} in the element at {string}", async ({ page }, key: string, selector: string) => {
+  await page.locator(text(selector)).press(text(key));
+});
+
+Then("a new page loads", async ({ page }) => {
+  await page.waitForLoadState("load");
+});
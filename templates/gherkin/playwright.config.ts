@@ -0,0 +1,28 @@
+import { defineConfig, devices } from "@playwright/test";
+import { defineBddConfig } from "playwright-bdd";
+
+// Features are turned into Playwright tests by `bddgen`; see https://vitalets.github.io/playwright-bdd
+const testDir = defineBddConfig({
+  features: "features/*.feature",
+  steps: "features/steps.ts",
+});
+
+export default defineConfig({
+  testDir,
+  forbidOnly: !!process.env.CI,
+  retries: process.env.CI ? 2 : 0,
+  reporter: "html",
+  use: {
+    trace: "on-first-retry",
+  },
+  projects: [
+    {
+      name: "chromium",
+      use: {
+        ...devices["Desktop Chrome"],
+        // The viewport the recording was made in
+        viewport: { width: {{viewport.width}}, height: {{viewport.height}} },
+      },
+    },
+  ],
+});
@@ -27,17 +27,17 @@ async function main({
   context: BrowserContext; // Playwright BrowserContext
   stagehand: Stagehand; // Stagehand instance
 }) {
-  // Navigate to the starting URL (placeholder)
-  await page.goto("__START_URL__");
+  // Navigate to the recording's starting URL
+  await page.goto("{{start_url|js}}");
 
   // --- Generated Action Sequence Start ---
-  // __ACTION_SEQUENCE__
+  {{action_sequence}}
   // --- Generated Action Sequence End ---
 
   // Optional: Add final checks or logging if needed after the sequence
   stagehand.log({
     category: "generated-script",
-    message: `Finished executing generated action sequence.`,
+    message: `Finished executing generated action sequence for {{test_name|js}}.`,
   });
 
   // Example: Log final metrics
@@ -8,9 +8,9 @@ const StagehandConfig: ConstructorParams = {
   domSettleTimeoutMs: 30_000 /* Timeout for DOM to settle in milliseconds */,
 
   // LLM configuration
-  modelName: "google/gemini-2.0-flash" /* Name of the model to use */,
+  modelName: "{{llm.model|js}}" /* Name of the model to use */,
   modelClientOptions: {
-    apiKey: process.env.GOOGLE_API_KEY,
+    apiKey: process.env.{{llm.api_key_env}},
   } /* Configuration options for the model client */,
 
   // Browser configuration
@@ -24,15 +24,15 @@ const StagehandConfig: ConstructorParams = {
     browserSettings: {
       blockAds: true,
       viewport: {
-        width: 1024,
-        height: 768,
+        width: {{viewport.width}},
+        height: {{viewport.height}},
       },
     },
   },
   localBrowserLaunchOptions: {
     viewport: {
-      width: 1024,
-      height: 768,
+      width: {{viewport.width}},
+      height: {{viewport.height}},
     },
   } /* Configuration options for the local browser */,
 };
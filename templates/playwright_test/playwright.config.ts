@@ -0,0 +1,25 @@
+import "dotenv/config";
+import { defineConfig, devices } from "@playwright/test";
+
+// See https://playwright.dev/docs/test-configuration
+export default defineConfig({
+  testDir: "./tests",
+  forbidOnly: !!process.env.CI,
+  retries: process.env.CI ? 2 : 0,
+  reporter: "html",
+  use: {
+    // Where the recording started; the spec navigates relative to it
+    baseURL: "{{base_url|js}}",
+    trace: "on-first-retry",
+  },
+  projects: [
+    {
+      name: "chromium",
+      use: {
+        ...devices["Desktop Chrome"],
+        // The viewport the recording was made in
+        viewport: { width: {{viewport.width}}, height: {{viewport.height}} },
+      },
+    },
+  ],
+});
@@ -0,0 +1,10 @@
+import { test, expect } from "@playwright/test";
+
+test("{{test_name|js}}", async ({ page }) => {
+  // Navigate to the recording's starting URL (relative to baseURL in playwright.config.ts)
+  await page.goto("{{start_path|js}}");
+
+  // --- Generated Action Sequence Start ---
+  {{action_sequence}}
+  // --- Generated Action Sequence End ---
+});
@@ -0,0 +1,30 @@
+// Node runs this file directly, stripping the TypeScript types
+import puppeteer, { type ElementHandle } from "puppeteer";
+
+// Recording parameters may be set in .env
+try {
+  process.loadEnvFile();
+} catch {
+  // No .env file
+}
+
+const browser = await puppeteer.launch({
+  headless: process.env.HEADLESS !== "0",
+  // The viewport the recording was made in
+  defaultViewport: { width: {{viewport.width}}, height: {{viewport.height}} },
+});
+
+try {
+  const page = await browser.newPage();
+
+  // Navigate to the recording's starting URL
+  await page.goto("{{start_url|js}}");
+
+  // --- Generated Action Sequence Start ---
+  {{action_sequence}}
+  // --- Generated Action Sequence End ---
+
+  console.log("Replayed {{test_name|js}}");
+} finally {
+  await browser.close();
+}
@@ -0,0 +1,21 @@
+import os
+
+import pytest
+from dotenv import load_dotenv
+from selenium import webdriver
+
+# Recording parameters and HEADLESS may be set in .env
+load_dotenv()
+
+
+@pytest.fixture
+def driver():
+    options = webdriver.ChromeOptions()
+    if os.environ.get("HEADLESS", "1") != "0":
+        options.add_argument("--headless=new")
+    # Selenium Manager downloads a matching chromedriver on first use
+    driver = webdriver.Chrome(options=options)
+    # The viewport the recording was made in
+    driver.set_window_size({{viewport.width}}, {{viewport.height}})
+    yield driver
+    driver.quit()
@@ -0,0 +1,19 @@
+import os
+import re
+
+from selenium.webdriver.common.by import By
+from selenium.webdriver.common.keys import Keys
+from selenium.webdriver.support import expected_conditions as EC
+from selenium.webdriver.support.ui import Select, WebDriverWait
+
+
+# Replays {{test_name}}
+def test_recording(driver):
+    wait = WebDriverWait(driver, 10)
+
+    # Navigate to the recording's starting URL
+    driver.get("{{start_url|py}}")
+
+    # --- Generated Action Sequence Start ---
+    {{action_sequence}}
+    # --- Generated Action Sequence End ---
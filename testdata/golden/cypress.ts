@@ -0,0 +1,19 @@
+  // Timestamp: 1100
+  // Action: Input, Selector: '#country', Value: 'fr'
+  cy.get("#country").select("fr");
+
+  // Timestamp: 1200
+  // Action: Input, Selector: '#terms', Value: 'on'
+  cy.get("#terms").check();
+
+  // Timestamp: 1300
+  // Action: Click, Selector: 'shop-cart >> #checkout'
+  cy.get("shop-cart").shadow().find("#checkout").click();
+
+  // Timestamp: 2100
+  // Action: Input, Selector: '#holder', Value: 'Jane Doe'
+  cy.get("#card").its("0.contentDocument.body").should("not.be.empty").then(cy.wrap).find("#holder").clear().type("Jane Doe", { parseSpecialCharSequences: false });
+
+  // Timestamp: 2200
+  // Action: Click, Selector: '#pay'
+  cy.get("#pay").click();
\ No newline at end of file
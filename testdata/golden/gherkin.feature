@@ -0,0 +1,7 @@
+    Given I am on 'https://shop.test/cart'
+    When I select 'fr' from 'country'
+    And I check 'terms'
+    And I click the 'Checkout' button
+    Then a new page loads
+    # Left out, inside an iframe: Fill in the "holder" field
+    When I click the 'Pay' button
\ No newline at end of file
@@ -0,0 +1,34 @@
+  // Timestamp: 1100
+  // Action: Input, Selector: '#country', Value: 'fr'
+  await test.step("Fill in the \"country\" dropdown", async () => {
+    await page.locator("#country").selectOption("fr");
+    await expect(page.locator("#country")).toHaveValue("fr");
+  });
+
+  // Timestamp: 1200
+  // Action: Input, Selector: '#terms', Value: 'on'
+  await test.step("Fill in the \"terms\" checkbox", async () => {
+    await page.locator("#terms").check();
+    await expect(page.locator("#terms")).toBeChecked();
+  });
+
+  // Timestamp: 1300
+  // Action: Click, Selector: 'shop-cart >> #checkout'
+  await test.step("Click the \"Checkout\" button", async () => {
+    await expect(page.locator("shop-cart").locator("#checkout")).toBeVisible();
+    await page.locator("shop-cart").locator("#checkout").click();
+  });
+
+  // Timestamp: 2100
+  // Action: Input, Selector: '#holder', Value: 'Jane Doe'
+  await test.step("Fill in the \"holder\" field", async () => {
+    await page.frameLocator("#card").locator("#holder").fill("Jane Doe");
+    await expect(page.frameLocator("#card").locator("#holder")).toHaveValue("Jane Doe");
+  });
+
+  // Timestamp: 2200
+  // Action: Click, Selector: '#pay'
+  await test.step("Click the \"Pay\" button", async () => {
+    await expect(page.locator("#pay")).toBeVisible();
+    await page.locator("#pay").click();
+  });
\ No newline at end of file
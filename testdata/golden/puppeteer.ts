@@ -0,0 +1,33 @@
+  // Timestamp: 1100
+  // Action: Input, Selector: '#country', Value: 'fr'
+  await page.waitForSelector("#country");
+  await page.select("#country", "fr");
+
+  // Timestamp: 1200
+  // Action: Input, Selector: '#terms', Value: 'on'
+  await page.waitForSelector("#terms");
+  if ((await page.$eval("#terms", (element) => (element as HTMLInputElement).checked)) !== true) {
+    await page.click("#terms");
+  }
+
+  // Timestamp: 1300
+  // Action: Click, Selector: 'shop-cart >> #checkout'
+  await page.waitForSelector("shop-cart >>> #checkout");
+  await Promise.all([
+    page.waitForNavigation(),
+    page.click("shop-cart >>> #checkout"),
+  ]);
+
+  // Timestamp: 2100
+  // Action: Input, Selector: '#holder', Value: 'Jane Doe'
+  {
+    const frame = await (await page.waitForSelector("#card"))!.contentFrame();
+    await frame.waitForSelector("#holder");
+    await frame.$eval("#holder", (element) => ((element as HTMLInputElement).value = ""));
+    await frame.type("#holder", "Jane Doe");
+  }
+
+  // Timestamp: 2200
+  // Action: Click, Selector: '#pay'
+  await page.waitForSelector("#pay");
+  await page.click("#pay");
\ No newline at end of file
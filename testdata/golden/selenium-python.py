@@ -0,0 +1,25 @@
+    # Timestamp: 1100
+    # Action: Input, Selector: '#country', Value: 'fr'
+    Select(wait.until(EC.visibility_of_element_located((By.CSS_SELECTOR, "#country")))).select_by_value("fr")
+
+    # Timestamp: 1200
+    # Action: Input, Selector: '#terms', Value: 'on'
+    element = wait.until(EC.element_to_be_clickable((By.CSS_SELECTOR, "#terms")))
+    if not element.is_selected():
+        element.click()
+
+    # Timestamp: 1300
+    # Action: Click, Selector: 'shop-cart >> #checkout'
+    wait.until(lambda driver: driver.find_element(By.CSS_SELECTOR, "shop-cart").shadow_root.find_element(By.CSS_SELECTOR, "#checkout")).click()
+
+    # Timestamp: 2100
+    # Action: Input, Selector: '#holder', Value: 'Jane Doe'
+    wait.until(EC.frame_to_be_available_and_switch_to_it((By.CSS_SELECTOR, "#card")))
+    element = wait.until(EC.element_to_be_clickable((By.CSS_SELECTOR, "#holder")))
+    element.clear()
+    element.send_keys("Jane Doe")
+    driver.switch_to.default_content()
+
+    # Timestamp: 2200
+    # Action: Click, Selector: '#pay'
+    wait.until(EC.element_to_be_clickable((By.CSS_SELECTOR, "#pay"))).click()
\ No newline at end of file
@@ -0,0 +1,39 @@
+  // Timestamp: 1100
+  // Action: Input, Selector: '#country', Value: 'fr'
+  try {
+    await page.locator("#country").selectOption("fr");
+  } catch (error) {
+    console.warn("Action failed for selector [#country]:", (error as Error).message);
+  }
+
+  // Timestamp: 1200
+  // Action: Input, Selector: '#terms', Value: 'on'
+  try {
+    await page.locator("#terms").check();
+  } catch (error) {
+    console.warn("Action failed for selector [#terms]:", (error as Error).message);
+  }
+
+  // Timestamp: 1300
+  // Action: Click, Selector: 'shop-cart >> #checkout'
+  try {
+    await page.locator("shop-cart").locator("#checkout").click();
+  } catch (error) {
+    console.warn("Action failed for selector [shop-cart >> #checkout]:", (error as Error).message);
+  }
+
+  // Timestamp: 2100
+  // Action: Input, Selector: '#holder', Value: 'Jane Doe'
+  try {
+    await page.frameLocator("#card").locator("#holder").fill("Jane Doe");
+  } catch (error) {
+    console.warn("Action failed for selector [#holder]:", (error as Error).message);
+  }
+
+  // Timestamp: 2200
+  // Action: Click, Selector: '#pay'
+  try {
+    await page.locator("#pay").click();
+  } catch (error) {
+    console.warn("Action failed for selector [#pay]:", (error as Error).message);
+  }
\ No newline at end of file